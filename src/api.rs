@@ -2,6 +2,11 @@
 use http::Method;
 use serde::Serialize;
 
+pub mod encoding;
+
+#[doc(inline)]
+pub use encoding::{EncodedBody, Encoding, FilePart, encode_body};
+
 /// The [`ApiRequest`] trait lets each request type declare its own HTTP
 /// method and endpoint path, so the client knows how to send it.
 pub trait ApiRequest: Serialize {
@@ -14,4 +19,19 @@ pub trait ApiRequest: Serialize {
     ///
     /// - `"/email"` - Send an email
     const ENDPOINT: &'static str;
+
+    /// How the request body is encoded, JSON unless overridden
+    const ENCODING: Encoding = Encoding::Json;
+
+    /// Files sent as parts of a [`Encoding::Multipart`] body
+    fn files(&self) -> Vec<FilePart> {
+        Vec::new()
+    }
+
+    /// Key that lets the provider recognize retries of the same request
+    ///
+    /// Only sent by clients of providers that support idempotent requests.
+    fn idempotency_key(&self) -> Option<&str> {
+        None
+    }
 }
@@ -0,0 +1,336 @@
+//! Request body encodings
+//!
+//! Form encodings flatten the serialized request into fields sorted by name:
+//! strings, numbers and booleans become one field each, arrays repeat the
+//! field once per item, and `null` values are left out.
+use std::fmt::Write;
+use std::hash::{BuildHasher, RandomState};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bytes::Bytes;
+use serde_json::Value;
+
+use super::ApiRequest;
+use crate::error::Error;
+
+/// How an [`ApiRequest`] body is sent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// `application/json`
+    #[default]
+    Json,
+    /// `application/x-www-form-urlencoded`
+    FormUrlEncoded,
+    /// `multipart/form-data`, the only encoding that carries files
+    Multipart,
+}
+
+/// A file sent as a part of a multipart body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePart {
+    /// Name of the form field
+    pub field: String,
+    /// File name
+    pub filename: String,
+    /// MIME content type
+    pub content_type: String,
+    /// Raw file content
+    pub content: Bytes,
+}
+
+/// An encoded request body
+#[derive(Debug, Clone)]
+pub struct EncodedBody {
+    /// Value for the `content-type` header
+    pub content_type: String,
+    /// Body bytes
+    pub body: Bytes,
+}
+
+/// Encodes the request as declared by its [`ApiRequest::ENCODING`]
+pub fn encode_body<R: ApiRequest>(request: &R) -> Result<EncodedBody, Error> {
+    let files = request.files();
+    match R::ENCODING {
+        Encoding::Json if files.is_empty() => {
+            let body = serde_json::to_vec(request).map_err(|err| {
+                #[cfg(feature = "tracing")]
+                tracing::error!(?err);
+                Error::InvalidRequest(format!("failed to serialize email: {err}"))
+            })?;
+            Ok(EncodedBody {
+                content_type: "application/json".to_owned(),
+                body: Bytes::from(body),
+            })
+        }
+        Encoding::FormUrlEncoded if files.is_empty() => {
+            let body = form_fields(request)?
+                .iter()
+                .map(|(name, value)| format!("{}={}", url_encode(name), url_encode(value)))
+                .collect::<Vec<_>>()
+                .join("&");
+            Ok(EncodedBody {
+                content_type: "application/x-www-form-urlencoded".to_owned(),
+                body: Bytes::from(body),
+            })
+        }
+        Encoding::Multipart => encode_multipart(&form_fields(request)?, &files),
+        Encoding::Json | Encoding::FormUrlEncoded => Err(Error::InvalidRequest(
+            "files can only be sent in multipart requests".to_owned(),
+        )),
+    }
+}
+
+/// Flattens the serialized request into form fields
+fn form_fields<R: ApiRequest>(request: &R) -> Result<Vec<(String, String)>, Error> {
+    let value = serde_json::to_value(request).map_err(|err| {
+        #[cfg(feature = "tracing")]
+        tracing::error!(?err);
+        Error::InvalidRequest(format!("failed to serialize email: {err}"))
+    })?;
+    let Value::Object(object) = value else {
+        return Err(Error::InvalidRequest(
+            "form requests must serialize to a map".to_owned(),
+        ));
+    };
+
+    let mut fields = Vec::with_capacity(object.len());
+    for (name, value) in object {
+        let values = match value {
+            Value::Array(values) => values,
+            value => vec![value],
+        };
+        for value in values {
+            let value = match value {
+                Value::Null => continue,
+                Value::String(value) => value,
+                Value::Bool(value) => value.to_string(),
+                Value::Number(value) => value.to_string(),
+                Value::Array(_) | Value::Object(_) => {
+                    return Err(Error::InvalidRequest(format!(
+                        "form field {name} must be a scalar or a list of scalars"
+                    )));
+                }
+            };
+            fields.push((name.clone(), value));
+        }
+    }
+    Ok(fields)
+}
+
+/// Percent-encodes a form name or value, with spaces as `+`
+fn url_encode(input: &str) -> String {
+    input
+        .bytes()
+        .fold(String::with_capacity(input.len()), |mut output, byte| {
+            match byte {
+                b' ' => output.push('+'),
+                byte if byte.is_ascii_alphanumeric() || b"-_.*".contains(&byte) => {
+                    output.push(char::from(byte));
+                }
+                byte => {
+                    let _ = write!(output, "%{byte:02X}");
+                }
+            }
+            output
+        })
+}
+
+/// Builds a `multipart/form-data` body from fields and files
+fn encode_multipart(fields: &[(String, String)], files: &[FilePart]) -> Result<EncodedBody, Error> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_nanos());
+    let boundary = format!("sendout-{:016x}", RandomState::new().hash_one(nanos));
+
+    let mut body = Vec::new();
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{boundary}\r\ncontent-disposition: form-data; name=\"{}\"\r\n\r\n",
+                quote(name)
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(value.as_bytes());
+        body.extend_from_slice(b"\r\n");
+    }
+    for file in files {
+        if file.content_type.chars().any(char::is_control) {
+            return Err(Error::Attachment(format!(
+                "{} has an invalid content type",
+                file.filename
+            )));
+        }
+        if file
+            .content
+            .windows(boundary.len())
+            .any(|window| window == boundary.as_bytes())
+        {
+            return Err(Error::Attachment(format!(
+                "{} contains the multipart boundary",
+                file.filename
+            )));
+        }
+        body.extend_from_slice(
+            format!(
+                "--{boundary}\r\ncontent-disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n\
+                 content-type: {}\r\n\r\n",
+                quote(&file.field),
+                quote(&file.filename),
+                file.content_type
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(&file.content);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+
+    Ok(EncodedBody {
+        content_type: format!("multipart/form-data; boundary={boundary}"),
+        body: Bytes::from(body),
+    })
+}
+
+/// Escapes a name for a quoted `content-disposition` parameter
+fn quote(name: &str) -> String {
+    name.replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::matchers::{anything, contains_substring, eq, err, pat, starts_with};
+    use googletest::{expect_that, gtest};
+    use http::Method;
+    use serde::Serialize;
+
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Form {
+        to: Vec<&'static str>,
+        subject: &'static str,
+        tracking: bool,
+        text: Option<&'static str>,
+        #[serde(skip)]
+        files: Vec<FilePart>,
+    }
+
+    impl ApiRequest for Form {
+        const METHOD: Method = Method::POST;
+        const ENDPOINT: &'static str = "/messages";
+        const ENCODING: Encoding = Encoding::Multipart;
+
+        fn files(&self) -> Vec<FilePart> {
+            self.files.clone()
+        }
+    }
+
+    /// Same fields as [`Form`], URL-encoded
+    #[derive(Serialize)]
+    struct UrlEncodedForm(Form);
+
+    impl ApiRequest for UrlEncodedForm {
+        const METHOD: Method = Method::POST;
+        const ENDPOINT: &'static str = "/messages";
+        const ENCODING: Encoding = Encoding::FormUrlEncoded;
+
+        fn files(&self) -> Vec<FilePart> {
+            self.0.files.clone()
+        }
+    }
+
+    fn form() -> Form {
+        Form {
+            to: vec![
+                "kwame.nkrumah@example.africa",
+                "Julius <julius@example.africa>",
+            ],
+            subject: "Green Belt & friends",
+            tracking: false,
+            text: None,
+            files: Vec::new(),
+        }
+    }
+
+    #[gtest]
+    fn url_encodes_fields() {
+        let encoded = encode_body(&UrlEncodedForm(form())).expect("encoding to succeed");
+
+        expect_that!(
+            encoded.content_type,
+            eq("application/x-www-form-urlencoded")
+        );
+        expect_that!(
+            encoded.body,
+            eq(&Bytes::from_static(
+                b"subject=Green+Belt+%26+friends&to=kwame.nkrumah%40example.africa\
+                  &to=Julius+%3Cjulius%40example.africa%3E&tracking=false"
+            ))
+        );
+    }
+
+    #[gtest]
+    fn url_encoding_rejects_files() {
+        let mut form = form();
+        form.files.push(FilePart {
+            field: "attachment".to_owned(),
+            filename: "trees.csv".to_owned(),
+            content_type: "text/csv".to_owned(),
+            content: Bytes::from_static(b"kenya,10000"),
+        });
+        expect_that!(
+            encode_body(&UrlEncodedForm(form)),
+            err(pat!(Error::InvalidRequest(anything())))
+        );
+    }
+
+    #[gtest]
+    fn multipart_carries_fields_and_files() {
+        let mut form = form();
+        form.files.push(FilePart {
+            field: "attachment".to_owned(),
+            filename: "trees.csv".to_owned(),
+            content_type: "text/csv".to_owned(),
+            content: Bytes::from_static(b"kenya,10000"),
+        });
+        let encoded = encode_body(&form).expect("encoding to succeed");
+        let boundary = encoded
+            .content_type
+            .strip_prefix("multipart/form-data; boundary=")
+            .expect("a multipart content type")
+            .to_owned();
+        let body = String::from_utf8(encoded.body.to_vec()).expect("a UTF-8 body");
+
+        expect_that!(body, starts_with(format!("--{boundary}\r\n")));
+        expect_that!(
+            body,
+            contains_substring(
+                "content-disposition: form-data; name=\"to\"\r\n\r\nkwame.nkrumah@example.africa\r\n"
+            )
+        );
+        expect_that!(
+            body,
+            contains_substring(
+                "content-disposition: form-data; name=\"attachment\"; filename=\"trees.csv\"\r\n\
+                 content-type: text/csv\r\n\r\nkenya,10000\r\n"
+            )
+        );
+        expect_that!(body.ends_with(&format!("--{boundary}--\r\n")), eq(true));
+        expect_that!(body.matches(&format!("--{boundary}\r\n")).count(), eq(5));
+    }
+
+    #[gtest]
+    fn multipart_rejects_content_types_with_control_characters() {
+        let mut form = form();
+        form.files.push(FilePart {
+            field: "attachment".to_owned(),
+            filename: "trees.csv".to_owned(),
+            content_type: "text/csv\r\ncontent-disposition: form-data; name=\"to\"".to_owned(),
+            content: Bytes::from_static(b"kenya,10000"),
+        });
+        expect_that!(encode_body(&form), err(pat!(Error::Attachment(anything()))));
+    }
+}
@@ -0,0 +1,31 @@
+//! Synchronous counterparts of the email service traits
+//!
+//! For programs without an async runtime, such as command line tools and
+//! cron jobs. Blocking clients must not be used from inside an async runtime.
+use bytes::Bytes;
+use http::{Request, Response};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::error::Error;
+
+/// Trait for sending HTTP requests and waiting for the response
+pub trait Execute: Send + Sync {
+    /// Sends the request and returns a parsed response
+    ///
+    /// It returns an error if something goes wrong
+    fn execute<Req, Res>(&self, request: Req) -> Result<Res, Error>
+    where
+        Req: Into<Request<Bytes>>,
+        Res: TryFrom<Response<Bytes>, Error = Error>;
+}
+
+/// Trait for sending an email with a provider, blocking until it is accepted
+pub trait EmailService<Email, Response>: Send + Sync
+where
+    Email: Serialize,
+    Response: DeserializeOwned,
+{
+    /// Send an email
+    fn send_email(&self, email: Email) -> Result<Response, Error>;
+}
@@ -0,0 +1,95 @@
+//! Sources of the current time
+//!
+//! Time-dependent components read the time from a [`Clock`], so tests can
+//! drive them with the `ManualClock` of the `test-util` feature.
+use std::fmt;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Source of the current time
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Returns the current time
+    fn now(&self) -> SystemTime;
+}
+
+impl<C> Clock for Arc<C>
+where
+    C: Clock + ?Sized,
+{
+    fn now(&self) -> SystemTime {
+        (**self).now()
+    }
+}
+
+/// Clock reading the system time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+cfg_test_util! {
+    use std::sync::{Mutex, PoisonError};
+    use std::time::Duration;
+
+    /// Clock that only moves when told to
+    ///
+    /// Clones share the same time, so a test can keep one and hand the other
+    /// to the component under test.
+    #[derive(Debug, Clone)]
+    pub struct ManualClock {
+        /// The current time
+        now: Arc<Mutex<SystemTime>>,
+    }
+
+    impl ManualClock {
+        /// Creates a clock stopped at the given time
+        pub fn new(now: SystemTime) -> Self {
+            Self {
+                now: Arc::new(Mutex::new(now)),
+            }
+        }
+
+        /// Sets the current time
+        pub fn set(&self, now: SystemTime) {
+            *self.now.lock().unwrap_or_else(PoisonError::into_inner) = now;
+        }
+
+        /// Moves the current time forward
+        pub fn advance(&self, duration: Duration) {
+            *self.now.lock().unwrap_or_else(PoisonError::into_inner) += duration;
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now(&self) -> SystemTime {
+            *self.now.lock().unwrap_or_else(PoisonError::into_inner)
+        }
+    }
+}
+
+cfg_test! {
+    mod tests {
+        use std::time::UNIX_EPOCH;
+
+        use googletest::matchers::eq;
+        use googletest::{expect_that, gtest};
+
+        use super::*;
+
+        #[gtest]
+        fn manual_clock_moves_when_told_to() {
+            let clock = ManualClock::new(UNIX_EPOCH);
+            let shared = clock.clone();
+
+            clock.advance(Duration::from_secs(60));
+            expect_that!(shared.now(), eq(UNIX_EPOCH + Duration::from_secs(60)));
+
+            shared.set(UNIX_EPOCH);
+            expect_that!(clock.now(), eq(UNIX_EPOCH));
+        }
+    }
+}
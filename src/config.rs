@@ -13,7 +13,8 @@ pub struct ServiceConfig {
     pub base_url: String,
     /// Secret API token for authentication
     ///
-    /// Maps to the X-Postmark-Server-Token header in Postmark
+    /// Maps to the X-Postmark-Server-Token header in Postmark, and to the
+    /// bearer token in SendGrid
     pub server_token: SecretString,
     /// Token for requests that need account level privileges
     ///
@@ -21,7 +22,9 @@ pub struct ServiceConfig {
     pub account_token: Option<SecretString>,
     /// The verified sender email address
     ///
-    /// Make sure this email address is verified by your provider
+    /// Used for messages that don't set their own sender. It may include a
+    /// display name, as in `Jane Doe <jane@example.com>`. Make sure this
+    /// email address is verified by your provider
     pub from_email: String,
 }
 
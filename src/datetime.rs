@@ -0,0 +1,102 @@
+//! UTC calendar dates for timestamps written into messages and requests
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A point in time broken down into UTC calendar fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UtcDateTime {
+    /// Year, such as `2026`
+    pub year: u64,
+    /// Month, from `1` to `12`
+    pub month: u64,
+    /// Day of the month, from `1` to `31`
+    pub day: u64,
+    /// Hour, from `0` to `23`
+    pub hour: u64,
+    /// Minute, from `0` to `59`
+    pub minute: u64,
+    /// Second, from `0` to `59`
+    pub second: u64,
+    /// Day of the week, `0` being Monday
+    pub weekday: u64,
+}
+
+impl UtcDateTime {
+    /// Breaks a time down into calendar fields, clamping times before 1970
+    pub fn from_system_time(time: SystemTime) -> Self {
+        let secs = time
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        let days = secs / 86_400;
+        let (year, month, day) = civil_from_days(days);
+        let seconds_of_day = secs % 86_400;
+        Self {
+            year,
+            month,
+            day,
+            hour: seconds_of_day / 3_600,
+            minute: seconds_of_day % 3_600 / 60,
+            second: seconds_of_day % 60,
+            // The 1st of January 1970 was a Thursday
+            weekday: (days + 3) % 7,
+        }
+    }
+
+    /// Formats the time as ISO 8601, as in `2026-10-16T08:30:00Z`
+    #[cfg(any(
+        feature = "mailgun",
+        feature = "resend",
+        feature = "sendgrid",
+        feature = "ses",
+        feature = "smtp",
+        feature = "test-util"
+    ))]
+    pub fn to_iso8601(self) -> String {
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+/// Converts days since the Unix epoch to a `(year, month, day)` date
+///
+/// See Howard Hinnant's `civil_from_days` algorithm.
+const fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use googletest::matchers::eq;
+    use googletest::{expect_that, gtest};
+
+    use super::*;
+
+    #[gtest]
+    fn breaks_down_times_including_leap_days() {
+        let leap_day = UtcDateTime::from_system_time(UNIX_EPOCH + Duration::from_secs(951_782_400));
+        expect_that!(
+            (leap_day.year, leap_day.month, leap_day.day),
+            eq((2000, 2, 29))
+        );
+        expect_that!(leap_day.weekday, eq(1));
+
+        let time = UtcDateTime::from_system_time(UNIX_EPOCH + Duration::from_secs(1_792_148_400));
+        expect_that!(
+            (time.year, time.month, time.day, time.hour),
+            eq((2026, 10, 16, 11))
+        );
+        expect_that!(time.weekday, eq(4));
+    }
+}
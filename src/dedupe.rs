@@ -0,0 +1,250 @@
+//! Deduplication of emails sent more than once
+//!
+//! Job runners may retry a job after its email already went out. Emails
+//! carrying an [`EmailMessage::idempotency_key`] are sent once per key within
+//! a window, and later sends get the original [`EmailDelivery`] back.
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+
+pub mod file;
+pub mod memory;
+
+#[doc(inline)]
+pub use file::FileDedupeStore;
+#[doc(inline)]
+pub use memory::MemoryDedupeStore;
+
+use crate::clock::{Clock, SystemClock};
+use crate::email::{EmailDelivery, EmailMessage};
+use crate::error::Error;
+use crate::service::EmailService;
+
+/// Storage for the deliveries of emails sent with an idempotency key
+#[async_trait]
+pub trait DedupeStore: Send + Sync {
+    /// Returns the delivery recorded for the key, unless it expired at `now`
+    async fn get(&self, key: &str, now: SystemTime) -> Result<Option<EmailDelivery>, Error>;
+
+    /// Records the delivery of the email sent with the key until `expires_at`
+    async fn insert(
+        &self,
+        key: &str,
+        delivery: EmailDelivery,
+        expires_at: SystemTime,
+    ) -> Result<(), Error>;
+}
+
+/// Email service that sends each idempotency key only once within a window
+///
+/// Emails without an idempotency key are always sent. Failed sends are not
+/// recorded, so they can be retried. Two sends with the same key running at
+/// the same time may both go out; the key is still forwarded to providers
+/// that deduplicate requests themselves.
+#[derive(Debug)]
+pub struct Deduplicate<S, D> {
+    /// The wrapped service
+    inner: S,
+    /// Where deliveries are recorded
+    store: D,
+    /// How long a delivery is remembered
+    window: Duration,
+    /// Source of the current time
+    clock: Arc<dyn Clock>,
+}
+
+impl<S, D> Deduplicate<S, D> {
+    /// Window used unless [`Deduplicate::with_window`] sets another one
+    pub const DEFAULT_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+    /// Longest window, longer ones are shortened to it
+    pub const MAX_WINDOW: Duration = Duration::from_secs(10 * 365 * 24 * 60 * 60);
+
+    /// Wraps the service, recording deliveries in the store
+    pub fn new(inner: S, store: D) -> Self {
+        Self {
+            inner,
+            store,
+            window: Self::DEFAULT_WINDOW,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Sets how long a delivery is remembered
+    ///
+    /// Windows over [`Deduplicate::MAX_WINDOW`] are shortened to it.
+    #[must_use]
+    pub const fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Sets the clock deciding when deliveries expire
+    #[must_use]
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Returns a reference to the wrapped service
+    pub const fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Returns the store of deliveries
+    pub const fn store(&self) -> &D {
+        &self.store
+    }
+}
+
+#[async_trait]
+impl<S, D> EmailService<EmailMessage, EmailDelivery> for Deduplicate<S, D>
+where
+    S: EmailService<EmailMessage, EmailDelivery>,
+    D: DedupeStore,
+{
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Deduplicate::send_email", skip(self, email))
+    )]
+    async fn send_email(&self, email: EmailMessage) -> Result<EmailDelivery, Error> {
+        let Some(key) = email.idempotency_key.clone() else {
+            return self.inner.send_email(email).await;
+        };
+        if let Some(delivery) = self.store.get(&key, self.clock.now()).await? {
+            #[cfg(feature = "tracing")]
+            tracing::info!(%key, message_id = %delivery.message_id, "skipping duplicate email");
+            return Ok(delivery);
+        }
+
+        let delivery = self.inner.send_email(email).await?;
+        let now = self.clock.now();
+        let expires_at = now
+            .checked_add(self.window.min(Self::MAX_WINDOW))
+            .unwrap_or(now);
+        // The email is out, so failing here would only invite a duplicate
+        if let Err(_err) = self.store.insert(&key, delivery.clone(), expires_at).await {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?_err, %key, "failed to record delivery");
+        }
+        Ok(delivery)
+    }
+}
+
+cfg_test! {
+    mod tests {
+        use std::time::UNIX_EPOCH;
+
+        use googletest::matchers::{anything, eq, pat};
+        use googletest::{expect_that, gtest};
+
+        use super::*;
+        use crate::clock::ManualClock;
+        use crate::fixtures::{Scripted, email_message};
+
+        fn receipt(idempotency_key: Option<&str>) -> EmailMessage {
+            EmailMessage {
+                subject: "Your receipt".to_owned(),
+                idempotency_key: idempotency_key.map(str::to_owned),
+                ..email_message()
+            }
+        }
+
+        fn deduplicate(service: &Scripted) -> (Deduplicate<Scripted, MemoryDedupeStore>, ManualClock) {
+            let clock = ManualClock::new(UNIX_EPOCH + Duration::from_secs(1_791_968_400));
+            let deduplicate = Deduplicate::new(service.clone(), MemoryDedupeStore::new())
+                .with_window(Duration::from_secs(3600))
+                .with_clock(clock.clone());
+            (deduplicate, clock)
+        }
+
+        #[tokio::test]
+        #[gtest]
+        async fn returns_the_original_delivery_within_the_window() {
+            let service = Scripted::default();
+            let (deduplicate, clock) = deduplicate(&service);
+
+            let first = deduplicate
+                .send_email(receipt(Some("receipt/42")))
+                .await
+                .expect("email to be sent");
+            clock.advance(Duration::from_secs(3599));
+            let second = deduplicate
+                .send_email(receipt(Some("receipt/42")))
+                .await
+                .expect("email to be deduplicated");
+
+            expect_that!(second.message_id, eq(&first.message_id));
+            expect_that!(service.calls(), eq(1));
+        }
+
+        #[tokio::test]
+        #[gtest]
+        async fn sends_again_once_the_window_is_over() {
+            let service = Scripted::default();
+            let (deduplicate, clock) = deduplicate(&service);
+
+            deduplicate
+                .send_email(receipt(Some("receipt/42")))
+                .await
+                .expect("email to be sent");
+            clock.advance(Duration::from_secs(3600));
+            let delivery = deduplicate
+                .send_email(receipt(Some("receipt/42")))
+                .await
+                .expect("email to be sent");
+
+            expect_that!(delivery.message_id, eq("message-2"));
+        }
+
+        #[tokio::test]
+        #[gtest]
+        async fn huge_windows_remember_deliveries() {
+            let service = Scripted::default();
+            let (deduplicate, clock) = deduplicate(&service);
+            let deduplicate = deduplicate.with_window(Duration::MAX);
+
+            for _ in 0..2 {
+                deduplicate
+                    .send_email(receipt(Some("receipt/42")))
+                    .await
+                    .expect("email to be sent");
+                clock.advance(Duration::from_secs(365 * 24 * 60 * 60));
+            }
+
+            expect_that!(service.calls(), eq(1));
+        }
+
+        #[tokio::test]
+        #[gtest]
+        async fn emails_without_a_key_are_always_sent() {
+            let service = Scripted::default();
+            let (deduplicate, _clock) = deduplicate(&service);
+
+            for _ in 0..2 {
+                deduplicate
+                    .send_email(receipt(None))
+                    .await
+                    .expect("email to be sent");
+            }
+
+            expect_that!(service.calls(), eq(2));
+        }
+
+        #[tokio::test]
+        #[gtest]
+        async fn failed_sends_are_not_recorded() {
+            let service = Scripted::new([Error::SendFailed("connection failed".to_owned())]);
+            let (deduplicate, _clock) = deduplicate(&service);
+
+            let result = deduplicate.send_email(receipt(Some("receipt/42"))).await;
+            expect_that!(result, pat!(Err(pat!(Error::SendFailed(anything())))));
+
+            let delivery = deduplicate
+                .send_email(receipt(Some("receipt/42")))
+                .await
+                .expect("email to be sent");
+            expect_that!(delivery.message_id, eq("message-2"));
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! Deliveries remembered as JSON files in a directory
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::DedupeStore;
+use crate::email::EmailDelivery;
+use crate::error::Error;
+use crate::json_store;
+
+/// A delivery and its expiry, as stored on disk
+#[derive(Debug, Serialize, Deserialize)]
+struct Record {
+    /// The idempotency key the delivery was recorded for
+    key: String,
+    /// The recorded delivery
+    delivery: EmailDelivery,
+    /// When the delivery is forgotten
+    expires_at: SystemTime,
+}
+
+/// Store keeping one JSON file per idempotency key
+///
+/// File names are the hex-encoded SHA-256 hashes of the keys, so keys of
+/// any length fit, and each record keeps its original key. Expired files are
+/// removed when looked up, or all at once by [`FileDedupeStore::purge`].
+/// Files that can't be parsed are renamed with a `.corrupt` extension and
+/// treated as missing, so the email is sent again rather than failing.
+#[derive(Debug, Clone)]
+pub struct FileDedupeStore {
+    /// Directory holding the records
+    dir: PathBuf,
+}
+
+impl FileDedupeStore {
+    /// Opens the store in the directory, creating it if needed
+    pub async fn open(dir: impl Into<PathBuf>) -> Result<Self, Error> {
+        let dir = dir.into();
+        json_store::create_dir(&dir).await?;
+        Ok(Self { dir })
+    }
+
+    /// Returns the directory of the store
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Removes the records expired at `now` and returns how many there were
+    pub async fn purge(&self, now: SystemTime) -> Result<usize, Error> {
+        let mut purged = 0;
+        for (path, record) in json_store::read_all::<Record>(&self.dir).await? {
+            if record.expires_at <= now {
+                json_store::remove(&path).await?;
+                purged += 1;
+            }
+        }
+        Ok(purged)
+    }
+
+    /// Returns the path of the record for the key
+    fn path(&self, key: &str) -> PathBuf {
+        json_store::path(&self.dir, key)
+    }
+}
+
+#[async_trait]
+impl DedupeStore for FileDedupeStore {
+    async fn get(&self, key: &str, now: SystemTime) -> Result<Option<EmailDelivery>, Error> {
+        let path = self.path(key);
+        match json_store::read::<Record>(&path).await? {
+            // Another key with the same hash is treated as unknown
+            Some(record) if record.key != key => Ok(None),
+            Some(record) if record.expires_at > now => Ok(Some(record.delivery)),
+            Some(_expired) => json_store::remove(&path).await.map(|()| None),
+            None => Ok(None),
+        }
+    }
+
+    async fn insert(
+        &self,
+        key: &str,
+        delivery: EmailDelivery,
+        expires_at: SystemTime,
+    ) -> Result<(), Error> {
+        let record = Record {
+            key: key.to_owned(),
+            delivery,
+            expires_at,
+        };
+        json_store::write(&self.path(key), &record).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use googletest::matchers::{eq, none, pat, some};
+    use googletest::{expect_that, gtest};
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::fixtures::temp_dir;
+
+    /// Opens a store in a new temporary directory, removed once dropped
+    async fn temp_store() -> (TempDir, FileDedupeStore) {
+        let dir = temp_dir();
+        let store = FileDedupeStore::open(dir.path())
+            .await
+            .expect("store to open");
+        (dir, store)
+    }
+
+    fn delivery() -> EmailDelivery {
+        EmailDelivery {
+            to: "kwame.nkrumah@example.africa".to_owned(),
+            submitted_at: "2026-10-16T08:30:00Z".to_owned(),
+            message_id: "message-1".to_owned(),
+            error_code: 0,
+            message: "OK".to_owned(),
+            provider: Some("postmark".to_owned()),
+        }
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn returns_deliveries_until_they_expire() {
+        let (_dir, store) = temp_store().await;
+        let now = UNIX_EPOCH + Duration::from_secs(1_791_968_400);
+        store
+            .insert("receipt/42", delivery(), now + Duration::from_secs(60))
+            .await
+            .expect("delivery to be recorded");
+
+        let found = store
+            .get("receipt/42", now)
+            .await
+            .expect("store to be read");
+        expect_that!(
+            found.map(|delivery| (delivery.message_id, delivery.provider)),
+            some(eq(&("message-1".to_owned(), Some("postmark".to_owned()))))
+        );
+        expect_that!(
+            store.get("receipt/42", now + Duration::from_secs(60)).await,
+            pat!(Ok(none()))
+        );
+        expect_that!(store.get("receipt/43", now).await, pat!(Ok(none())));
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn stores_keys_of_the_maximum_length() {
+        let (_dir, store) = temp_store().await;
+        let now = UNIX_EPOCH + Duration::from_secs(1_791_968_400);
+        let key = "k".repeat(256);
+        store
+            .insert(&key, delivery(), now + Duration::from_secs(60))
+            .await
+            .expect("delivery to be recorded");
+
+        expect_that!(
+            store.get(&key, now).await,
+            pat!(Ok(some(pat!(EmailDelivery { .. }))))
+        );
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn purges_expired_records() {
+        let (_dir, store) = temp_store().await;
+        let now = UNIX_EPOCH + Duration::from_secs(1_791_968_400);
+        for (key, ttl) in [("expired", 0), ("current", 60)] {
+            store
+                .insert(key, delivery(), now + Duration::from_secs(ttl))
+                .await
+                .expect("delivery to be recorded");
+        }
+
+        expect_that!(store.purge(now).await, pat!(Ok(eq(&1))));
+        expect_that!(
+            store.get("current", now).await,
+            pat!(Ok(some(pat!(EmailDelivery { .. }))))
+        );
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn quarantines_records_that_cannot_be_parsed() {
+        let (_dir, store) = temp_store().await;
+        let now = UNIX_EPOCH + Duration::from_secs(1_791_968_400);
+        store
+            .insert("expired", delivery(), now)
+            .await
+            .expect("delivery to be recorded");
+        let corrupt = store.path("receipt/42");
+        tokio::fs::write(&corrupt, b"{\"key\":")
+            .await
+            .expect("file to be written");
+
+        expect_that!(store.get("receipt/42", now).await, pat!(Ok(none())));
+        expect_that!(
+            tokio::fs::try_exists(corrupt.with_extension("corrupt")).await,
+            pat!(Ok(eq(&true)))
+        );
+
+        tokio::fs::write(&corrupt, b"{\"key\":")
+            .await
+            .expect("file to be written");
+        expect_that!(store.purge(now).await, pat!(Ok(eq(&1))));
+        store
+            .insert("receipt/42", delivery(), now + Duration::from_secs(60))
+            .await
+            .expect("delivery to be recorded");
+        expect_that!(
+            store.get("receipt/42", now).await,
+            pat!(Ok(some(pat!(EmailDelivery { .. }))))
+        );
+    }
+}
@@ -0,0 +1,64 @@
+//! Deliveries remembered in memory
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+
+use super::DedupeStore;
+use crate::email::EmailDelivery;
+use crate::error::Error;
+
+/// Store keeping deliveries in a map until they expire
+///
+/// Deliveries are lost when the process stops. Expired entries are dropped
+/// on lookup.
+#[derive(Debug, Default)]
+pub struct MemoryDedupeStore {
+    /// Deliveries and their expiry, by idempotency key
+    entries: Mutex<HashMap<String, (EmailDelivery, SystemTime)>>,
+}
+
+impl MemoryDedupeStore {
+    /// Creates an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of deliveries stored, including expired ones
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Returns `true` if no delivery is stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait]
+impl DedupeStore for MemoryDedupeStore {
+    async fn get(&self, key: &str, now: SystemTime) -> Result<Option<EmailDelivery>, Error> {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries.retain(|_key, (_delivery, expires_at)| *expires_at > now);
+        Ok(entries
+            .get(key)
+            .map(|(delivery, _expires_at)| delivery.clone()))
+    }
+
+    async fn insert(
+        &self,
+        key: &str,
+        delivery: EmailDelivery,
+        expires_at: SystemTime,
+    ) -> Result<(), Error> {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key.to_owned(), (delivery, expires_at));
+        Ok(())
+    }
+}
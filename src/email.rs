@@ -1,8 +1,17 @@
 //! Core emails types: messages, recipients, attachments, and delivery receipt
+pub mod defaults;
 pub mod delivery;
+pub mod html;
+pub mod mailbox;
 pub mod message;
 
+#[doc(inline)]
+pub use defaults::MessageDefaults;
 #[doc(inline)]
 pub use delivery::EmailDelivery;
 #[doc(inline)]
+pub use html::html_to_text;
+#[doc(inline)]
+pub use mailbox::Mailbox;
+#[doc(inline)]
 pub use message::{Attachment, Body, EmailMessage, Header, Recipients};
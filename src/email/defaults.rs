@@ -0,0 +1,227 @@
+//! Defaults filled in on messages before they are sent
+use std::collections::BTreeMap;
+
+use super::message::{EmailMessage, Header};
+use crate::error::Error;
+
+/// Values applied to every outgoing message that doesn't set its own
+///
+/// Fields set on the message always win over the defaults.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "bon", derive(bon::Builder))]
+pub struct MessageDefaults {
+    /// Message stream used when the message has none
+    pub message_stream: Option<String>,
+    /// Tag used when the message has none
+    pub tag: Option<String>,
+    /// Headers added unless the message sets a header with the same name
+    #[cfg_attr(feature = "bon", builder(default))]
+    pub headers: Vec<Header>,
+    /// Metadata entries added unless the message sets the same key
+    #[cfg_attr(feature = "bon", builder(default))]
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl MessageDefaults {
+    /// Creates defaults that leave messages untouched
+    pub const fn new() -> Self {
+        Self {
+            message_stream: None,
+            tag: None,
+            headers: Vec::new(),
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    /// Fills in the sender and the fields the message leaves unset
+    ///
+    /// Clients call it before sending, with the sender from their
+    /// configuration. The sender is parsed as a [`Mailbox`], and only used
+    /// when the message doesn't set one.
+    ///
+    /// [`Mailbox`]: super::Mailbox
+    pub fn prepare(
+        &self,
+        mut email: EmailMessage,
+        from_email: &str,
+    ) -> Result<EmailMessage, Error> {
+        if email.from.is_none() {
+            let from = from_email.parse().map_err(|_err| {
+                #[cfg(feature = "tracing")]
+                tracing::error!(?_err);
+                Error::ConfigError(format!("invalid sender address: {from_email}"))
+            })?;
+            email.from = Some(from);
+        }
+        self.apply(&mut email);
+        Ok(email)
+    }
+
+    /// Fills in the fields the message leaves unset
+    pub fn apply(&self, email: &mut EmailMessage) {
+        if email.message_stream.is_none() {
+            email.message_stream.clone_from(&self.message_stream);
+        }
+        if email.tag.is_none() {
+            email.tag.clone_from(&self.tag);
+        }
+
+        if !self.headers.is_empty() {
+            let headers = email.headers.get_or_insert_default();
+            let missing = self
+                .headers
+                .iter()
+                .filter(|default| {
+                    !headers
+                        .iter()
+                        .any(|header| header.name.eq_ignore_ascii_case(&default.name))
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            headers.extend(missing);
+        }
+
+        if !self.metadata.is_empty() {
+            let metadata = email.metadata.get_or_insert_default();
+            for (key, value) in &self.metadata {
+                metadata.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::matchers::{anything, eq, none, pat, some};
+    use googletest::{expect_that, gtest};
+
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::email::Body;
+
+    fn email_message() -> EmailMessage {
+        EmailMessage {
+            from: None,
+            to: vec!["yaa.asantewaa@example.africa"].into(),
+            subject: "Council of Elders".to_owned(),
+            body: Body::Text("The council meets at dawn.".to_owned()),
+            cc: None,
+            bcc: None,
+            tag: None,
+            reply_to: None,
+            headers: None,
+            metadata: None,
+            attachments: None,
+            message_stream: None,
+            idempotency_key: None,
+        }
+    }
+
+    fn defaults() -> MessageDefaults {
+        MessageDefaults {
+            message_stream: Some("outbound".to_owned()),
+            tag: Some("ashanti".to_owned()),
+            headers: vec![Header {
+                name: "X-Kingdom".to_owned(),
+                value: "ashanti".to_owned(),
+            }],
+            metadata: BTreeMap::from([("region".to_owned(), "ejisu".to_owned())]),
+        }
+    }
+
+    #[gtest]
+    fn fills_unset_fields() {
+        let mut email = email_message();
+        defaults().apply(&mut email);
+
+        expect_that!(email.message_stream.as_deref(), some(eq("outbound")));
+        expect_that!(email.tag.as_deref(), some(eq("ashanti")));
+        expect_that!(
+            email
+                .headers
+                .as_ref()
+                .and_then(|headers| headers.first())
+                .map(|header| header.value.as_str()),
+            some(eq("ashanti"))
+        );
+        expect_that!(
+            email
+                .metadata
+                .as_ref()
+                .and_then(|metadata| metadata.get("region"))
+                .map(String::as_str),
+            some(eq("ejisu"))
+        );
+    }
+
+    #[gtest]
+    fn keeps_fields_set_on_the_message() {
+        let mut email = email_message();
+        email.message_stream = Some("broadcast".to_owned());
+        email.tag = Some("edweso".to_owned());
+        email.headers = Some(vec![Header {
+            name: "x-kingdom".to_owned(),
+            value: "edweso".to_owned(),
+        }]);
+        email.metadata = Some(HashMap::from([
+            ("region".to_owned(), "kumasi".to_owned()),
+            ("queen".to_owned(), "yaa".to_owned()),
+        ]));
+        defaults().apply(&mut email);
+
+        expect_that!(email.message_stream.as_deref(), some(eq("broadcast")));
+        expect_that!(email.tag.as_deref(), some(eq("edweso")));
+        expect_that!(email.headers.as_ref().map(Vec::len), some(eq(1)));
+        expect_that!(
+            email
+                .metadata
+                .as_ref()
+                .and_then(|metadata| metadata.get("region"))
+                .map(String::as_str),
+            some(eq("kumasi"))
+        );
+        expect_that!(email.metadata.as_ref().map(HashMap::len), some(eq(2)));
+    }
+
+    #[gtest]
+    fn prepare_falls_back_to_the_configured_sender() {
+        let email = defaults()
+            .prepare(email_message(), "Yaa Asantewaa <yaa@example.africa>")
+            .expect("email to be prepared");
+        expect_that!(
+            email.from.as_ref().map(|from| from.email.as_str()),
+            some(eq("yaa@example.africa"))
+        );
+        expect_that!(email.tag.as_deref(), some(eq("ashanti")));
+
+        let mut email = email_message();
+        email.from = Some("prempeh@example.africa".into());
+        let email = defaults()
+            .prepare(email, "yaa@example.africa")
+            .expect("email to be prepared");
+        expect_that!(
+            email.from.as_ref().map(|from| from.email.as_str()),
+            some(eq("prempeh@example.africa"))
+        );
+    }
+
+    #[gtest]
+    fn prepare_rejects_an_invalid_configured_sender() {
+        expect_that!(
+            defaults().prepare(email_message(), "not an address"),
+            pat!(Err(pat!(Error::ConfigError(anything()))))
+        );
+    }
+
+    #[gtest]
+    fn empty_defaults_leave_message_untouched() {
+        let mut email = email_message();
+        MessageDefaults::new().apply(&mut email);
+
+        expect_that!(email.message_stream, none());
+        expect_that!(email.tag, none());
+        expect_that!(email.headers, none());
+        expect_that!(email.metadata, none());
+    }
+}
@@ -1,12 +1,12 @@
 //! Email sent responses data structures
 use bytes::Bytes;
 use http::Response;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::error::Error;
 
 /// What the provider hands back after receiving an email
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EmailDelivery {
     /// Recipient email address
     pub to: String,
@@ -18,6 +18,20 @@ pub struct EmailDelivery {
     pub error_code: u16,
     /// Human readable status message
     pub message: String,
+    /// Name of the provider that accepted the message
+    ///
+    /// Only set by services that pick between several providers, such as
+    /// `Failover`.
+    #[serde(default)]
+    pub provider: Option<String>,
+}
+
+impl EmailDelivery {
+    /// Returns the typed Postmark error code of the delivery
+    #[cfg(feature = "postmark")]
+    pub fn postmark_error_code(&self) -> crate::postmark::PostmarkErrorCode {
+        self.error_code.into()
+    }
 }
 
 impl TryFrom<Response<Bytes>> for EmailDelivery {
@@ -31,7 +45,7 @@ impl TryFrom<Response<Bytes>> for EmailDelivery {
         serde_json::from_slice(response.body()).map_err(|err| {
             #[cfg(feature = "tracing")]
             tracing::error!(?err);
-            Error::SendFailed(err.to_string())
+            Error::UnexpectedResponse(err.to_string())
         })
     }
 }
@@ -88,6 +102,25 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "postmark")]
+    #[gtest]
+    fn exposes_typed_postmark_error_code() {
+        let json = make_json(
+            "kwame.nkrumah@example.africa",
+            "2026-02-08T14:22:31Z",
+            "sendout-msg-7f3a9b2c",
+            406,
+            "Inactive recipient",
+        );
+        let response: EmailDelivery =
+            serde_json::from_str(&json).expect("deserialization to succeed");
+
+        expect_that!(
+            response.postmark_error_code(),
+            eq(crate::postmark::PostmarkErrorCode::InactiveRecipient)
+        );
+    }
+
     #[gtest]
     fn email_response_missing_field_fails() {
         let json = make_json_without_message_id(
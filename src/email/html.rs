@@ -0,0 +1,610 @@
+//! Plain text rendering of HTML email bodies
+//!
+//! Produces a readable text alternative for HTML-only messages: links become
+//! numbered footnotes, lists keep their markers, headings are prefixed with
+//! `#` and table rows are collapsed onto a single line.
+
+/// Converts an HTML document or fragment into readable plain text
+///
+/// # Examples
+///
+/// ```
+/// use sendout::email::html_to_text;
+///
+/// let text = html_to_text(r#"<h1>Welcome</h1><p>Read the <a href="https://example.africa/guide">guide</a>.</p>"#);
+/// assert_eq!(text, "# Welcome\n\nRead the guide[1].\n\n[1] https://example.africa/guide");
+/// ```
+pub fn html_to_text(html: &str) -> String {
+    let mut writer = TextWriter::default();
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        writer.text(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+
+        let starts_tag = rest[1..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '/' || c == '!' || c == '?');
+        if !starts_tag {
+            writer.text("<");
+            rest = &rest[1..];
+            continue;
+        }
+
+        let Some(end) = tag_end(rest) else {
+            // Unterminated tag, nothing sensible left to render
+            break;
+        };
+        let tag = Tag::parse(&rest[1..end]);
+        rest = &rest[end + 1..];
+
+        let Some(tag) = tag else { continue };
+        if !tag.closing && is_raw_text_element(&tag.name) {
+            rest = skip_element_content(rest, &tag.name);
+            continue;
+        }
+        writer.tag(&tag);
+    }
+    writer.text(rest);
+    writer.finish()
+}
+
+/// Elements whose content is never rendered
+fn is_raw_text_element(name: &str) -> bool {
+    matches!(name, "script" | "style" | "head" | "title" | "template")
+}
+
+/// Skips everything up to and including the closing tag of `name`
+fn skip_element_content<'a>(rest: &'a str, name: &str) -> &'a str {
+    let closing = format!("</{name}");
+    let lowercase = rest.to_ascii_lowercase();
+    lowercase
+        .find(&closing)
+        .and_then(|start| rest[start..].find('>').map(|end| &rest[start + end + 1..]))
+        .unwrap_or("")
+}
+
+/// Returns the index of the `>` closing the tag that starts `input`
+fn tag_end(input: &str) -> Option<usize> {
+    let mut quote = None;
+    for (idx, c) in input.char_indices().skip(1) {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return Some(idx),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// A parsed HTML tag
+#[derive(Debug)]
+struct Tag {
+    /// Lowercase element name
+    name: String,
+    /// Whether this is a closing tag
+    closing: bool,
+    /// Attributes in source order with lowercase names
+    attributes: Vec<(String, String)>,
+}
+
+impl Tag {
+    /// Parses the content between `<` and `>`
+    ///
+    /// Returns `None` for declarations, processing instructions and empty tags.
+    fn parse(content: &str) -> Option<Self> {
+        if content.starts_with('!') || content.starts_with('?') {
+            return None;
+        }
+        let (closing, content) = content
+            .strip_prefix('/')
+            .map_or((false, content), |content| (true, content));
+        let content = content.trim_end_matches('/');
+        let name_end = content
+            .find(|c: char| c.is_whitespace() || c == '/')
+            .unwrap_or(content.len());
+        let name = content[..name_end].to_ascii_lowercase();
+        if name.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            name,
+            closing,
+            attributes: parse_attributes(&content[name_end..]),
+        })
+    }
+
+    /// Returns the value of the attribute, if present
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Parses `key="value"`, `key='value'`, `key=value` and bare `key` attributes
+fn parse_attributes(mut input: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    loop {
+        input = input.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+        if input.is_empty() {
+            break;
+        }
+        let key_end = input
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '/')
+            .unwrap_or(input.len());
+        let key = input[..key_end].to_ascii_lowercase();
+        input = input[key_end..].trim_start();
+
+        let mut value = "";
+        if let Some(after_eq) = input.strip_prefix('=') {
+            let after_eq = after_eq.trim_start();
+            let (raw, remaining) = split_attribute_value(after_eq);
+            value = raw;
+            input = remaining;
+        }
+        attributes.push((key, decode_entities(value)));
+    }
+    attributes
+}
+
+/// Splits a quoted or unquoted attribute value from the rest of the tag
+fn split_attribute_value(input: &str) -> (&str, &str) {
+    match input.chars().next() {
+        Some(quote @ ('"' | '\'')) => {
+            let body = &input[1..];
+            let end = body.find(quote).unwrap_or(body.len());
+            (&body[..end], body.get(end + 1..).unwrap_or(""))
+        }
+        _ => {
+            let end = input.find(char::is_whitespace).unwrap_or(input.len());
+            (&input[..end], &input[end..])
+        }
+    }
+}
+
+/// Replaces character references with the characters they stand for
+fn decode_entities(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('&') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest
+            .get(1..)
+            .and_then(|after| after.find(';').filter(|end| *end <= 10))
+            .and_then(|end| decode_entity(&rest[1..=end]).map(|c| (c, end + 2)));
+        match decoded {
+            Some((c, len)) => {
+                output.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                output.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Decodes a single entity name without the surrounding `&` and `;`
+fn decode_entity(entity: &str) -> Option<char> {
+    if let Some(number) = entity.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    let c = match entity {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "mdash" => '—',
+        "ndash" => '–',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "bull" => '•',
+        "middot" => '·',
+        "euro" => '€',
+        "pound" => '£',
+        _ => return None,
+    };
+    Some(c)
+}
+
+/// A list being rendered
+#[derive(Debug)]
+enum List {
+    /// Bulleted list
+    Unordered,
+    /// Numbered list with the number of the next item
+    Ordered(u32),
+}
+
+/// Accumulates the plain text output
+#[derive(Debug, Default)]
+struct TextWriter {
+    /// Text rendered so far
+    output: String,
+    /// Minimum number of line breaks to emit before the next text
+    pending_newlines: usize,
+    /// Whether a space separates the previous text from the next one
+    pending_space: bool,
+    /// Link footnotes in order of appearance
+    links: Vec<String>,
+    /// Link being rendered with its target and where its text started
+    open_link: Option<(String, usize)>,
+    /// Lists being rendered, innermost last
+    lists: Vec<List>,
+    /// Number of cells already rendered in the current table row
+    cells: usize,
+    /// Depth of nested `<blockquote>` elements
+    quote_depth: usize,
+    /// Depth of nested `<pre>` elements
+    pre_depth: usize,
+}
+
+impl TextWriter {
+    /// Handles an opening or closing tag
+    fn tag(&mut self, tag: &Tag) {
+        match (tag.name.as_str(), tag.closing) {
+            ("br", _) => {
+                self.output.push('\n');
+                self.pending_space = false;
+            }
+            ("p", _) => self.block(2),
+            (
+                "div" | "section" | "article" | "header" | "footer" | "main" | "nav" | "aside"
+                | "address" | "figure" | "figcaption" | "form" | "dl" | "dt" | "dd",
+                _,
+            ) => self.block(1),
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", false) => {
+                self.block(2);
+                let level = usize::from(tag.name.as_bytes()[1] - b'0');
+                self.write(&format!("{} ", "#".repeat(level)));
+            }
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", true) => self.block(2),
+            ("ul" | "ol", false) => {
+                self.block(if self.lists.is_empty() { 2 } else { 1 });
+                let list = if tag.name == "ol" {
+                    let start = tag
+                        .attribute("start")
+                        .and_then(|start| start.parse().ok())
+                        .unwrap_or(1);
+                    List::Ordered(start)
+                } else {
+                    List::Unordered
+                };
+                self.lists.push(list);
+            }
+            ("ul" | "ol", true) => {
+                self.lists.pop();
+                self.block(if self.lists.is_empty() { 2 } else { 1 });
+            }
+            ("li", false) => {
+                self.block(1);
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let marker = match self.lists.last_mut() {
+                    Some(List::Ordered(number)) => {
+                        let marker = format!("{number}. ");
+                        *number = number.saturating_add(1);
+                        marker
+                    }
+                    Some(List::Unordered) | None => "* ".to_owned(),
+                };
+                self.write(&format!("{indent}{marker}"));
+            }
+            ("li", true) => self.block(1),
+            ("table", _) => self.block(2),
+            ("tr", _) => {
+                self.block(1);
+                self.cells = 0;
+            }
+            ("td" | "th", false) => {
+                if self.cells > 0 {
+                    self.write(" | ");
+                }
+                self.cells += 1;
+            }
+            ("blockquote", false) => {
+                self.block(2);
+                self.quote_depth += 1;
+            }
+            ("blockquote", true) => {
+                self.block(2);
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+            }
+            ("pre", false) => {
+                self.block(2);
+                self.pre_depth += 1;
+            }
+            ("pre", true) => {
+                self.pre_depth = self.pre_depth.saturating_sub(1);
+                self.block(2);
+            }
+            ("hr", _) => {
+                self.block(2);
+                self.write("---");
+                self.block(2);
+            }
+            ("img", false) => {
+                if let Some(alt) = tag.attribute("alt").filter(|alt| !alt.trim().is_empty()) {
+                    self.text(alt);
+                }
+            }
+            ("a", false) => {
+                self.open_link = tag
+                    .attribute("href")
+                    .map(str::trim)
+                    .filter(|href| !href.is_empty() && !href.starts_with('#'))
+                    .filter(|href| !href.to_ascii_lowercase().starts_with("javascript:"))
+                    .map(|href| (href.to_owned(), self.output.len()));
+            }
+            ("a", true) => self.close_link(),
+            _ => {}
+        }
+    }
+
+    /// Records the footnote for the link being closed
+    fn close_link(&mut self) {
+        let Some((href, start)) = self.open_link.take() else {
+            return;
+        };
+        let label = self.output.get(start..).unwrap_or("").trim();
+        let target = href.strip_prefix("mailto:").unwrap_or(&href);
+        if label.is_empty() || label == target {
+            if label.is_empty() {
+                self.write(target);
+            }
+            return;
+        }
+
+        let number = match self.links.iter().position(|link| *link == href) {
+            Some(idx) => idx + 1,
+            None => {
+                self.links.push(href);
+                self.links.len()
+            }
+        };
+        self.output.push_str(&format!("[{number}]"));
+    }
+
+    /// Handles text content between tags
+    fn text(&mut self, raw: &str) {
+        let decoded = decode_entities(raw);
+        if self.pre_depth > 0 {
+            for (idx, line) in decoded.split('\n').enumerate() {
+                if idx > 0 {
+                    self.output.push('\n');
+                }
+                if !line.is_empty() {
+                    self.write(line);
+                }
+            }
+            return;
+        }
+
+        let is_space = |c: char| c.is_whitespace() && c != '\u{a0}';
+        if decoded.starts_with(is_space) {
+            self.pending_space = true;
+        }
+        for (idx, word) in decoded
+            .split(is_space)
+            .filter(|w| !w.is_empty())
+            .enumerate()
+        {
+            if idx > 0 {
+                self.pending_space = true;
+            }
+            self.write(&word.replace('\u{a0}', " "));
+        }
+        if decoded.ends_with(is_space) {
+            self.pending_space = true;
+        }
+    }
+
+    /// Requests at least `newlines` line breaks before the next text
+    fn block(&mut self, newlines: usize) {
+        self.pending_newlines = self.pending_newlines.max(newlines);
+        self.pending_space = false;
+    }
+
+    /// Writes text, flushing pending line breaks and spaces first
+    fn write(&mut self, text: &str) {
+        if self.output.is_empty() {
+            self.pending_newlines = 0;
+            self.pending_space = false;
+        } else {
+            let trailing = self.output.len() - self.output.trim_end_matches('\n').len();
+            if trailing < self.pending_newlines {
+                trim_trailing_spaces(&mut self.output);
+                self.output
+                    .push_str(&"\n".repeat(self.pending_newlines - trailing));
+            }
+            self.pending_newlines = 0;
+        }
+
+        if self.output.is_empty() || self.output.ends_with('\n') {
+            self.output.push_str(&"> ".repeat(self.quote_depth));
+        } else if self.pending_space && !self.output.ends_with(' ') {
+            self.output.push(' ');
+        }
+        self.pending_space = false;
+        self.output.push_str(text);
+    }
+
+    /// Returns the rendered text followed by the link footnotes
+    fn finish(mut self) -> String {
+        self.close_link();
+
+        let mut text = String::with_capacity(self.output.len());
+        let mut blank_lines = 0;
+        for line in self.output.lines().map(str::trim_end) {
+            if line.is_empty() {
+                blank_lines += 1;
+                continue;
+            }
+            if !text.is_empty() {
+                text.push_str(if blank_lines > 0 { "\n\n" } else { "\n" });
+            }
+            text.push_str(line);
+            blank_lines = 0;
+        }
+
+        if !self.links.is_empty() {
+            text.push_str("\n\n");
+            let footnotes = self
+                .links
+                .iter()
+                .enumerate()
+                .map(|(idx, link)| format!("[{}] {link}", idx + 1))
+                .collect::<Vec<_>>();
+            text.push_str(&footnotes.join("\n"));
+        }
+        text
+    }
+}
+
+/// Removes spaces at the end of the buffer
+fn trim_trailing_spaces(output: &mut String) {
+    let len = output.trim_end_matches(' ').len();
+    output.truncate(len);
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::matchers::eq;
+    use googletest::{expect_that, gtest};
+
+    use super::*;
+
+    #[gtest]
+    fn renders_paragraphs_and_collapses_whitespace() {
+        let text = html_to_text("<p>We planted   10,000\n trees.</p><p>Across Kenya.</p>");
+        expect_that!(text, eq("We planted 10,000 trees.\n\nAcross Kenya."));
+    }
+
+    #[gtest]
+    fn renders_headings() {
+        let text = html_to_text("<h1>Monthly Update</h1><h2>Trees</h2><p>10,000</p>");
+        expect_that!(text, eq("# Monthly Update\n\n## Trees\n\n10,000"));
+    }
+
+    #[gtest]
+    fn renders_links_as_footnotes() {
+        let text = html_to_text(concat!(
+            r#"<p>Read the <a href="https://example.africa/report">report</a> "#,
+            r#"and the <a href="https://example.africa/faq">FAQ</a>. "#,
+            r#"Again, the <a href="https://example.africa/report">report</a>.</p>"#,
+        ));
+        expect_that!(
+            text,
+            eq(concat!(
+                "Read the report[1] and the FAQ[2]. Again, the report[1].\n\n",
+                "[1] https://example.africa/report\n",
+                "[2] https://example.africa/faq",
+            ))
+        );
+    }
+
+    #[gtest]
+    fn links_without_label_or_matching_label_are_inlined() {
+        let text = html_to_text(concat!(
+            r#"<a href="https://example.africa">https://example.africa</a> "#,
+            r#"<a href="mailto:info@example.africa">info@example.africa</a> "#,
+            r##"<a href="#top">Top</a>"##,
+        ));
+        expect_that!(text, eq("https://example.africa info@example.africa Top"));
+    }
+
+    #[gtest]
+    fn renders_lists() {
+        let text = html_to_text(concat!(
+            "<p>Agenda:</p>",
+            "<ul><li>Welcome</li><li>Reports<ol><li>Kenya</li><li>Ghana</li></ol></li></ul>",
+            "<p>See you there</p>",
+        ));
+        expect_that!(
+            text,
+            eq("Agenda:\n\n* Welcome\n* Reports\n  1. Kenya\n  2. Ghana\n\nSee you there")
+        );
+    }
+
+    #[gtest]
+    fn ordered_lists_starting_at_the_maximum_do_not_overflow() {
+        let text = html_to_text(r#"<ol start="4294967295"><li>a<li>b</ol>"#);
+        expect_that!(text, eq("4294967295. a\n4294967295. b"));
+    }
+
+    #[gtest]
+    fn collapses_tables_into_rows() {
+        let text = html_to_text(concat!(
+            "<table><tr><th>Country</th><th>Trees</th></tr>",
+            "<tr><td>Kenya</td><td>10,000</td></tr></table>",
+        ));
+        expect_that!(text, eq("Country | Trees\nKenya | 10,000"));
+    }
+
+    #[gtest]
+    fn skips_head_scripts_styles_and_comments() {
+        let text = html_to_text(concat!(
+            "<!DOCTYPE html><html><head><title>Ignored</title>",
+            "<style>p { color: red; }</style></head><body>",
+            "<!-- hidden --><script>alert('x')</script><p>Visible</p></body></html>",
+        ));
+        expect_that!(text, eq("Visible"));
+    }
+
+    #[gtest]
+    fn decodes_entities() {
+        let text =
+            html_to_text("<p>Fish &amp; chips &lt;3 &#8212; &#x263A; &copy; &unknown; a &lt b</p>");
+        expect_that!(text, eq("Fish & chips <3 — ☺ © &unknown; a &lt b"));
+    }
+
+    #[gtest]
+    fn keeps_preformatted_text() {
+        let text = html_to_text("<pre>line one\n  indented</pre>");
+        expect_that!(text, eq("line one\n  indented"));
+    }
+
+    #[gtest]
+    fn renders_line_breaks_quotes_and_images() {
+        let text = html_to_text(concat!(
+            "<p>Kind regards,<br>Wangari</p>",
+            "<blockquote>Every tree counts</blockquote>",
+            r#"<img src="logo.png" alt="Green Belt Movement">"#,
+        ));
+        expect_that!(
+            text,
+            eq("Kind regards,\nWangari\n\n> Every tree counts\n\nGreen Belt Movement")
+        );
+    }
+
+    #[gtest]
+    fn keeps_stray_angle_brackets() {
+        let text = html_to_text("<p>1 < 2 and 3 > 2</p>");
+        expect_that!(text, eq("1 < 2 and 3 > 2"));
+    }
+}
@@ -0,0 +1,381 @@
+//! Email addresses with an optional display name
+use std::fmt;
+use std::str::FromStr;
+
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+
+use crate::error::Error;
+
+/// An email address with an optional display name
+///
+/// Formats as `Jane Doe <jane@example.com>`, quoting the display name when
+/// it contains characters that aren't allowed in an RFC 5322 phrase.
+/// Control characters in the display name are written as spaces, so a name
+/// can't break the header it ends up in:
+///
+/// ```
+/// use sendout::email::Mailbox;
+///
+/// let mailbox = Mailbox::new("Doe, Jane", "jane@example.com");
+/// assert_eq!(mailbox.to_string(), r#""Doe, Jane" <jane@example.com>"#);
+///
+/// let parsed: Mailbox = r#""Doe, Jane" <jane@example.com>"#.parse().unwrap();
+/// assert_eq!(parsed, mailbox);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SerializeDisplay, DeserializeFromStr)]
+#[cfg_attr(feature = "garde", derive(garde::Validate))]
+pub struct Mailbox {
+    /// Display name shown by mail clients
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub name: Option<String>,
+    /// The email address
+    #[cfg_attr(feature = "garde", garde(email))]
+    pub email: String,
+}
+
+impl Mailbox {
+    /// Creates a mailbox with a display name
+    pub fn new(name: impl Into<String>, email: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            email: email.into(),
+        }
+    }
+
+    /// Checks that the address can be written into headers and commands
+    ///
+    /// [`Mailbox::from`] doesn't check the address, so one could carry line
+    /// breaks that inject extra headers. Empty addresses, addresses with
+    /// control characters or angle brackets, and display names with control
+    /// characters are rejected.
+    pub fn check_address(&self) -> Result<(), Error> {
+        let unsafe_char = |c: char| c.is_control() || c == '<' || c == '>';
+        if self.email.is_empty() || self.email.contains(unsafe_char) {
+            return Err(Error::InvalidRecipient(
+                self.email.escape_debug().to_string(),
+            ));
+        }
+        if let Some(name) = self.name.as_deref()
+            && name.contains(char::is_control)
+        {
+            return Err(Error::InvalidRecipient(name.escape_debug().to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl From<String> for Mailbox {
+    /// Uses the string as is for the address, without a display name
+    fn from(email: String) -> Self {
+        Self { name: None, email }
+    }
+}
+
+impl From<&str> for Mailbox {
+    /// Uses the string as is for the address, without a display name
+    fn from(email: &str) -> Self {
+        Self::from(email.to_owned())
+    }
+}
+
+impl fmt::Display for Mailbox {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name.as_deref() {
+            Some(name) if !name.is_empty() => {
+                if is_phrase(name) {
+                    write!(f, "{name} <{}>", self.email)
+                } else {
+                    f.write_str("\"")?;
+                    for c in name.chars() {
+                        match c {
+                            '"' | '\\' => write!(f, "\\{c}")?,
+                            c if c.is_control() => f.write_str(" ")?,
+                            c => write!(f, "{c}")?,
+                        }
+                    }
+                    write!(f, "\" <{}>", self.email)
+                }
+            }
+            _ => f.write_str(&self.email),
+        }
+    }
+}
+
+impl FromStr for Mailbox {
+    type Err = Error;
+
+    /// Parses `address`, `<address>`, `Name <address>` or `"Name" <address>`
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        let invalid = || Error::InvalidRecipient(input.to_owned());
+        if !input.contains('<') {
+            // No angle brackets: the whole input is a bare address
+            return if is_address(input) {
+                Ok(Self::from(input))
+            } else {
+                Err(invalid())
+            };
+        }
+
+        let mut name = String::new();
+        let mut pending_space = false;
+        let mut email = None;
+        let mut chars = input.char_indices();
+        while let Some((idx, c)) = chars.next() {
+            match c {
+                '<' => {
+                    let rest = &input[idx + 1..];
+                    let end = rest.find('>').ok_or_else(invalid)?;
+                    if !rest[end + 1..].trim().is_empty() {
+                        return Err(invalid());
+                    }
+                    email = Some(rest[..end].trim());
+                    break;
+                }
+                '"' => {
+                    if pending_space && !name.is_empty() {
+                        name.push(' ');
+                    }
+                    pending_space = false;
+                    loop {
+                        match chars.next().ok_or_else(invalid)? {
+                            (_, '"') => break,
+                            (_, '\\') => name.push(chars.next().ok_or_else(invalid)?.1),
+                            (_, c) => name.push(c),
+                        }
+                    }
+                }
+                c if c.is_whitespace() => pending_space = true,
+                c if is_special(c) => return Err(invalid()),
+                c => {
+                    if pending_space && !name.is_empty() {
+                        name.push(' ');
+                    }
+                    pending_space = false;
+                    name.push(c);
+                }
+            }
+        }
+
+        let Some(email) = email else {
+            return Err(invalid());
+        };
+        if !is_address(email) {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            name: (!name.is_empty()).then_some(name),
+            email: email.to_owned(),
+        })
+    }
+}
+
+/// Returns `true` if the name can be written without quotes
+///
+/// That is a sequence of atoms separated by single spaces. Non-ASCII
+/// characters are allowed as in RFC 6532.
+fn is_phrase(name: &str) -> bool {
+    name.split(' ')
+        .all(|atom| !atom.is_empty() && atom.chars().all(is_atext))
+}
+
+/// Returns `true` for characters allowed in an RFC 5322 atom
+fn is_atext(c: char) -> bool {
+    c.is_ascii_alphanumeric()
+        || "!#$%&'*+-/=?^_`{|}~".contains(c)
+        || (!c.is_ascii() && !c.is_control())
+}
+
+/// Returns `true` for characters that must be quoted in a display name
+///
+/// Periods are accepted unquoted, as many mailers still produce them.
+fn is_special(c: char) -> bool {
+    "()<>[]:;@\\,\"".contains(c)
+}
+
+/// Loosely checks that the input looks like `local@domain`
+fn is_address(input: &str) -> bool {
+    input.rsplit_once('@').is_some_and(|(local, domain)| {
+        !local.is_empty()
+            && !domain.is_empty()
+            && !input
+                .chars()
+                .any(|c| c.is_whitespace() || "<>\",;()[]\\".contains(c))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::matchers::{anything, eq, err, none, ok, some};
+    use googletest::{expect_that, gtest};
+
+    use super::*;
+
+    #[gtest]
+    fn formats_bare_address() {
+        let mailbox = Mailbox::from("kwame.nkrumah@example.africa");
+        expect_that!(mailbox.to_string(), eq("kwame.nkrumah@example.africa"));
+    }
+
+    #[gtest]
+    fn formats_plain_display_name_unquoted() {
+        let mailbox = Mailbox::new("Kwame Nkrumah", "kwame.nkrumah@example.africa");
+        expect_that!(
+            mailbox.to_string(),
+            eq("Kwame Nkrumah <kwame.nkrumah@example.africa>")
+        );
+    }
+
+    #[gtest]
+    fn quotes_display_name_with_specials() {
+        let mailbox = Mailbox::new("Nkrumah, Kwame", "kwame.nkrumah@example.africa");
+        expect_that!(
+            mailbox.to_string(),
+            eq(r#""Nkrumah, Kwame" <kwame.nkrumah@example.africa>"#)
+        );
+
+        let mailbox = Mailbox::new(r#"Kwame "Osagyefo" Nkrumah"#, "kwame@example.africa");
+        expect_that!(
+            mailbox.to_string(),
+            eq(r#""Kwame \"Osagyefo\" Nkrumah" <kwame@example.africa>"#)
+        );
+
+        let mailbox = Mailbox::new("K. Nkrumah", "kwame@example.africa");
+        expect_that!(
+            mailbox.to_string(),
+            eq(r#""K. Nkrumah" <kwame@example.africa>"#)
+        );
+    }
+
+    #[gtest]
+    fn keeps_non_ascii_display_name_unquoted() {
+        let mailbox = Mailbox::new("Béhanzin", "gbehanzin@example.africa");
+        expect_that!(
+            mailbox.to_string(),
+            eq("Béhanzin <gbehanzin@example.africa>")
+        );
+    }
+
+    #[gtest]
+    fn parses_mailboxes() {
+        let bare: Mailbox = "miriam.makeba@example.africa"
+            .parse()
+            .expect("parsing to succeed");
+        expect_that!(bare.name, none());
+        expect_that!(bare.email, eq("miriam.makeba@example.africa"));
+
+        let angle: Mailbox = "<miriam.makeba@example.africa>"
+            .parse()
+            .expect("parsing to succeed");
+        expect_that!(angle.name, none());
+
+        let named: Mailbox = "  Miriam   Makeba <miriam.makeba@example.africa> "
+            .parse()
+            .expect("parsing to succeed");
+        expect_that!(named.name.as_deref(), some(eq("Miriam Makeba")));
+        expect_that!(named.email, eq("miriam.makeba@example.africa"));
+
+        let quoted: Mailbox = r#""Makeba, \"Mama Africa\"" <miriam.makeba@example.africa>"#
+            .parse()
+            .expect("parsing to succeed");
+        expect_that!(quoted.name.as_deref(), some(eq(r#"Makeba, "Mama Africa""#)));
+    }
+
+    #[gtest]
+    fn round_trips_through_display() {
+        for name in [
+            "Steve Biko",
+            "Biko, Steve",
+            r#"Steve "Bantu" Biko"#,
+            "S. Biko",
+        ] {
+            let mailbox = Mailbox::new(name, "steve.biko@example.africa");
+            expect_that!(mailbox.to_string().parse::<Mailbox>(), ok(eq(&mailbox)));
+        }
+    }
+
+    #[gtest]
+    fn rejects_malformed_mailboxes() {
+        for input in [
+            "",
+            "not-an-address",
+            "Steve Biko",
+            "Biko, Steve <steve.biko@example.africa>",
+            "Steve <steve.biko@example.africa",
+            "Steve <steve.biko@example.africa> trailing",
+            r#""Unterminated <steve.biko@example.africa>"#,
+            "steve biko@example.africa",
+        ] {
+            expect_that!(input.parse::<Mailbox>(), err(anything()));
+        }
+    }
+
+    #[gtest]
+    fn serializes_as_string() {
+        let mailbox = Mailbox::new("Biko, Steve", "steve.biko@example.africa");
+        let json = serde_json::to_value(&mailbox).expect("serialization to succeed");
+        expect_that!(
+            json.as_str(),
+            some(eq(r#""Biko, Steve" <steve.biko@example.africa>"#))
+        );
+
+        let parsed: Mailbox = serde_json::from_value(json).expect("deserialization to succeed");
+        expect_that!(parsed, eq(&mailbox));
+    }
+
+    #[gtest]
+    fn checks_addresses_for_unsafe_characters() {
+        expect_that!(
+            Mailbox::from("steve.biko@example.africa").check_address(),
+            ok(anything())
+        );
+        for email in [
+            "x@example.com\r\nBcc: evil@attacker.com",
+            "x@example.com>",
+            "x\0@example.com",
+            "",
+        ] {
+            expect_that!(Mailbox::from(email).check_address(), err(anything()));
+        }
+        expect_that!(
+            Mailbox::new(
+                "Steve\r\nBcc: evil@attacker.com",
+                "steve.biko@example.africa"
+            )
+            .check_address(),
+            err(anything())
+        );
+    }
+
+    #[gtest]
+    fn writes_control_characters_in_names_as_spaces() {
+        let mailbox = Mailbox::new(
+            "Steve\r\nBcc: evil@attacker.com",
+            "steve.biko@example.africa",
+        );
+        expect_that!(
+            mailbox.to_string(),
+            eq(r#""Steve  Bcc: evil@attacker.com" <steve.biko@example.africa>"#)
+        );
+        expect_that!(
+            Mailbox::new("Steve\u{85}Biko", "steve.biko@example.africa").to_string(),
+            eq(r#""Steve Biko" <steve.biko@example.africa>"#)
+        );
+    }
+
+    #[cfg(feature = "garde")]
+    #[gtest]
+    fn validates_address() {
+        use garde::Validate;
+
+        expect_that!(
+            Mailbox::new("Steve Biko", "steve.biko@example.africa").validate(),
+            ok(anything())
+        );
+        expect_that!(
+            Mailbox::new("Steve Biko", "steve-biko").validate(),
+            err(anything())
+        );
+    }
+}
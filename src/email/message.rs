@@ -2,25 +2,32 @@
 use std::collections::HashMap;
 use std::io::Read;
 use std::path::Path;
+use std::str::FromStr;
 
 use base64::prelude::{BASE64_STANDARD, Engine};
 use fs_err::File;
-use serde::Serialize;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_with::formats::CommaSeparator;
 use serde_with::{StringWithSeparator, serde_as};
 
+use super::html::html_to_text;
+use super::mailbox::Mailbox;
 use crate::error::Error;
 
 /// An email to be sent
 #[serde_as]
 #[serde_with::skip_serializing_none]
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "bon", derive(bon::Builder))]
 #[cfg_attr(feature = "garde", derive(garde::Validate))]
 pub struct EmailMessage {
-    /// The sender email address
-    #[cfg_attr(feature = "garde", garde(email))]
-    pub from: String,
+    /// The sender mailbox
+    ///
+    /// When unset, clients fall back to the sender from their configuration.
+    #[cfg_attr(feature = "garde", garde(dive))]
+    #[cfg_attr(feature = "bon", builder(into))]
+    pub from: Option<Mailbox>,
     /// Recipient email address
     #[cfg_attr(feature = "garde", garde(dive))]
     pub to: Recipients,
@@ -56,19 +63,79 @@ pub struct EmailMessage {
     /// Message stream to send through
     #[cfg_attr(feature = "garde", garde(length(graphemes, min = 1)))]
     pub message_stream: Option<String>,
+    /// Key identifying the message across retries
+    ///
+    /// Sends with the same key are deduplicated by `Deduplicate`, and the key
+    /// is forwarded to providers that support idempotent requests.
+    #[cfg_attr(feature = "garde", garde(length(min = 1, max = 256)))]
+    pub idempotency_key: Option<String>,
+}
+
+impl EmailMessage {
+    /// Derives a plain text alternative when the body is HTML only
+    ///
+    /// See [`Body::with_text_alternative`].
+    #[must_use]
+    pub fn with_text_alternative(mut self) -> Self {
+        self.body = self.body.with_text_alternative();
+        self
+    }
 }
 
 /// Email message body
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Body {
     /// Plain text email message
     Text(String),
     /// HTML email message
     Html(String),
+    /// Plain text and HTML alternatives of the same message
+    ///
+    /// Mail clients display the HTML part and fall back to the text part.
+    #[serde(untagged, rename_all = "PascalCase")]
+    Multipart {
+        /// Plain text alternative
+        text: String,
+        /// HTML alternative
+        html: String,
+    },
+}
+
+impl Body {
+    /// Returns the plain text part, if any
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            Self::Text(text) | Self::Multipart { text, .. } => Some(text),
+            Self::Html(_) => None,
+        }
+    }
+
+    /// Returns the HTML part, if any
+    pub fn html(&self) -> Option<&str> {
+        match self {
+            Self::Html(html) | Self::Multipart { html, .. } => Some(html),
+            Self::Text(_) => None,
+        }
+    }
+
+    /// Derives a plain text alternative from an HTML only body
+    ///
+    /// [`Body::Html`] becomes [`Body::Multipart`] with the text produced by
+    /// [`html_to_text`]. Other bodies are returned unchanged.
+    #[must_use]
+    pub fn with_text_alternative(self) -> Self {
+        match self {
+            Self::Html(html) => Self::Multipart {
+                text: html_to_text(&html),
+                html,
+            },
+            body => body,
+        }
+    }
 }
 
 /// A custom header to attach to the email
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "garde", derive(garde::Validate))]
 pub struct Header {
     /// Name of the header
@@ -80,37 +147,105 @@ pub struct Header {
 }
 
 /// A list of recipients serialized as comma separated string
+///
+/// Display names are quoted as needed, so commas inside them don't
+/// split the list.
 #[serde_as]
 #[derive(Debug, Clone, Serialize)]
 #[cfg_attr(feature = "garde", derive(garde::Validate))]
 #[cfg_attr(feature = "garde", garde(transparent))]
 pub struct Recipients(
-    #[cfg_attr(feature = "garde", garde(length(min = 1), inner(email)))]
-    #[serde_as(as = "StringWithSeparator::<CommaSeparator, String>")]
-    Vec<String>,
+    #[cfg_attr(feature = "garde", garde(length(min = 1), dive))]
+    #[serde_as(serialize_as = "StringWithSeparator::<CommaSeparator, Mailbox>")]
+    Vec<Mailbox>,
 );
 
 impl Recipients {
-    /// Consumes self and returns the inner list of email addresses
-    pub fn into_inner(self) -> Vec<String> {
+    /// Returns the mailboxes in the list
+    pub fn as_slice(&self) -> &[Mailbox] {
+        &self.0
+    }
+
+    /// Consumes self and returns the inner list of mailboxes
+    pub fn into_inner(self) -> Vec<Mailbox> {
         self.0
     }
 }
 
+impl FromStr for Recipients {
+    type Err = Error;
+
+    /// Parses a comma separated list of mailboxes
+    ///
+    /// Commas inside quoted display names or angle brackets don't split
+    /// the list.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut items = Vec::new();
+        let mut in_quotes = false;
+        let mut in_angle = false;
+        let mut escaped = false;
+        let mut start = 0;
+        for (idx, c) in input.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if in_quotes => escaped = true,
+                '"' => in_quotes = !in_quotes,
+                '<' if !in_quotes => in_angle = true,
+                '>' if !in_quotes => in_angle = false,
+                ',' if !in_quotes && !in_angle => {
+                    items.push(&input[start..idx]);
+                    start = idx + 1;
+                }
+                _ => {}
+            }
+        }
+        items.push(&input[start..]);
+
+        items
+            .into_iter()
+            .filter(|item| !item.trim().is_empty())
+            .map(Mailbox::from_str)
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Recipients {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let list = String::deserialize(deserializer)?;
+        list.parse().map_err(D::Error::custom)
+    }
+}
+
+impl From<Vec<Mailbox>> for Recipients {
+    fn from(mailboxes: Vec<Mailbox>) -> Self {
+        Self(mailboxes)
+    }
+}
+
+impl From<Mailbox> for Recipients {
+    fn from(mailbox: Mailbox) -> Self {
+        Self(vec![mailbox])
+    }
+}
+
 impl From<Vec<String>> for Recipients {
     fn from(emails: Vec<String>) -> Self {
-        Self(emails)
+        Self(emails.into_iter().map(Mailbox::from).collect())
     }
 }
 
 impl From<Vec<&str>> for Recipients {
     fn from(emails: Vec<&str>) -> Self {
-        Self(emails.iter().map(ToString::to_string).collect())
+        Self(emails.into_iter().map(Mailbox::from).collect())
     }
 }
 
 /// An attachment to the email
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[cfg_attr(feature = "garde", derive(garde::Validate))]
 #[cfg_attr(feature = "bon", derive(bon::Builder))]
 pub struct Attachment {
@@ -193,7 +328,7 @@ mod tests {
     #[gtest]
     fn email_email_message_serializes_required_fields() {
         let email_message = EmailMessage {
-            from: "wangari.maathai@example.africa".to_owned(),
+            from: Some("wangari.maathai@example.africa".into()),
             to: vec!["kwame.nkrumah@example.africa"].into(),
             subject: "Green Belt Movement Monthly Update".to_owned(),
             body: Body::Text("We planted 10,000 trees across Kenya this month.".to_owned()),
@@ -205,6 +340,7 @@ mod tests {
             metadata: None,
             attachments: None,
             message_stream: None,
+            idempotency_key: None,
         };
 
         let json: Value = serde_json::to_value(&email_message).expect("serialization to succeed");
@@ -230,7 +366,7 @@ mod tests {
     #[gtest]
     fn email_email_message_omits_none_optional_fields() {
         let email_message = EmailMessage {
-            from: "thomas.sankara@example.africa".to_owned(),
+            from: Some("thomas.sankara@example.africa".into()),
             to: vec!["patrice.lumumba@example.africa"].into(),
             subject: "Self-Sufficiency Progress Report".to_owned(),
             body: Body::Text("Burkina Faso grows stronger through our own efforts.".to_owned()),
@@ -242,6 +378,7 @@ mod tests {
             metadata: None,
             attachments: None,
             message_stream: None,
+            idempotency_key: None,
         };
 
         let json: Value = serde_json::to_value(&email_message).expect("serialization to succeed");
@@ -262,7 +399,7 @@ mod tests {
         metadata.insert("literary_genre".to_owned(), "african-fiction".to_owned());
 
         let email_message = EmailMessage {
-            from: "chimamanda.adichie@example.africa".to_owned(),
+            from: Some("chimamanda.adichie@example.africa".into()),
             to: vec!["yaa.asantewaa@example.africa"].into(),
             subject: "New Novel Draft Ready for Review".to_owned(),
             body: Body::Text("The story of our ancestors deserves to be told.".to_owned()),
@@ -282,6 +419,7 @@ mod tests {
                 content_id: Some("ci:yeyi".to_owned()),
             }]),
             message_stream: Some("literary-submissions".to_owned()),
+            idempotency_key: None,
         };
 
         let json: Value = serde_json::to_value(&email_message).expect("serialization to succeed");
@@ -343,6 +481,108 @@ mod tests {
             some(eq("<h1>Pan-African Unity Conference</h1>"))
         );
         expect_that!(html_json.get("Text"), none());
+
+        let multipart_body = Body::Multipart {
+            text: "Pan-African Unity Conference".to_owned(),
+            html: "<h1>Pan-African Unity Conference</h1>".to_owned(),
+        };
+        let multipart_json: Value =
+            serde_json::to_value(&multipart_body).expect("serialization to succeed");
+        expect_that!(
+            multipart_json.get("Text").and_then(|t| t.as_str()),
+            some(eq("Pan-African Unity Conference"))
+        );
+        expect_that!(
+            multipart_json.get("Html").and_then(|h| h.as_str()),
+            some(eq("<h1>Pan-African Unity Conference</h1>"))
+        );
+        expect_that!(multipart_json.get("Multipart"), none());
+    }
+
+    #[gtest]
+    fn email_message_round_trips_through_json() {
+        let bodies = [
+            Body::Text("Harambee".to_owned()),
+            Body::Html("<p>Harambee</p>".to_owned()),
+            Body::Multipart {
+                text: "Harambee".to_owned(),
+                html: "<p>Harambee</p>".to_owned(),
+            },
+        ];
+        for body in bodies {
+            let email_message = EmailMessage {
+                from: Some(Mailbox::new(
+                    "Wangari Maathai",
+                    "wangari.maathai@example.africa",
+                )),
+                to: vec![
+                    "kwame.nkrumah@example.africa",
+                    "julius.nyerere@example.africa",
+                ]
+                .into(),
+                subject: "Green Belt Movement Monthly Update".to_owned(),
+                body: body.clone(),
+                cc: None,
+                bcc: Some(vec!["archive@example.africa"].into()),
+                tag: Some("monthly".to_owned()),
+                reply_to: None,
+                headers: Some(vec![Header {
+                    name: "X-Campaign".to_owned(),
+                    value: "green-belt".to_owned(),
+                }]),
+                metadata: Some(HashMap::from([("region".to_owned(), "nyeri".to_owned())])),
+                attachments: None,
+                message_stream: None,
+                idempotency_key: None,
+            };
+
+            let json = serde_json::to_string(&email_message).expect("serialization to succeed");
+            let parsed: EmailMessage =
+                serde_json::from_str(&json).expect("deserialization to succeed");
+
+            expect_that!(
+                parsed.from,
+                some(eq(&email_message.from.clone().expect("a sender")))
+            );
+            expect_that!(parsed.to.as_slice(), eq(email_message.to.as_slice()));
+            expect_that!(parsed.body.text(), eq(body.text()));
+            expect_that!(parsed.body.html(), eq(body.html()));
+            expect_that!(
+                parsed.bcc.as_ref().map(Recipients::as_slice),
+                eq(email_message.bcc.as_ref().map(Recipients::as_slice))
+            );
+            expect_that!(parsed.metadata, eq(&email_message.metadata));
+            expect_that!(parsed.headers.map(|headers| headers.len()), some(eq(1)));
+        }
+    }
+
+    #[gtest]
+    fn body_exposes_its_parts() {
+        let text_body = Body::Text("Harambee".to_owned());
+        expect_that!(text_body.text(), some(eq("Harambee")));
+        expect_that!(text_body.html(), none());
+
+        let multipart_body = Body::Multipart {
+            text: "Harambee".to_owned(),
+            html: "<p>Harambee</p>".to_owned(),
+        };
+        expect_that!(multipart_body.text(), some(eq("Harambee")));
+        expect_that!(multipart_body.html(), some(eq("<p>Harambee</p>")));
+    }
+
+    #[gtest]
+    fn html_body_gains_text_alternative() {
+        let body =
+            Body::Html("<h1>Harambee</h1><p>Pull together.</p>".to_owned()).with_text_alternative();
+
+        expect_that!(body.text(), some(eq("# Harambee\n\nPull together.")));
+        expect_that!(
+            body.html(),
+            some(eq("<h1>Harambee</h1><p>Pull together.</p>"))
+        );
+
+        let text_body = Body::Text("Pull together.".to_owned()).with_text_alternative();
+        expect_that!(text_body.html(), none());
     }
 
     #[gtest]
@@ -388,6 +628,42 @@ mod tests {
         );
     }
 
+    #[gtest]
+    fn recipients_quote_display_names_with_commas() {
+        let recipients = Recipients::from(vec![
+            Mailbox::new("Maathai, Wangari", "wangari.maathai@example.africa"),
+            Mailbox::new("Thomas Sankara", "thomas.sankara@example.africa"),
+        ]);
+        let json: Value = serde_json::to_value(&recipients).expect("serialization to succeed");
+
+        expect_that!(
+            json.as_str(),
+            some(eq(
+                r#""Maathai, Wangari" <wangari.maathai@example.africa>,Thomas Sankara <thomas.sankara@example.africa>"#
+            ))
+        );
+    }
+
+    #[gtest]
+    fn recipients_round_trip_display_names_with_commas_and_quotes() {
+        let recipients = Recipients::from(vec![
+            Mailbox::new("Maathai, Wangari", "wangari.maathai@example.africa"),
+            Mailbox::new(r#"Thomas "Tom" Sankara"#, "thomas.sankara@example.africa"),
+            Mailbox::from("miriam.makeba@example.africa"),
+        ]);
+
+        let json = serde_json::to_string(&recipients).expect("serialization to succeed");
+        let parsed: Recipients = serde_json::from_str(&json).expect("deserialization to succeed");
+
+        expect_that!(parsed.as_slice(), eq(recipients.as_slice()));
+    }
+
+    #[gtest]
+    fn recipients_reject_invalid_mailboxes() {
+        let parsed = serde_json::from_str::<Recipients>(r#""Unterminated <steve.biko@""#);
+        expect_that!(parsed.is_err(), eq(true));
+    }
+
     #[gtest]
     fn attachment_serializes_all_fields() {
         let attachment = Attachment {
@@ -425,7 +701,7 @@ mod tests {
         #[gtest]
         fn email_email_message_valid_from_email() {
             let email_message = EmailMessage {
-                from: "wangari.maathai@example.africa".to_owned(),
+                from: Some("wangari.maathai@example.africa".into()),
                 to: vec!["patrice.lumumba@example.africa"].into(),
                 subject: "Environmental Restoration Initiative".to_owned(),
                 body: Body::Text(
@@ -439,6 +715,7 @@ mod tests {
                 metadata: None,
                 attachments: None,
                 message_stream: None,
+                idempotency_key: None,
             };
 
             expect_that!(email_message.validate(), ok(anything()));
@@ -447,7 +724,7 @@ mod tests {
         #[gtest]
         fn email_email_message_invalid_from_email_fails() {
             let email_message = EmailMessage {
-                from: "this-is-not-an-email-address".to_owned(),
+                from: Some("this-is-not-an-email-address".into()),
                 to: vec!["thomas.sankara@example.africa"].into(),
                 subject: "Revolutionary Economic Reforms".to_owned(),
                 body: Body::Text("The people of Burkina Faso demand self-reliance.".to_owned()),
@@ -459,6 +736,7 @@ mod tests {
                 metadata: None,
                 attachments: None,
                 message_stream: None,
+                idempotency_key: None,
             };
 
             expect_that!(email_message.validate(), err(anything()));
@@ -467,7 +745,7 @@ mod tests {
         #[gtest]
         fn email_email_message_validates_nested_to_recipients() {
             let email_message = EmailMessage {
-                from: "chimamanda.adichie@example.africa".to_owned(),
+                from: Some("chimamanda.adichie@example.africa".into()),
                 to: vec!["broken-recipient-format"].into(),
                 subject: "The Danger of a Single Story".to_owned(),
                 body: Body::Text("Our narratives shape how the world sees Africa.".to_owned()),
@@ -479,6 +757,7 @@ mod tests {
                 metadata: None,
                 attachments: None,
                 message_stream: None,
+                idempotency_key: None,
             };
 
             expect_that!(email_message.validate(), err(anything()));
@@ -1,4 +1,8 @@
 //! Error that can come up when sending a request to a service provider
+use std::time::Duration;
+
+use http::HeaderMap;
+use http::header::RETRY_AFTER;
 
 /// Errors that can occurs when talking a service provider
 #[derive(Debug, Clone, thiserror::Error)]
@@ -10,15 +14,53 @@ pub enum Error {
     /// The email failed to send due to a network error
     ///
     /// This includes connection failures, timeouts, and non-success HTTP
-    /// responses from the email service except rate limiting.
+    /// responses from the email service that carry no structured error body.
     #[error("failed to send email: {0}")]
     SendFailed(String),
 
+    /// The request cannot be built from the email
+    ///
+    /// Raised before anything is sent, for emails that can't be serialized,
+    /// signed or encoded, and for batches over the provider's limit.
+    #[error("invalid request: {0}")]
+    InvalidRequest(String),
+
+    /// The provider accepted the request but its response cannot be read
+    ///
+    /// The email may already be on its way, so sending it again could
+    /// deliver it twice.
+    #[error("unexpected response: {0}")]
+    UnexpectedResponse(String),
+
+    /// The provider rejected the request with a structured error
+    ///
+    /// Carries the HTTP status along with the provider's own error code
+    /// and message, so callers can tell rejection reasons apart.
+    #[error(
+        "provider rejected the request with status {status} (error code {error_code}): {message}"
+    )]
+    Api {
+        /// HTTP status code of the response
+        status: http::StatusCode,
+        /// Provider-specific error code
+        error_code: u16,
+        /// Human readable error message
+        message: String,
+        /// Classification derived from the provider's error code
+        ///
+        /// Overrides the classification from the HTTP status when set.
+        class: Option<ErrorClass>,
+    },
+
     /// Rate limit hit when interacting with a service provider
     ///
-    /// Back off and retry after a bit
+    /// Back off and retry after a bit, or after `retry_after` when the
+    /// provider says how long to wait.
     #[error("rate limit exceeded")]
-    RateLimitExceeded,
+    RateLimitExceeded {
+        /// How long the provider asked to wait before retrying
+        retry_after: Option<Duration>,
+    },
 
     /// The recipient email address is invalid or rejected
     ///
@@ -30,6 +72,125 @@ pub enum Error {
     /// A file cannot be attached to an email
     #[error("attachment error: {0}")]
     Attachment(String),
+
+    /// The message cannot be rendered as MIME
+    ///
+    /// This includes messages without a sender, invalid header names, and
+    /// failures writing to the output.
+    #[error("failed to render MIME message: {0}")]
+    Mime(String),
+
+    /// The raw message cannot be parsed
+    ///
+    /// Raised for broken headers, multipart bodies without a boundary, and
+    /// undecodable content.
+    #[error("malformed message: {0}")]
+    MalformedMessage(String),
+
+    /// Queued emails cannot be read from or written to storage
+    #[error("outbox storage error: {0}")]
+    Storage(String),
+
+    /// The SMTP server answered with an error reply
+    #[error("SMTP server replied {code}: {message}")]
+    Smtp {
+        /// Three digit reply code
+        code: u16,
+        /// Text of the reply
+        message: String,
+    },
+}
+
+/// How a failure should be handled by the caller
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorClass {
+    /// The request will keep failing as it is and shouldn't be retried
+    Permanent,
+    /// The failure is transient and the request can be retried later
+    Retryable,
+    /// The account or client setup needs fixing before sending again
+    Configuration,
+}
+
+impl ErrorClass {
+    /// Returns `true` if the failed request can be retried as is
+    pub const fn is_retryable(self) -> bool {
+        matches!(self, Self::Retryable)
+    }
+}
+
+impl Error {
+    /// Creates a [`Error::RateLimitExceeded`] from the headers of a `429` response
+    ///
+    /// Reads the `Retry-After` header when it holds a number of seconds.
+    pub fn rate_limited(headers: &HeaderMap) -> Self {
+        let retry_after = headers
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+        Self::RateLimitExceeded { retry_after }
+    }
+
+    /// Creates a [`Error::Api`] for a non-success response without a provider error body
+    ///
+    /// Such bodies usually come from a proxy or load balancer, so the HTTP
+    /// status alone decides the class.
+    pub fn unexpected_status(status: http::StatusCode) -> Self {
+        Self::Api {
+            status,
+            error_code: 0,
+            message: "unexpected response body".to_owned(),
+            class: None,
+        }
+    }
+
+    /// Returns how long the provider asked to wait before retrying, if it did
+    pub const fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimitExceeded { retry_after } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// Classifies the error to help decide whether to retry
+    ///
+    /// Provider rejections are classified from their error code when the
+    /// provider documents one, such as `PostmarkErrorCode::class` for
+    /// Postmark, and from their HTTP status otherwise.
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            Self::ConfigError(_) => ErrorClass::Configuration,
+            Self::SendFailed(_) | Self::RateLimitExceeded { .. } | Self::Storage(_) => {
+                ErrorClass::Retryable
+            }
+            Self::Api {
+                class: Some(class), ..
+            } => *class,
+            Self::Api { status, .. } => {
+                if status.is_server_error() || *status == http::StatusCode::TOO_MANY_REQUESTS {
+                    ErrorClass::Retryable
+                } else if *status == http::StatusCode::UNAUTHORIZED
+                    || *status == http::StatusCode::FORBIDDEN
+                {
+                    ErrorClass::Configuration
+                } else {
+                    ErrorClass::Permanent
+                }
+            }
+            Self::InvalidRequest(_)
+            | Self::UnexpectedResponse(_)
+            | Self::InvalidRecipient(_)
+            | Self::Attachment(_)
+            | Self::Mime(_)
+            | Self::MalformedMessage(_) => ErrorClass::Permanent,
+            Self::Smtp { code, .. } => match code {
+                400..=499 => ErrorClass::Retryable,
+                530 | 534 | 535 | 538 => ErrorClass::Configuration,
+                _ => ErrorClass::Permanent,
+            },
+        }
+    }
 }
 
 #[cfg(feature = "reqwest")]
@@ -44,3 +205,141 @@ impl From<reqwest::Error> for Error {
         }
     }
 }
+
+#[cfg(feature = "hyper")]
+impl From<hyper_util::client::legacy::Error> for Error {
+    fn from(error: hyper_util::client::legacy::Error) -> Self {
+        if error.is_connect() {
+            Error::SendFailed("connection failed".into())
+        } else {
+            Error::SendFailed(error.to_string())
+        }
+    }
+}
+
+#[cfg(feature = "hyper")]
+impl From<hyper::Error> for Error {
+    fn from(error: hyper::Error) -> Self {
+        if error.is_timeout() {
+            Error::SendFailed("connection timeout".into())
+        } else {
+            Error::SendFailed(error.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::matchers::eq;
+    use googletest::{expect_that, gtest};
+    use http::StatusCode;
+
+    use super::*;
+
+    fn api_error(status: StatusCode) -> Error {
+        Error::Api {
+            status,
+            error_code: 0,
+            message: "rejected".to_owned(),
+            class: None,
+        }
+    }
+
+    #[gtest]
+    fn rate_limit_and_network_failures_are_retryable() {
+        expect_that!(
+            Error::RateLimitExceeded { retry_after: None }.class(),
+            eq(ErrorClass::Retryable)
+        );
+        expect_that!(
+            Error::SendFailed("connection timeout".into()).class(),
+            eq(ErrorClass::Retryable)
+        );
+        expect_that!(
+            api_error(StatusCode::SERVICE_UNAVAILABLE).class(),
+            eq(ErrorClass::Retryable)
+        );
+    }
+
+    #[gtest]
+    fn rate_limited_reads_retry_after_seconds() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, http::HeaderValue::from_static("30"));
+
+        let error = Error::rate_limited(&headers);
+        expect_that!(error.retry_after(), eq(Some(Duration::from_secs(30))));
+    }
+
+    #[gtest]
+    fn rate_limited_ignores_missing_or_invalid_retry_after() {
+        expect_that!(
+            Error::rate_limited(&HeaderMap::new()).retry_after(),
+            eq(None)
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            RETRY_AFTER,
+            http::HeaderValue::from_static("Wed, 21 Oct 2026 07:28:00 GMT"),
+        );
+        expect_that!(Error::rate_limited(&headers).retry_after(), eq(None));
+    }
+
+    #[gtest]
+    fn rejected_requests_are_permanent() {
+        expect_that!(
+            api_error(StatusCode::UNPROCESSABLE_ENTITY).class(),
+            eq(ErrorClass::Permanent)
+        );
+        expect_that!(
+            Error::InvalidRecipient("nobody".into()).class(),
+            eq(ErrorClass::Permanent)
+        );
+    }
+
+    #[gtest]
+    fn client_side_and_post_acceptance_failures_are_permanent() {
+        expect_that!(
+            Error::InvalidRequest("batch too large".into()).class(),
+            eq(ErrorClass::Permanent)
+        );
+        expect_that!(
+            Error::UnexpectedResponse("missing message ID".into()).class(),
+            eq(ErrorClass::Permanent)
+        );
+    }
+
+    #[gtest]
+    fn provider_error_codes_override_the_status() {
+        let error = Error::Api {
+            status: StatusCode::UNPROCESSABLE_ENTITY,
+            error_code: 412,
+            message: "account pending approval".to_owned(),
+            class: Some(ErrorClass::Configuration),
+        };
+        expect_that!(error.class(), eq(ErrorClass::Configuration));
+    }
+
+    #[gtest]
+    fn authentication_failures_are_configuration() {
+        expect_that!(
+            api_error(StatusCode::UNAUTHORIZED).class(),
+            eq(ErrorClass::Configuration)
+        );
+        expect_that!(
+            Error::ConfigError("missing token".into()).class(),
+            eq(ErrorClass::Configuration)
+        );
+    }
+
+    #[gtest]
+    fn smtp_replies_are_classified_by_code() {
+        let smtp_error = |code| Error::Smtp {
+            code,
+            message: "rejected".to_owned(),
+        };
+        expect_that!(smtp_error(451).class(), eq(ErrorClass::Retryable));
+        expect_that!(smtp_error(535).class(), eq(ErrorClass::Configuration));
+        expect_that!(smtp_error(550).class(), eq(ErrorClass::Permanent));
+    }
+}
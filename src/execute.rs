@@ -2,7 +2,7 @@
 
 use async_trait::async_trait;
 use bytes::Bytes;
-use http::{Request, Response};
+use http::{HeaderMap, Request, Response, StatusCode};
 
 use crate::error::Error;
 
@@ -17,3 +17,143 @@ pub trait Execute: Send + Sync {
         Req: Into<Request<Bytes>> + Send,
         Res: TryFrom<Response<Bytes>, Error = Error>;
 }
+
+/// Rebuilds an HTTP response once its body has been read
+///
+/// HTTP backends use it to hand the status, headers and body of their own
+/// response type to the provider parsers.
+pub fn build_response(
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response<Bytes>, Error> {
+    let mut response = Response::builder()
+        .status(status)
+        .body(body)
+        .map_err(|err| {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?err);
+
+            Error::UnexpectedResponse(format!("failed to create response {err}"))
+        })?;
+    *response.headers_mut() = headers;
+    Ok(response)
+}
+
+/// Turns a provider's HTTP response into the expected type
+///
+/// `429 Too Many Requests` becomes [`Error::RateLimitExceeded`], other
+/// non-success statuses are handed to the provider's `error_from_response`,
+/// and successful responses are parsed into `Res`.
+pub fn parse_response<Res>(
+    response: Response<Bytes>,
+    error_from_response: fn(&Response<Bytes>) -> Error,
+) -> Result<Res, Error>
+where
+    Res: TryFrom<Response<Bytes>, Error = Error>,
+{
+    let status = response.status();
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(Error::rate_limited(response.headers()));
+    }
+    if !status.is_success() {
+        return Err(error_from_response(&response));
+    }
+    Res::try_from(response)
+}
+
+/// Sends the request with a `reqwest` client and reads the whole response
+#[cfg(feature = "reqwest")]
+pub async fn send_with_reqwest(
+    client: &reqwest::Client,
+    request: Request<Bytes>,
+) -> Result<Response<Bytes>, Error> {
+    let reqwest_request = request.try_into().inspect_err(|_err| {
+        #[cfg(feature = "tracing")]
+        tracing::error!(?_err);
+    })?;
+
+    let response = client.execute(reqwest_request).await?;
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = response.bytes().await.inspect_err(|_err| {
+        #[cfg(feature = "tracing")]
+        tracing::error!(?_err)
+    })?;
+
+    build_response(status, headers, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::matchers::{anything, eq, pat, some};
+    use googletest::{expect_that, gtest};
+    use http::HeaderValue;
+
+    use super::*;
+
+    #[gtest]
+    fn keeps_status_headers_and_body() {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("30"));
+
+        let response = build_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            headers,
+            Bytes::from_static(b"{}"),
+        )
+        .expect("response to be built");
+
+        expect_that!(response.status(), eq(StatusCode::TOO_MANY_REQUESTS));
+        expect_that!(
+            response
+                .headers()
+                .get("retry-after")
+                .map(HeaderValue::as_bytes),
+            some(eq(b"30"))
+        );
+        expect_that!(response.body().as_ref(), eq(b"{}"));
+    }
+
+    /// Response type that keeps the body
+    #[derive(Debug)]
+    struct Body(Bytes);
+
+    impl TryFrom<Response<Bytes>> for Body {
+        type Error = Error;
+
+        fn try_from(response: Response<Bytes>) -> Result<Self, Self::Error> {
+            Ok(Self(response.into_body()))
+        }
+    }
+
+    /// Error parser of a provider that rejects everything
+    fn rejected(_response: &Response<Bytes>) -> Error {
+        Error::InvalidRecipient("rejected".to_owned())
+    }
+
+    fn response(status: StatusCode) -> Response<Bytes> {
+        build_response(status, HeaderMap::new(), Bytes::from_static(b"{}"))
+            .expect("response to be built")
+    }
+
+    #[gtest]
+    fn parses_successful_responses() {
+        expect_that!(
+            parse_response::<Body>(response(StatusCode::ACCEPTED), rejected),
+            pat!(Ok(pat!(Body(eq(&Bytes::from_static(b"{}"))))))
+        );
+    }
+
+    #[gtest]
+    fn maps_failed_responses_to_errors() {
+        expect_that!(
+            parse_response::<Body>(response(StatusCode::TOO_MANY_REQUESTS), rejected),
+            pat!(Err(pat!(Error::RateLimitExceeded { .. })))
+        );
+        expect_that!(
+            parse_response::<Body>(response(StatusCode::BAD_REQUEST), rejected),
+            pat!(Err(pat!(Error::InvalidRecipient(anything()))))
+        );
+    }
+}
@@ -0,0 +1,283 @@
+//! Failover across several email providers
+//!
+//! Emails go to the first healthy provider. When a provider fails in a way
+//! another provider could avoid, it is marked unhealthy for a cooldown and
+//! the next one is tried.
+use std::fmt;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use crate::email::{EmailDelivery, EmailMessage};
+use crate::error::{Error, ErrorClass};
+use crate::service::{BoxEmailService, DynEmailService, EmailService};
+
+/// A provider of a [`Failover`] and its health
+struct Provider {
+    /// Name recorded in [`EmailDelivery::provider`]
+    name: String,
+    /// The service sending the emails
+    service: BoxEmailService,
+    /// Until when the provider is skipped, after a failure
+    unhealthy_until: Mutex<Option<Instant>>,
+}
+
+impl Provider {
+    /// Returns `true` unless the provider is cooling down after a failure
+    fn is_healthy(&self, now: Instant) -> bool {
+        self.unhealthy_until
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_none_or(|until| until <= now)
+    }
+
+    /// Sets or clears the end of the cooldown
+    fn set_unhealthy_until(&self, until: Option<Instant>) {
+        *self
+            .unhealthy_until
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = until;
+    }
+}
+
+/// Email service that falls back to the next provider when one fails
+///
+/// Providers are tried in the order they were added. Retryable and
+/// configuration errors, such as outages, rate limits or a revoked API key,
+/// move on to the next provider and mark the failing one unhealthy. Other
+/// errors, such as an invalid recipient, would fail with every provider and
+/// are returned right away.
+///
+/// Unhealthy providers are skipped until their cooldown ends, unless every
+/// provider is unhealthy, in which case they are all tried in order.
+pub struct Failover {
+    /// Providers in order of preference
+    providers: Vec<Provider>,
+    /// How long a failed provider is skipped
+    cooldown: Duration,
+}
+
+impl Default for Failover {
+    fn default() -> Self {
+        Self {
+            providers: Vec::new(),
+            cooldown: Self::DEFAULT_COOLDOWN,
+        }
+    }
+}
+
+impl fmt::Debug for Failover {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Failover")
+            .field("providers", &self.provider_names())
+            .field("cooldown", &self.cooldown)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Failover {
+    /// Cooldown used unless [`Failover::with_cooldown`] sets another one
+    pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+    /// Creates a [`Failover`] without providers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a provider after the existing ones
+    #[must_use]
+    pub fn with_provider<S>(mut self, name: impl Into<String>, service: S) -> Self
+    where
+        S: DynEmailService + 'static,
+    {
+        self.providers.push(Provider {
+            name: name.into(),
+            service: Box::new(service),
+            unhealthy_until: Mutex::new(None),
+        });
+        self
+    }
+
+    /// Sets how long a failed provider is skipped
+    ///
+    /// A longer `Retry-After` delay from the provider takes precedence.
+    #[must_use]
+    pub const fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Returns the provider names in order of preference
+    pub fn provider_names(&self) -> Vec<&str> {
+        self.providers
+            .iter()
+            .map(|provider| provider.name.as_str())
+            .collect()
+    }
+
+    /// Returns the names of the providers that are currently healthy
+    pub fn healthy_providers(&self) -> Vec<&str> {
+        let now = Instant::now();
+        self.providers
+            .iter()
+            .filter(|provider| provider.is_healthy(now))
+            .map(|provider| provider.name.as_str())
+            .collect()
+    }
+
+    /// Returns `true` if the next provider should be tried after the error
+    fn should_fail_over(error: &Error) -> bool {
+        matches!(
+            error.class(),
+            ErrorClass::Retryable | ErrorClass::Configuration
+        )
+    }
+}
+
+#[async_trait]
+impl EmailService<EmailMessage, EmailDelivery> for Failover {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Failover::send_email", skip(self, email))
+    )]
+    async fn send_email(&self, email: EmailMessage) -> Result<EmailDelivery, Error> {
+        let now = Instant::now();
+        let (healthy, unhealthy): (Vec<_>, Vec<_>) = self
+            .providers
+            .iter()
+            .partition(|provider| provider.is_healthy(now));
+        let candidates = if healthy.is_empty() {
+            unhealthy
+        } else {
+            healthy
+        };
+
+        let mut last_error = Error::ConfigError("no email provider configured".into());
+        for provider in candidates {
+            match provider.service.send(email.clone()).await {
+                Ok(mut delivery) => {
+                    provider.set_unhealthy_until(None);
+                    delivery.provider = Some(provider.name.clone());
+                    return Ok(delivery);
+                }
+                Err(err) if Self::should_fail_over(&err) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(?err, provider = %provider.name, "failing over");
+                    let cooldown = err
+                        .retry_after()
+                        .map_or(self.cooldown, |retry_after| retry_after.max(self.cooldown));
+                    provider.set_unhealthy_until(Instant::now().checked_add(cooldown));
+                    last_error = err;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Err(last_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::matchers::{anything, elements_are, eq, pat, some};
+    use googletest::{expect_that, gtest};
+
+    use super::*;
+    use crate::fixtures::{Scripted, email_message};
+
+    fn outage() -> Error {
+        Error::Api {
+            status: http::StatusCode::SERVICE_UNAVAILABLE,
+            error_code: 0,
+            message: "down for maintenance".to_owned(),
+            class: None,
+        }
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn primary_delivers_when_healthy() {
+        let primary = Scripted::default();
+        let secondary = Scripted::default();
+        let failover = Failover::new()
+            .with_provider("postmark", primary.clone())
+            .with_provider("ses", secondary.clone());
+
+        let delivery = failover
+            .send_email(email_message())
+            .await
+            .expect("email to be sent");
+
+        expect_that!(delivery.provider, some(eq("postmark")));
+        expect_that!(secondary.calls(), eq(0));
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn fails_over_and_skips_the_unhealthy_provider() {
+        let primary = Scripted::new([outage()]);
+        let secondary = Scripted::default();
+        let failover = Failover::new()
+            .with_provider("postmark", primary.clone())
+            .with_provider("ses", secondary.clone());
+
+        let delivery = failover
+            .send_email(email_message())
+            .await
+            .expect("email to be sent");
+        expect_that!(delivery.provider, some(eq("ses")));
+        expect_that!(failover.healthy_providers(), elements_are![eq(&"ses")]);
+
+        failover
+            .send_email(email_message())
+            .await
+            .expect("email to be sent");
+        expect_that!(primary.calls(), eq(1));
+        expect_that!(secondary.calls(), eq(2));
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn invalid_recipients_do_not_fail_over() {
+        let primary = Scripted::new([Error::InvalidRecipient("nobody".to_owned())]);
+        let secondary = Scripted::default();
+        let failover = Failover::new()
+            .with_provider("postmark", primary)
+            .with_provider("ses", secondary.clone());
+
+        let result = failover.send_email(email_message()).await;
+
+        expect_that!(result, pat!(Err(pat!(Error::InvalidRecipient(anything())))));
+        expect_that!(secondary.calls(), eq(0));
+        expect_that!(failover.healthy_providers().len(), eq(2));
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn tries_unhealthy_providers_when_all_are_down() {
+        let primary = Scripted::new([outage()]);
+        let secondary = Scripted::new([outage()]);
+        let failover = Failover::new()
+            .with_provider("postmark", primary)
+            .with_provider("ses", secondary)
+            .with_cooldown(Duration::from_secs(3600));
+
+        let result = failover.send_email(email_message()).await;
+        expect_that!(result, pat!(Err(pat!(Error::Api { .. }))));
+        expect_that!(failover.healthy_providers().len(), eq(0));
+
+        let delivery = failover
+            .send_email(email_message())
+            .await
+            .expect("email to be sent");
+        expect_that!(delivery.provider, some(eq("postmark")));
+        expect_that!(failover.healthy_providers(), elements_are![eq(&"postmark")]);
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn fails_without_providers() {
+        let result = Failover::new().send_email(email_message()).await;
+        expect_that!(result, pat!(Err(pat!(Error::ConfigError(anything())))));
+    }
+}
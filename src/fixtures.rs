@@ -0,0 +1,146 @@
+//! Messages, services and stores shared by the unit tests
+#[cfg(any(feature = "dedupe", feature = "outbox"))]
+use tempfile::TempDir;
+
+#[cfg(any(
+    feature = "failover",
+    feature = "outbox",
+    feature = "router",
+    feature = "test-util"
+))]
+use crate::email::{Body, EmailMessage};
+#[cfg(feature = "outbox")]
+use crate::outbox::FileOutbox;
+
+/// Creates a plain text email with a sender and a single recipient
+#[cfg(any(
+    feature = "failover",
+    feature = "outbox",
+    feature = "router",
+    feature = "test-util"
+))]
+pub fn email_message() -> EmailMessage {
+    EmailMessage {
+        from: Some("wangari.maathai@example.africa".into()),
+        to: vec!["kwame.nkrumah@example.africa"].into(),
+        subject: "Green Belt Movement Monthly Update".to_owned(),
+        body: Body::Text("We planted 10,000 trees.".to_owned()),
+        cc: None,
+        bcc: None,
+        tag: None,
+        reply_to: None,
+        headers: None,
+        metadata: None,
+        attachments: None,
+        message_stream: None,
+        idempotency_key: None,
+    }
+}
+
+/// Creates a temporary directory, removed once dropped
+#[cfg(any(feature = "dedupe", feature = "outbox"))]
+pub fn temp_dir() -> TempDir {
+    tempfile::Builder::new()
+        .prefix("sendout-")
+        .tempdir()
+        .expect("temporary directory to be created")
+}
+
+/// Opens an outbox in a new temporary directory, removed once dropped
+#[cfg(feature = "outbox")]
+pub async fn temp_outbox() -> (TempDir, FileOutbox) {
+    let dir = temp_dir();
+    let outbox = FileOutbox::open(dir.path()).await.expect("outbox to open");
+    (dir, outbox)
+}
+
+#[cfg(any(
+    feature = "failover",
+    feature = "outbox",
+    feature = "router",
+    all(feature = "dedupe", feature = "test-util")
+))]
+pub use self::scripted::Scripted;
+
+/// Stub email service for the combinators' tests
+#[cfg(any(
+    feature = "failover",
+    feature = "outbox",
+    feature = "router",
+    all(feature = "dedupe", feature = "test-util")
+))]
+mod scripted {
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+
+    use crate::email::{EmailDelivery, EmailMessage};
+    use crate::error::Error;
+    use crate::service::EmailService;
+
+    /// Service failing with scripted errors, then accepting every email
+    ///
+    /// Deliveries are numbered, the first one having the message ID
+    /// `message-1`.
+    #[derive(Debug, Clone, Default)]
+    pub struct Scripted {
+        /// Errors returned in order before succeeding
+        errors: Arc<Mutex<VecDeque<Error>>>,
+        /// Subjects of the emails received
+        subjects: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Scripted {
+        /// Creates a service failing with the errors first
+        #[cfg(any(
+            feature = "failover",
+            feature = "outbox",
+            all(feature = "dedupe", feature = "test-util")
+        ))]
+        pub fn new(errors: impl IntoIterator<Item = Error>) -> Self {
+            Self {
+                errors: Arc::new(Mutex::new(errors.into_iter().collect())),
+                subjects: Arc::default(),
+            }
+        }
+
+        /// Returns the number of emails received, failed or not
+        #[cfg(any(
+            feature = "failover",
+            feature = "outbox",
+            all(feature = "dedupe", feature = "test-util")
+        ))]
+        pub fn calls(&self) -> usize {
+            self.subjects.lock().expect("unpoisoned mutex").len()
+        }
+
+        /// Returns the subjects of the emails received
+        #[cfg(all(feature = "schedule", feature = "test-util"))]
+        pub fn subjects(&self) -> Vec<String> {
+            self.subjects.lock().expect("unpoisoned mutex").clone()
+        }
+    }
+
+    #[async_trait]
+    impl EmailService<EmailMessage, EmailDelivery> for Scripted {
+        async fn send_email(&self, email: EmailMessage) -> Result<EmailDelivery, Error> {
+            let calls = {
+                let mut subjects = self.subjects.lock().expect("unpoisoned mutex");
+                subjects.push(email.subject);
+                subjects.len()
+            };
+            if let Some(err) = self.errors.lock().expect("unpoisoned mutex").pop_front() {
+                return Err(err);
+            }
+            Ok(EmailDelivery {
+                to: email.to.as_slice()[0].email.clone(),
+                submitted_at: "2026-10-16T08:30:00Z".to_owned(),
+                message_id: format!("message-{calls}"),
+                error_code: 0,
+                message: "OK".to_owned(),
+                provider: None,
+            })
+        }
+    }
+}
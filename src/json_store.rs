@@ -0,0 +1,238 @@
+//! JSON files in a directory, shared by the file-backed stores
+//!
+//! Each value lives in its own file, named after the hex-encoded SHA-256
+//! hash of its key so keys of any length fit. Values are written to a new
+//! temporary file and flushed to disk first, then moved into place, so
+//! readers never see a partial file, even after a crash. Files that can't be
+//! parsed are renamed with a `.corrupt` extension and treated as missing, so
+//! one bad file doesn't block the others.
+use std::fmt::Write;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+
+use crate::error::Error;
+
+/// Extension of the files holding values
+const EXTENSION: &str = "json";
+
+/// Returns the path of the file for the key in the directory
+pub fn path(dir: &Path, key: &str) -> PathBuf {
+    let name =
+        Sha256::digest(key.as_bytes())
+            .iter()
+            .fold(String::with_capacity(64), |mut name, byte| {
+                let _ = write!(name, "{byte:02x}");
+                name
+            });
+    dir.join(name).with_extension(EXTENSION)
+}
+
+/// Creates the directory and its parents, if needed
+pub async fn create_dir(dir: &Path) -> Result<(), Error> {
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|err| storage_error("cannot create", dir, &err))
+}
+
+/// Returns whether the file exists
+#[cfg(feature = "outbox")]
+pub async fn exists(path: &Path) -> Result<bool, Error> {
+    tokio::fs::try_exists(path)
+        .await
+        .map_err(|err| storage_error("cannot read", path, &err))
+}
+
+/// Reads the value at the path, if the file exists and can be parsed
+pub async fn read<T>(path: &Path) -> Result<Option<T>, Error>
+where
+    T: DeserializeOwned,
+{
+    match tokio::fs::read(path).await {
+        Ok(json) => parse(path, &json).await,
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(storage_error("cannot read", path, &err)),
+    }
+}
+
+/// Reads every value in the directory, with the path of its file
+pub async fn read_all<T>(dir: &Path) -> Result<Vec<(PathBuf, T)>, Error>
+where
+    T: DeserializeOwned,
+{
+    let mut files = tokio::fs::read_dir(dir)
+        .await
+        .map_err(|err| storage_error("cannot read", dir, &err))?;
+
+    let mut values = Vec::new();
+    while let Some(file) = files
+        .next_entry()
+        .await
+        .map_err(|err| storage_error("cannot read", dir, &err))?
+    {
+        let path = file.path();
+        if path
+            .extension()
+            .is_none_or(|extension| extension != EXTENSION)
+        {
+            continue;
+        }
+        let json = match tokio::fs::read(&path).await {
+            Ok(json) => json,
+            // Removed while listing
+            Err(err) if err.kind() == ErrorKind::NotFound => continue,
+            Err(err) => return Err(storage_error("cannot read", &path, &err)),
+        };
+        if let Some(value) = parse(&path, &json).await? {
+            values.push((path, value));
+        }
+    }
+    Ok(values)
+}
+
+/// Parses the content of the file, quarantining it when that fails
+async fn parse<T>(path: &Path, json: &[u8]) -> Result<Option<T>, Error>
+where
+    T: DeserializeOwned,
+{
+    match serde_json::from_slice(json) {
+        Ok(value) => Ok(Some(value)),
+        Err(_err) => {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?_err, path = %path.display(), "quarantining corrupt file");
+            quarantine(path).await.map(|()| None)
+        }
+    }
+}
+
+/// Writes the value, replacing any previous file
+pub async fn write<T>(path: &Path, value: &T) -> Result<(), Error>
+where
+    T: Serialize,
+{
+    let tmp = write_temp(path, value).await?;
+    if let Err(err) = tokio::fs::rename(&tmp, path).await {
+        discard(&tmp).await;
+        return Err(storage_error("cannot write", path, &err));
+    }
+    sync_dir(path).await
+}
+
+/// Writes the value unless the file already exists
+///
+/// Returns `false` when it does. The file is linked into place, which fails
+/// if it exists, so concurrent calls for the same path create it only once.
+#[cfg(feature = "outbox")]
+pub async fn create<T>(path: &Path, value: &T) -> Result<bool, Error>
+where
+    T: Serialize,
+{
+    let tmp = write_temp(path, value).await?;
+    let created = match tokio::fs::hard_link(&tmp, path).await {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == ErrorKind::AlreadyExists => Ok(false),
+        Err(err) => Err(storage_error("cannot write", path, &err)),
+    };
+    remove(&tmp).await?;
+    let created = created?;
+    if created {
+        sync_dir(path).await?;
+    }
+    Ok(created)
+}
+
+/// Writes the value to a new temporary file next to the path
+///
+/// Each call gets its own file, opened with `create_new`, so concurrent
+/// writers of the same path don't write into each other's file.
+async fn write_temp<T>(path: &Path, value: &T) -> Result<PathBuf, Error>
+where
+    T: Serialize,
+{
+    /// Number of temporary files created by this process
+    static TEMP_FILES: AtomicU64 = AtomicU64::new(0);
+
+    let json = serde_json::to_vec(value)
+        .map_err(|err| Error::Storage(format!("cannot serialize {}: {err}", path.display())))?;
+    let tmp = path.with_extension(format!(
+        "{}-{}.tmp",
+        std::process::id(),
+        TEMP_FILES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let mut file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&tmp)
+        .await
+        .map_err(|err| storage_error("cannot write", &tmp, &err))?;
+    let written = async {
+        file.write_all(&json).await?;
+        file.sync_all().await
+    }
+    .await;
+    drop(file);
+    if let Err(err) = written {
+        discard(&tmp).await;
+        return Err(storage_error("cannot write", &tmp, &err));
+    }
+    Ok(tmp)
+}
+
+/// Removes a temporary file after a failed write
+///
+/// Failing to remove it is only logged, so the write error is reported.
+async fn discard(tmp: &Path) {
+    let _ = remove(tmp).await;
+}
+
+/// Flushes the directory holding the path, so a new name survives a crash
+///
+/// Directories can only be opened for syncing on Unix, elsewhere the new
+/// name is left for the file system to persist.
+async fn sync_dir(path: &Path) -> Result<(), Error> {
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        let file = tokio::fs::File::open(dir)
+            .await
+            .map_err(|err| storage_error("cannot sync", dir, &err))?;
+        file.sync_all()
+            .await
+            .map_err(|err| storage_error("cannot sync", dir, &err))?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// Removes the file, if it exists
+pub async fn remove(path: &Path) -> Result<(), Error> {
+    match tokio::fs::remove_file(path).await {
+        Err(err) if err.kind() != ErrorKind::NotFound => {
+            Err(storage_error("cannot remove", path, &err))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Moves a file that can't be parsed out of the way
+async fn quarantine(path: &Path) -> Result<(), Error> {
+    let corrupt = path.with_extension("corrupt");
+    match tokio::fs::rename(path, &corrupt).await {
+        Err(err) if err.kind() != ErrorKind::NotFound => {
+            Err(storage_error("cannot quarantine", path, &err))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Builds the error for a failed file operation
+fn storage_error(action: &str, path: &Path, err: &std::io::Error) -> Error {
+    #[cfg(feature = "tracing")]
+    tracing::error!(?err, path = %path.display());
+    Error::Storage(format!("{action} {}: {err}", path.display()))
+}
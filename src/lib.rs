@@ -6,15 +6,67 @@
 //! from your app.
 #![cfg_attr(docsrs, feature(doc_cfg))]
 pub mod config;
+#[cfg(any(
+    feature = "mailgun",
+    feature = "mime",
+    feature = "resend",
+    feature = "sendgrid",
+    feature = "ses",
+    feature = "test-util"
+))]
+mod datetime;
 pub mod error;
 #[macro_use]
 mod macros;
 pub mod api;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(any(feature = "dedupe", feature = "outbox"))]
+pub mod clock;
+#[cfg(feature = "dedupe")]
+pub mod dedupe;
 pub mod email;
 pub mod execute;
+#[cfg(feature = "failover")]
+pub mod failover;
+#[cfg(all(
+    test,
+    any(
+        feature = "dedupe",
+        feature = "failover",
+        feature = "outbox",
+        feature = "router",
+        feature = "test-util"
+    )
+))]
+mod fixtures;
+#[cfg(any(feature = "dedupe", feature = "outbox"))]
+mod json_store;
+#[cfg(feature = "mailgun")]
+pub mod mailgun;
+#[cfg(feature = "mime")]
+pub mod mime;
+#[cfg(feature = "outbox")]
+pub mod outbox;
 #[cfg(feature = "postmark")]
 pub mod postmark;
+#[cfg(feature = "resend")]
+pub mod resend;
+#[cfg(feature = "retry")]
+pub mod retry;
+#[cfg(feature = "router")]
+pub mod router;
+#[cfg(feature = "schedule")]
+pub mod schedule;
+#[cfg(feature = "sendgrid")]
+pub mod sendgrid;
 pub mod service;
+#[cfg(feature = "ses")]
+pub mod ses;
+#[cfg(feature = "smtp")]
+pub mod smtp;
+#[cfg(feature = "tower")]
+pub mod tower;
 
 #[doc(inline)]
 pub use self::api::ApiRequest;
@@ -23,4 +75,4 @@ pub use self::execute::Execute;
 #[doc(inline)]
 pub use config::ServiceConfig;
 #[doc(inline)]
-pub use service::EmailService;
+pub use service::{BatchEmailService, DynEmailService, EmailService};
@@ -0,0 +1,15 @@
+//! Email sending module with Mailgun
+
+pub mod client;
+pub mod config;
+pub mod request;
+pub mod response;
+
+#[doc(inline)]
+pub use client::MailgunClient;
+#[doc(inline)]
+pub use config::{MailgunConfig, MailgunRegion};
+#[doc(inline)]
+pub use request::MailgunEmailRequest;
+#[doc(inline)]
+pub use response::{MailgunEmailResponse, MailgunErrorResponse};
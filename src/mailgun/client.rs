@@ -0,0 +1,103 @@
+//! The HTTP client that talks to the Mailgun API
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use bytes::Bytes;
+use http::{Request, Response};
+use secrecy::ExposeSecret;
+
+#[cfg(feature = "reqwest")]
+pub mod reqwest;
+
+use super::config::MailgunConfig;
+use crate::api::{ApiRequest, encode_body};
+use crate::email::{EmailMessage, MessageDefaults};
+use crate::error::Error;
+use crate::execute::parse_response;
+use crate::mailgun::MailgunErrorResponse;
+
+/// Client for interacting with Mailgun APIs
+#[derive(Debug)]
+pub struct MailgunClient<C> {
+    /// Mailgun configuration
+    pub config: MailgunConfig,
+    /// HTTP Client
+    pub client: C,
+    /// Defaults applied to every [`EmailMessage`] before it is sent
+    pub defaults: MessageDefaults,
+}
+
+impl<C> MailgunClient<C> {
+    /// User name paired with the API key in basic authentication
+    const API_USER: &str = "api";
+
+    /// Creates new [`MailgunClient`] instance
+    pub const fn new(client: C, config: MailgunConfig) -> Self {
+        Self {
+            client,
+            config,
+            defaults: MessageDefaults::new(),
+        }
+    }
+
+    /// Sets the defaults applied to outgoing messages
+    #[must_use]
+    pub fn with_defaults(mut self, defaults: MessageDefaults) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    /// Applies [`MessageDefaults::prepare`] with [`MailgunConfig::from_email`]
+    pub fn prepare_email(&self, email: EmailMessage) -> Result<EmailMessage, Error> {
+        self.defaults.prepare(email, &self.config.from_email)
+    }
+
+    /// Creates new HTTP request for the domain's Mailgun API
+    ///
+    /// The request goes to `/v3/<domain>` followed by [`ApiRequest::ENDPOINT`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "MailgunClient::new_http_request",
+            skip(self, request),
+            err(Debug)
+        )
+    )]
+    pub fn new_http_request<R: ApiRequest>(&self, request: &R) -> Result<Request<Bytes>, Error> {
+        let encoded = encode_body(request)?;
+        let uri = format!(
+            "{}/v3/{}{}",
+            self.config.base_url(),
+            self.config.domain,
+            R::ENDPOINT
+        );
+        let credentials = BASE64_STANDARD.encode(format!(
+            "{}:{}",
+            Self::API_USER,
+            self.config.api_key.expose_secret()
+        ));
+
+        Request::builder()
+            .method(R::METHOD)
+            .uri(uri)
+            .header("content-type", encoded.content_type)
+            .header("accept", "application/json")
+            .header("authorization", format!("Basic {credentials}"))
+            .body(encoded.body)
+            .map_err(|err| {
+                #[cfg(feature = "tracing")]
+                tracing::error!(?err);
+                Error::InvalidRequest(format!("failed to build HTTP request: {err}"))
+            })
+    }
+
+    /// Parses a Mailgun HTTP response into the expected type
+    ///
+    /// Non-success statuses become errors, with `429 Too Many Requests`
+    /// mapped to [`Error::RateLimitExceeded`].
+    pub fn parse_response<Res>(response: Response<Bytes>) -> Result<Res, Error>
+    where
+        Res: TryFrom<Response<Bytes>, Error = Error>,
+    {
+        parse_response(response, MailgunErrorResponse::error_from_response)
+    }
+}
@@ -0,0 +1,63 @@
+//! EmailService and Execute trait implementation for [`MailgunClient<reqwest::Client>`]
+use async_trait::async_trait;
+use bytes::Bytes;
+use http::Request;
+use reqwest::Client;
+
+use super::MailgunClient;
+use crate::email::{EmailDelivery, EmailMessage};
+use crate::error::Error;
+use crate::execute::{Execute, send_with_reqwest};
+use crate::mailgun::{MailgunEmailRequest, MailgunEmailResponse};
+use crate::service::{BatchEmailService, EmailService, send_each};
+
+#[async_trait]
+impl EmailService<EmailMessage, EmailDelivery> for MailgunClient<Client> {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "MailgunClient::send_email")
+    )]
+    async fn send_email(&self, email: EmailMessage) -> Result<EmailDelivery, Error> {
+        let email = self.prepare_email(email)?;
+        let to = email.to.as_slice().to_vec();
+        let request = self.new_http_request(&MailgunEmailRequest::try_from(email)?)?;
+
+        let response: MailgunEmailResponse = self.execute(request).await.inspect_err(|_err| {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?_err);
+        })?;
+
+        Ok(response.into_delivery(&to))
+    }
+}
+
+#[async_trait]
+impl BatchEmailService<EmailMessage, EmailDelivery> for MailgunClient<Client> {
+    /// Sends each email with its own request, as Mailgun batches only recipients of a single message
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "MailgunClient::send_batch", skip(emails), fields(batch_size = emails.len()))
+    )]
+    async fn send_batch(
+        &self,
+        emails: Vec<EmailMessage>,
+    ) -> Result<Vec<Result<EmailDelivery, Error>>, Error> {
+        Ok(send_each(self, emails).await)
+    }
+}
+
+#[async_trait]
+impl Execute for MailgunClient<reqwest::Client> {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "MailgunClient::execute", skip(self, request), err(Debug))
+    )]
+    async fn execute<Req, Res>(&self, request: Req) -> Result<Res, Error>
+    where
+        Req: Into<Request<Bytes>> + Send,
+        Res: TryFrom<http::Response<Bytes>, Error = Error>,
+    {
+        let response = send_with_reqwest(&self.client, request.into()).await?;
+        Self::parse_response(response)
+    }
+}
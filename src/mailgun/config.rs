@@ -0,0 +1,161 @@
+//! Configuration for sending through Mailgun
+use std::env::VarError;
+use std::str::FromStr;
+
+use secrecy::SecretString;
+use serde::Deserialize;
+
+use crate::config::ServiceConfig;
+use crate::error::Error;
+
+/// Region hosting the Mailgun domain
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MailgunRegion {
+    /// United States, `https://api.mailgun.net`
+    #[default]
+    Us,
+    /// European Union, `https://api.eu.mailgun.net`
+    Eu,
+}
+
+impl MailgunRegion {
+    /// Returns the API endpoint of the region
+    pub const fn base_url(self) -> &'static str {
+        match self {
+            Self::Us => "https://api.mailgun.net",
+            Self::Eu => "https://api.eu.mailgun.net",
+        }
+    }
+}
+
+impl FromStr for MailgunRegion {
+    type Err = Error;
+
+    fn from_str(region: &str) -> Result<Self, Self::Err> {
+        if region.eq_ignore_ascii_case("us") {
+            Ok(Self::Us)
+        } else if region.eq_ignore_ascii_case("eu") {
+            Ok(Self::Eu)
+        } else {
+            Err(Error::ConfigError(format!(
+                "unknown Mailgun region: {region}"
+            )))
+        }
+    }
+}
+
+/// Mailgun configuration data
+#[must_use]
+#[derive(Debug, Clone, Deserialize)]
+pub struct MailgunConfig {
+    /// Private API key, sent with basic authentication
+    pub api_key: SecretString,
+    /// Sending domain, such as `mg.example.com`
+    pub domain: String,
+    /// Region hosting the domain
+    #[serde(default)]
+    pub region: MailgunRegion,
+    /// API endpoint overriding the regional one
+    pub base_url: Option<String>,
+    /// The verified sender email address
+    ///
+    /// Used for messages that don't set their own sender.
+    pub from_email: String,
+}
+
+impl MailgunConfig {
+    /// Environment variable for the private API key
+    pub const MAILGUN_API_KEY: &str = "MAILGUN_API_KEY";
+    /// Environment variable for the sending domain
+    pub const MAILGUN_DOMAIN: &str = "MAILGUN_DOMAIN";
+    /// Environment variable for the region, `us` or `eu`
+    pub const MAILGUN_REGION: &str = "MAILGUN_REGION";
+
+    /// Creates [`MailgunConfig`] from environment variables
+    ///
+    /// The region defaults to the US one. The sender and endpoint override
+    /// are read from [`ServiceConfig::SENDOUT_FROM_EMAIL`] and
+    /// [`ServiceConfig::SENDOUT_BASE_URL`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use sendout::mailgun::MailgunConfig;
+    /// let mailgun_config = MailgunConfig::from_env()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "MailgunConfig::from_env", err(Debug))
+    )]
+    pub fn from_env() -> Result<Self, Error> {
+        let required = |name: &str| {
+            std::env::var(name).map_err(|_err| {
+                #[cfg(feature = "tracing")]
+                tracing::error!(%_err);
+                Error::ConfigError(format!("{name} not set"))
+            })
+        };
+        let optional = |name: &str| match std::env::var(name) {
+            Ok(value) => Ok(Some(value)),
+            Err(VarError::NotPresent) => Ok(None),
+            Err(_err) => {
+                #[cfg(feature = "tracing")]
+                tracing::error!(%_err);
+                Err(Error::ConfigError(format!("{name} not set")))
+            }
+        };
+
+        Ok(Self {
+            api_key: required(Self::MAILGUN_API_KEY).map(SecretString::from)?,
+            domain: required(Self::MAILGUN_DOMAIN)?,
+            region: optional(Self::MAILGUN_REGION)?
+                .map(|region| region.parse())
+                .transpose()?
+                .unwrap_or_default(),
+            base_url: optional(ServiceConfig::SENDOUT_BASE_URL)?,
+            from_email: required(ServiceConfig::SENDOUT_FROM_EMAIL)?,
+        })
+    }
+
+    /// Returns the API endpoint, the regional one unless overridden
+    pub fn base_url(&self) -> &str {
+        self.base_url
+            .as_deref()
+            .unwrap_or_else(|| self.region.base_url())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::matchers::{anything, eq, err, ok, pat};
+    use googletest::{expect_that, gtest};
+
+    use super::*;
+
+    #[gtest]
+    fn parses_regions() {
+        expect_that!("EU".parse::<MailgunRegion>(), ok(eq(&MailgunRegion::Eu)));
+        expect_that!("us".parse::<MailgunRegion>(), ok(eq(&MailgunRegion::Us)));
+        expect_that!(
+            "af".parse::<MailgunRegion>(),
+            err(pat!(Error::ConfigError(anything())))
+        );
+    }
+
+    #[gtest]
+    fn base_url_defaults_to_the_regional_endpoint() {
+        let mut config = MailgunConfig {
+            api_key: SecretString::from("key-example"),
+            domain: "mg.example.africa".into(),
+            region: MailgunRegion::Eu,
+            base_url: None,
+            from_email: "sender@example.africa".into(),
+        };
+        expect_that!(config.base_url(), eq("https://api.eu.mailgun.net"));
+
+        config.base_url = Some("http://localhost:8080".into());
+        expect_that!(config.base_url(), eq("http://localhost:8080"));
+    }
+}
@@ -0,0 +1,296 @@
+//! Mailgun-specific request types
+use std::collections::BTreeMap;
+
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use bytes::Bytes;
+#[cfg(feature = "garde")]
+use garde::Validate;
+use http::Method;
+use serde::{Serialize, Serializer};
+
+use crate::api::{ApiRequest, Encoding, FilePart};
+use crate::email::{Attachment, Body, EmailMessage, Recipients};
+use crate::error::Error;
+
+/// Mailgun message request, sent as `multipart/form-data`
+///
+/// The tag becomes an `o:tag` option, headers are sent as `h:` fields and
+/// metadata as `v:` variables. The message stream has no Mailgun equivalent
+/// so it is dropped.
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "garde", derive(Validate))]
+pub struct MailgunEmailRequest {
+    /// The sender, as in `Jane Doe <jane@example.com>`
+    #[cfg_attr(feature = "garde", garde(required))]
+    pub from: Option<String>,
+    /// To recipients
+    #[cfg_attr(feature = "garde", garde(length(min = 1, max = 1000)))]
+    pub to: Vec<String>,
+    /// Cc recipients
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub cc: Option<Vec<String>>,
+    /// Bcc recipients
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub bcc: Option<Vec<String>>,
+    /// Email subject
+    #[cfg_attr(feature = "garde", garde(length(min = 1)))]
+    pub subject: String,
+    /// Plain text body
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub text: Option<String>,
+    /// HTML body
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub html: Option<String>,
+    /// Tags used to group statistics
+    #[serde(rename = "o:tag")]
+    #[cfg_attr(feature = "garde", garde(length(max = 3)))]
+    pub tags: Option<Vec<String>>,
+    /// Reply-To addresses, comma separated
+    #[serde(rename = "h:Reply-To")]
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub reply_to: Option<String>,
+    /// Custom headers, by name
+    #[serde(flatten, serialize_with = "serialize_headers")]
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub headers: BTreeMap<String, String>,
+    /// Custom variables passed back in webhooks, by name
+    #[serde(flatten, serialize_with = "serialize_variables")]
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub variables: BTreeMap<String, String>,
+    /// Attachments and inline images
+    #[serde(skip)]
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub attachments: Vec<FilePart>,
+}
+
+impl ApiRequest for MailgunEmailRequest {
+    const METHOD: Method = Method::POST;
+    /// Relative to the domain, as in `/v3/<domain>/messages`
+    const ENDPOINT: &'static str = "/messages";
+    const ENCODING: Encoding = Encoding::Multipart;
+
+    fn files(&self) -> Vec<FilePart> {
+        self.attachments.clone()
+    }
+}
+
+impl TryFrom<EmailMessage> for MailgunEmailRequest {
+    type Error = Error;
+
+    /// Converts the message, decoding its attachments
+    fn try_from(email: EmailMessage) -> Result<Self, Self::Error> {
+        let addresses = |recipients: Recipients| {
+            recipients
+                .as_slice()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        };
+        let (text, html) = match email.body {
+            Body::Text(text) => (Some(text), None),
+            Body::Html(html) => (None, Some(html)),
+            Body::Multipart { text, html } => (Some(text), Some(html)),
+        };
+        let attachments = email
+            .attachments
+            .unwrap_or_default()
+            .into_iter()
+            .map(FilePart::try_from)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            from: email.from.map(|from| from.to_string()),
+            to: addresses(email.to),
+            cc: email.cc.map(addresses),
+            bcc: email.bcc.map(addresses),
+            subject: email.subject,
+            text,
+            html,
+            tags: email.tag.map(|tag| vec![tag]),
+            reply_to: email
+                .reply_to
+                .map(|reply_to| addresses(reply_to).join(", ")),
+            headers: email
+                .headers
+                .unwrap_or_default()
+                .into_iter()
+                .map(|header| (header.name, header.value))
+                .collect(),
+            variables: email.metadata.unwrap_or_default().into_iter().collect(),
+            attachments,
+        })
+    }
+}
+
+impl TryFrom<Attachment> for FilePart {
+    type Error = Error;
+
+    /// Decodes the attachment into an `attachment` part
+    ///
+    /// Attachments with a content ID become `inline` parts named after it,
+    /// since Mailgun matches `cid:` references against file names.
+    fn try_from(attachment: Attachment) -> Result<Self, Self::Error> {
+        let content = BASE64_STANDARD.decode(&attachment.content).map_err(|err| {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?err);
+            Error::Attachment(format!("{} is not valid base64: {err}", attachment.name))
+        })?;
+        let (field, filename) = match attachment.content_id {
+            Some(id) => (
+                "inline",
+                id.strip_prefix("cid:").map(str::to_owned).unwrap_or(id),
+            ),
+            None => ("attachment", attachment.name),
+        };
+        Ok(Self {
+            field: field.to_owned(),
+            filename,
+            content_type: attachment.content_type,
+            content: Bytes::from(content),
+        })
+    }
+}
+
+/// Serializes headers as `h:<name>` fields
+fn serialize_headers<S: Serializer>(
+    headers: &BTreeMap<String, String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(
+        headers
+            .iter()
+            .map(|(name, value)| (format!("h:{name}"), value)),
+    )
+}
+
+/// Serializes variables as `v:<name>` fields
+fn serialize_variables<S: Serializer>(
+    variables: &BTreeMap<String, String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(
+        variables
+            .iter()
+            .map(|(name, value)| (format!("v:{name}"), value)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use googletest::matchers::{anything, eq, err, pat};
+    use googletest::{expect_that, gtest};
+    use serde_json::json;
+
+    use super::*;
+    use crate::email::{Header, Mailbox};
+
+    fn email_message() -> EmailMessage {
+        EmailMessage {
+            from: Some(Mailbox::new(
+                "Wangari Maathai",
+                "wangari.maathai@example.africa",
+            )),
+            to: vec![
+                "kwame.nkrumah@example.africa",
+                "julius.nyerere@example.africa",
+            ]
+            .into(),
+            subject: "Green Belt Movement Monthly Update".to_owned(),
+            body: Body::Multipart {
+                text: "We planted 10,000 trees.".to_owned(),
+                html: "<p>We planted 10,000 trees.</p>".to_owned(),
+            },
+            cc: None,
+            bcc: None,
+            tag: Some("monthly".to_owned()),
+            reply_to: Some(vec!["replies@example.africa"].into()),
+            headers: Some(vec![Header {
+                name: "X-Campaign".to_owned(),
+                value: "green-belt".to_owned(),
+            }]),
+            metadata: Some(HashMap::from([("region".to_owned(), "nyeri".to_owned())])),
+            attachments: None,
+            message_stream: None,
+            idempotency_key: None,
+        }
+    }
+
+    #[gtest]
+    fn serializes_form_fields() {
+        let request =
+            MailgunEmailRequest::try_from(email_message()).expect("conversion to succeed");
+        let json = serde_json::to_value(&request).expect("serialization to succeed");
+
+        expect_that!(
+            json,
+            eq(&json!({
+                "from": "Wangari Maathai <wangari.maathai@example.africa>",
+                "to": ["kwame.nkrumah@example.africa", "julius.nyerere@example.africa"],
+                "subject": "Green Belt Movement Monthly Update",
+                "text": "We planted 10,000 trees.",
+                "html": "<p>We planted 10,000 trees.</p>",
+                "o:tag": ["monthly"],
+                "h:Reply-To": "replies@example.africa",
+                "h:X-Campaign": "green-belt",
+                "v:region": "nyeri",
+            }))
+        );
+    }
+
+    #[gtest]
+    fn attachments_become_file_parts() {
+        let mut email = email_message();
+        email.attachments = Some(vec![
+            Attachment {
+                name: "trees.csv".to_owned(),
+                content: BASE64_STANDARD.encode("kenya,10000"),
+                content_type: "text/csv".to_owned(),
+                content_id: None,
+            },
+            Attachment {
+                name: "logo.png".to_owned(),
+                content: BASE64_STANDARD.encode("png"),
+                content_type: "image/png".to_owned(),
+                content_id: Some("cid:logo".to_owned()),
+            },
+        ]);
+        let request = MailgunEmailRequest::try_from(email).expect("conversion to succeed");
+
+        expect_that!(
+            request.files(),
+            eq(&vec![
+                FilePart {
+                    field: "attachment".to_owned(),
+                    filename: "trees.csv".to_owned(),
+                    content_type: "text/csv".to_owned(),
+                    content: Bytes::from_static(b"kenya,10000"),
+                },
+                FilePart {
+                    field: "inline".to_owned(),
+                    filename: "logo".to_owned(),
+                    content_type: "image/png".to_owned(),
+                    content: Bytes::from_static(b"png"),
+                },
+            ])
+        );
+    }
+
+    #[gtest]
+    fn invalid_attachment_content_fails() {
+        let mut email = email_message();
+        email.attachments = Some(vec![Attachment {
+            name: "trees.csv".to_owned(),
+            content: "not base64!".to_owned(),
+            content_type: "text/csv".to_owned(),
+            content_id: None,
+        }]);
+        expect_that!(
+            MailgunEmailRequest::try_from(email),
+            err(pat!(Error::Attachment(anything())))
+        );
+    }
+}
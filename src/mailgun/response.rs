@@ -0,0 +1,164 @@
+//! Mailgun response types
+use std::time::SystemTime;
+
+use bytes::Bytes;
+use http::{Response, StatusCode};
+use serde::Deserialize;
+
+use crate::datetime::UtcDateTime;
+use crate::email::{EmailDelivery, Mailbox};
+use crate::error::Error;
+
+/// Mailgun message response
+#[derive(Debug, Clone, Deserialize)]
+pub struct MailgunEmailResponse {
+    /// Message ID, as in `<20260101000000.1.ABCDEF@mg.example.com>`
+    pub id: String,
+    /// Human readable status message
+    pub message: String,
+}
+
+impl MailgunEmailResponse {
+    /// Converts the response into a delivery receipt for the given recipients
+    ///
+    /// The angle brackets are stripped from the message ID to match the IDs
+    /// in Mailgun events. The submission time is the time of the conversion.
+    pub fn into_delivery(self, to: &[Mailbox]) -> EmailDelivery {
+        let message_id = self
+            .id
+            .strip_prefix('<')
+            .and_then(|id| id.strip_suffix('>'))
+            .map_or_else(|| self.id.clone(), str::to_owned);
+        EmailDelivery {
+            to: to
+                .iter()
+                .map(|mailbox| mailbox.email.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            submitted_at: UtcDateTime::from_system_time(SystemTime::now()).to_iso8601(),
+            message_id,
+            error_code: 0,
+            message: self.message,
+            provider: None,
+        }
+    }
+}
+
+impl TryFrom<Response<Bytes>> for MailgunEmailResponse {
+    type Error = Error;
+
+    fn try_from(response: Response<Bytes>) -> Result<Self, Self::Error> {
+        serde_json::from_slice(response.body())
+            .map_err(|err| Error::UnexpectedResponse(format!("failed to parse response: {err}")))
+    }
+}
+
+/// Mailgun error response body
+#[derive(Debug, Clone, Deserialize)]
+pub struct MailgunErrorResponse {
+    /// Human readable error message
+    pub message: String,
+}
+
+impl MailgunErrorResponse {
+    /// Builds the [`Error`] for a non-success HTTP response
+    ///
+    /// Mailgun answers some failures, such as a wrong API key, with a plain
+    /// text body, which is used as the message.
+    pub fn error_from_response(response: &Response<Bytes>) -> Error {
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Error::rate_limited(response.headers());
+        }
+
+        let message = match serde_json::from_slice::<Self>(response.body()) {
+            Ok(error) => error.message,
+            Err(_err) => {
+                #[cfg(feature = "tracing")]
+                tracing::error!(?_err, %status);
+                String::from_utf8_lossy(response.body()).trim().to_owned()
+            }
+        };
+        if message.is_empty() {
+            return Error::unexpected_status(status);
+        }
+        Error::Api {
+            status,
+            error_code: 0,
+            message,
+            class: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::matchers::{eq, none, pat};
+    use googletest::{expect_that, gtest};
+
+    use super::*;
+
+    fn response(status: StatusCode, body: &'static str) -> Response<Bytes> {
+        Response::builder()
+            .status(status)
+            .body(Bytes::from_static(body.as_bytes()))
+            .expect("a valid response")
+    }
+
+    #[gtest]
+    fn converts_message_id_into_delivery() {
+        let response = MailgunEmailResponse::try_from(response(
+            StatusCode::OK,
+            r#"{"id": "<20261016.1.ABC@mg.example.africa>", "message": "Queued. Thank you."}"#,
+        ))
+        .expect("response to parse");
+        let delivery = response.into_delivery(&["kwame.nkrumah@example.africa".into()]);
+
+        expect_that!(delivery.message_id, eq("20261016.1.ABC@mg.example.africa"));
+        expect_that!(delivery.to, eq("kwame.nkrumah@example.africa"));
+        expect_that!(delivery.message, eq("Queued. Thank you."));
+    }
+
+    #[gtest]
+    fn error_messages_are_api_errors() {
+        let error = MailgunErrorResponse::error_from_response(&response(
+            StatusCode::BAD_REQUEST,
+            r#"{"message": "'from' parameter is missing"}"#,
+        ));
+        expect_that!(
+            error,
+            pat!(Error::Api {
+                status: eq(&StatusCode::BAD_REQUEST),
+                message: eq("'from' parameter is missing"),
+                ..
+            })
+        );
+
+        let error = MailgunErrorResponse::error_from_response(&response(
+            StatusCode::UNAUTHORIZED,
+            "Forbidden",
+        ));
+        expect_that!(
+            error,
+            pat!(Error::Api {
+                status: eq(&StatusCode::UNAUTHORIZED),
+                message: eq("Forbidden"),
+                ..
+            })
+        );
+    }
+
+    #[gtest]
+    fn empty_bodies_are_classified_by_status() {
+        let error =
+            MailgunErrorResponse::error_from_response(&response(StatusCode::BAD_GATEWAY, ""));
+        expect_that!(
+            error,
+            pat!(Error::Api {
+                status: eq(&StatusCode::BAD_GATEWAY),
+                class: none(),
+                ..
+            })
+        );
+    }
+}
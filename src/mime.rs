@@ -0,0 +1,13 @@
+//! RFC 5322 / MIME representation of email messages
+//!
+//! Renders an [`EmailMessage`](crate::email::EmailMessage) to the raw
+//! message format stored in `.eml` files and spoken by SMTP servers, and
+//! parses such messages back.
+mod encoding;
+pub mod parse;
+pub mod render;
+
+#[doc(inline)]
+pub use parse::parse_message;
+#[doc(inline)]
+pub use render::MimeRenderer;
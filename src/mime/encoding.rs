@@ -0,0 +1,304 @@
+//! Transfer and header encodings used by MIME messages
+use std::fmt::Write;
+
+use base64::prelude::{BASE64_STANDARD, Engine};
+
+/// Recommended maximum length of a line, excluding the CRLF
+pub const MAX_LINE_LENGTH: usize = 76;
+
+/// Maximum number of bytes encoded in a single `=?UTF-8?B?...?=` word
+///
+/// Keeps each encoded word within the 75 characters allowed by RFC 2047.
+const ENCODED_WORD_BYTES: usize = 45;
+
+/// Encodes text as quoted-printable with CRLF line endings
+pub fn quoted_printable(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut lines = text.split('\n').peekable();
+    while let Some(line) = lines.next() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let mut width = 0;
+        let bytes = line.as_bytes();
+        for (idx, &byte) in bytes.iter().enumerate() {
+            let is_last = idx + 1 == bytes.len();
+            let literal = matches!(byte, b'!'..=b'<' | b'>'..=b'~')
+                || (matches!(byte, b' ' | b'\t') && !is_last);
+            let len = if literal { 1 } else { 3 };
+
+            // Keep room for the soft line break `=`
+            if width + len > MAX_LINE_LENGTH - 1 {
+                output.push_str("=\r\n");
+                width = 0;
+            }
+            if literal {
+                output.push(char::from(byte));
+            } else {
+                let _ = write!(output, "={byte:02X}");
+            }
+            width += len;
+        }
+        if lines.peek().is_some() {
+            output.push_str("\r\n");
+        }
+    }
+    output
+}
+
+/// Splits already base64-encoded content into lines of [`MAX_LINE_LENGTH`]
+pub fn wrap_base64(encoded: &str) -> String {
+    let compact = encoded
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .collect::<String>();
+    compact
+        .as_bytes()
+        .chunks(MAX_LINE_LENGTH)
+        .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+        .collect::<Vec<_>>()
+        .join("\r\n")
+}
+
+/// Encodes text as one or more RFC 2047 encoded words
+///
+/// Multi-byte characters are never split across words.
+pub fn encoded_words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut chunk = String::new();
+    for c in text.chars() {
+        if chunk.len() + c.len_utf8() > ENCODED_WORD_BYTES {
+            words.push(encoded_word(&chunk));
+            chunk.clear();
+        }
+        chunk.push(c);
+    }
+    if !chunk.is_empty() || words.is_empty() {
+        words.push(encoded_word(&chunk));
+    }
+    words
+}
+
+/// Encodes text as a single base64 encoded word
+fn encoded_word(text: &str) -> String {
+    format!("=?UTF-8?B?{}?=", BASE64_STANDARD.encode(text))
+}
+
+/// Returns `true` if the text can go into a header without encoding
+pub fn is_plain_header_text(text: &str) -> bool {
+    text.chars().all(|c| c == ' ' || c.is_ascii_graphic()) && !text.contains("=?")
+}
+
+/// Encodes a parameter value such as a file name
+///
+/// ASCII values are quoted, others use the RFC 2231 `name*=UTF-8''...` form.
+pub fn parameter(name: &str, value: &str) -> String {
+    if value.chars().all(|c| c == ' ' || c.is_ascii_graphic()) {
+        let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+        format!("{name}=\"{escaped}\"")
+    } else {
+        let mut encoded = String::with_capacity(value.len() * 3);
+        for byte in value.bytes() {
+            if byte.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&byte) {
+                encoded.push(char::from(byte));
+            } else {
+                let _ = write!(encoded, "%{byte:02X}");
+            }
+        }
+        format!("{name}*=UTF-8''{encoded}")
+    }
+}
+
+/// Decodes quoted-printable content, keeping stray `=` signs as they are
+pub fn decode_quoted_printable(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len());
+    let mut idx = 0;
+    while let Some(&byte) = input.get(idx) {
+        if byte != b'=' {
+            output.push(byte);
+            idx += 1;
+            continue;
+        }
+
+        let rest = &input[idx + 1..];
+        if rest.starts_with(b"\r\n") {
+            idx += 3;
+        } else if rest.starts_with(b"\n") {
+            idx += 2;
+        } else if let Some(decoded) = rest.get(..2).and_then(decode_hex) {
+            output.push(decoded);
+            idx += 3;
+        } else {
+            output.push(byte);
+            idx += 1;
+        }
+    }
+    output
+}
+
+/// Decodes two hexadecimal digits
+fn decode_hex(digits: &[u8]) -> Option<u8> {
+    let digits = std::str::from_utf8(digits).ok()?;
+    u8::from_str_radix(digits, 16).ok()
+}
+
+/// Decodes the RFC 2047 encoded words found in a header value
+///
+/// Whitespace between two adjacent encoded words is dropped, and words
+/// that fail to decode are kept verbatim.
+pub fn decode_words(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut whitespace = "";
+    let mut after_encoded_word = false;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let is_space = rest.starts_with(char::is_whitespace);
+        let end = rest
+            .find(|c: char| c.is_whitespace() != is_space)
+            .unwrap_or(rest.len());
+        let (token, remaining) = rest.split_at(end);
+        rest = remaining;
+        if is_space {
+            whitespace = token;
+            continue;
+        }
+
+        match decode_word(token) {
+            Some(decoded) => {
+                if !after_encoded_word {
+                    output.push_str(whitespace);
+                }
+                output.push_str(&decoded);
+                after_encoded_word = true;
+            }
+            None => {
+                output.push_str(whitespace);
+                output.push_str(token);
+                after_encoded_word = false;
+            }
+        }
+        whitespace = "";
+    }
+    output
+}
+
+/// Decodes a single `=?charset?encoding?text?=` word
+fn decode_word(token: &str) -> Option<String> {
+    let inner = token.strip_prefix("=?")?.strip_suffix("?=")?;
+    let mut fields = inner.splitn(3, '?');
+    let charset = fields.next()?;
+    let encoding = fields.next()?;
+    let text = fields.next()?;
+    // Drop the RFC 2231 language suffix, as in `UTF-8*en`
+    let charset = charset.split('*').next().unwrap_or(charset);
+
+    let bytes = if encoding.eq_ignore_ascii_case("b") {
+        BASE64_STANDARD.decode(text).ok()?
+    } else if encoding.eq_ignore_ascii_case("q") {
+        decode_quoted_printable(text.replace('_', " ").as_bytes())
+    } else {
+        return None;
+    };
+    Some(decode_charset(&bytes, Some(charset)))
+}
+
+/// Converts text in the given charset to a string
+///
+/// UTF-8 and Latin-1 are supported, other charsets are decoded as UTF-8
+/// with invalid sequences replaced.
+pub fn decode_charset(bytes: &[u8], charset: Option<&str>) -> String {
+    let latin1 = charset.is_some_and(|charset| {
+        ["iso-8859-1", "latin1", "windows-1252"]
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(charset.trim()))
+    });
+    if latin1 {
+        bytes.iter().copied().map(char::from).collect()
+    } else {
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::matchers::{eq, le};
+    use googletest::{expect_that, gtest};
+
+    use super::*;
+
+    #[gtest]
+    fn quoted_printable_escapes_non_ascii_and_equals() {
+        expect_that!(
+            quoted_printable("Akwaaba = welcome, café"),
+            eq("Akwaaba =3D welcome, caf=C3=A9")
+        );
+    }
+
+    #[gtest]
+    fn quoted_printable_uses_crlf_and_encodes_trailing_space() {
+        expect_that!(
+            quoted_printable("first line \nsecond\r\nthird"),
+            eq("first line=20\r\nsecond\r\nthird")
+        );
+    }
+
+    #[gtest]
+    fn quoted_printable_soft_breaks_long_lines() {
+        let encoded = quoted_printable(&"é".repeat(40));
+        for line in encoded.split("\r\n") {
+            expect_that!(line.len(), le(MAX_LINE_LENGTH));
+        }
+        expect_that!(encoded.replace("=\r\n", ""), eq(&"=C3=A9".repeat(40)));
+    }
+
+    #[gtest]
+    fn encoded_words_stay_within_limit() {
+        let words = encoded_words(&"Ọ̀yọ́ ".repeat(20));
+        for word in &words {
+            expect_that!(word.len(), le(75));
+        }
+        expect_that!(
+            encoded_words("Béhanzin"),
+            eq(&vec!["=?UTF-8?B?QsOpaGFuemlu?=".to_owned()])
+        );
+    }
+
+    #[gtest]
+    fn wraps_base64_content() {
+        let wrapped = wrap_base64(&BASE64_STANDARD.encode([0; 120]));
+        let lines = wrapped.split("\r\n").collect::<Vec<_>>();
+        expect_that!(lines.len(), eq(3));
+        expect_that!(lines[0].len(), eq(MAX_LINE_LENGTH));
+    }
+
+    #[gtest]
+    fn decodes_quoted_printable() {
+        expect_that!(
+            decode_quoted_printable(b"caf=C3=A9 =3D soft=\r\nbreak =ZZ"),
+            eq(&"café = softbreak =ZZ".as_bytes().to_vec())
+        );
+    }
+
+    #[gtest]
+    fn decodes_encoded_words() {
+        expect_that!(
+            decode_words("=?UTF-8?B?QsOpaGFuemlu?= =?utf-8?q?_of_Dahomey?= rules"),
+            eq("Béhanzin of Dahomey rules")
+        );
+        expect_that!(
+            decode_words("Caf=?ISO-8859-1?Q?=E9?= and =?x?y?z?="),
+            eq("Caf=?ISO-8859-1?Q?=E9?= and =?x?y?z?=")
+        );
+        expect_that!(decode_words("=?iso-8859-1?q?caf=E9?="), eq("café"));
+    }
+
+    #[gtest]
+    fn encodes_parameters() {
+        expect_that!(
+            parameter("filename", "report \"final\".pdf"),
+            eq(r#"filename="report \"final\".pdf""#)
+        );
+        expect_that!(
+            parameter("filename", "rapport été.pdf"),
+            eq("filename*=UTF-8''rapport%20%C3%A9t%C3%A9.pdf")
+        );
+    }
+}
@@ -0,0 +1,777 @@
+//! Parsing of RFC 5322 / MIME messages
+use base64::prelude::{BASE64_STANDARD, Engine};
+
+use super::encoding::{decode_charset, decode_quoted_printable, decode_words};
+use crate::email::{Attachment, Body, EmailMessage, Header, Mailbox};
+use crate::error::Error;
+
+/// Headers that map to message fields or are set again by the transport
+///
+/// They aren't kept as custom headers.
+const STRUCTURAL_HEADERS: [&str; 14] = [
+    "bcc",
+    "cc",
+    "content-disposition",
+    "content-id",
+    "content-transfer-encoding",
+    "content-type",
+    "date",
+    "from",
+    "message-id",
+    "mime-version",
+    "reply-to",
+    "return-path",
+    "subject",
+    "to",
+];
+
+/// How deep multipart bodies may nest before the message is rejected
+const MAX_DEPTH: usize = 16;
+
+/// A header name and its unfolded raw value
+type RawHeader = (String, String);
+
+/// Parts of the body collected while walking the MIME tree
+#[derive(Debug, Default)]
+struct Content {
+    /// First plain text part that isn't an attachment
+    text: Option<String>,
+    /// First HTML part that isn't an attachment
+    html: Option<String>,
+    /// Every other leaf part
+    attachments: Vec<Attachment>,
+}
+
+/// Parses a raw RFC 5322 message, such as the content of an `.eml` file
+///
+/// Address headers fill the sender and recipients, the first plain text
+/// and HTML parts become the [`Body`], and other parts become attachments,
+/// keeping their `Content-ID` as `cid:...`. Headers that don't map to a
+/// field are kept as custom headers, except `Date`, `Message-ID`,
+/// `MIME-Version` and `Return-Path`, which the transport sets again.
+///
+/// ```
+/// use sendout::mime::parse_message;
+///
+/// let raw = b"From: Wangari Maathai <wangari.maathai@example.africa>\r\n\
+///     To: kwame.nkrumah@example.africa\r\n\
+///     Subject: Tree planting\r\n\
+///     \r\n\
+///     See you at dawn.\r\n";
+///
+/// let email = parse_message(raw)?;
+/// assert_eq!(email.subject, "Tree planting");
+/// assert_eq!(email.body.text(), Some("See you at dawn.\n"));
+/// # Ok::<(), sendout::error::Error>(())
+/// ```
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(name = "mime::parse_message", skip_all, err(Debug))
+)]
+pub fn parse_message(input: &[u8]) -> Result<EmailMessage, Error> {
+    let (head, body) = split_head_body(input);
+    let headers = parse_headers(head)?;
+
+    let mut from = None;
+    let mut to = Vec::new();
+    let mut cc: Option<Vec<Mailbox>> = None;
+    let mut bcc: Option<Vec<Mailbox>> = None;
+    let mut reply_to: Option<Vec<Mailbox>> = None;
+    let mut subject = String::new();
+    let mut custom_headers = Vec::new();
+    for (name, value) in &headers {
+        match name.to_ascii_lowercase().as_str() {
+            "from" => from = parse_address_list(value)?.into_iter().next(),
+            "to" => to.extend(parse_address_list(value)?),
+            "cc" => cc
+                .get_or_insert_default()
+                .extend(parse_address_list(value)?),
+            "bcc" => bcc
+                .get_or_insert_default()
+                .extend(parse_address_list(value)?),
+            "reply-to" => reply_to
+                .get_or_insert_default()
+                .extend(parse_address_list(value)?),
+            "subject" => subject = decode_words(value),
+            lowercase if STRUCTURAL_HEADERS.contains(&lowercase) => {}
+            _ => custom_headers.push(Header {
+                name: name.clone(),
+                value: decode_words(value),
+            }),
+        }
+    }
+
+    let mut content = Content::default();
+    collect_parts(&headers, body, &mut content, 0)?;
+    let body = match (content.text, content.html) {
+        (Some(text), Some(html)) => Body::Multipart { text, html },
+        (None, Some(html)) => Body::Html(html),
+        (text, None) => Body::Text(text.unwrap_or_default()),
+    };
+
+    Ok(EmailMessage {
+        from,
+        to: to.into(),
+        subject,
+        body,
+        cc: cc.filter(|cc| !cc.is_empty()).map(Into::into),
+        bcc: bcc.filter(|bcc| !bcc.is_empty()).map(Into::into),
+        tag: None,
+        reply_to: reply_to
+            .filter(|reply_to| !reply_to.is_empty())
+            .map(Into::into),
+        headers: (!custom_headers.is_empty()).then_some(custom_headers),
+        metadata: None,
+        attachments: (!content.attachments.is_empty()).then_some(content.attachments),
+        message_stream: None,
+        idempotency_key: None,
+    })
+}
+
+/// Walks a part, recursing into multipart bodies
+fn collect_parts(
+    headers: &[RawHeader],
+    body: &[u8],
+    content: &mut Content,
+    depth: usize,
+) -> Result<(), Error> {
+    let (mime_type, type_params) = header_value(headers, "content-type")
+        .map(parse_parameterized)
+        .unwrap_or_else(|| ("text/plain".to_owned(), Vec::new()));
+
+    if mime_type.starts_with("multipart/") {
+        if depth >= MAX_DEPTH {
+            return Err(malformed("multipart body is nested too deeply"));
+        }
+        let boundary = parameter(&type_params, "boundary")
+            .ok_or_else(|| malformed("multipart body has no boundary"))?;
+        for part in split_multipart(body, &boundary)? {
+            let (head, body) = split_head_body(part);
+            let headers = parse_headers(head)?;
+            collect_parts(&headers, body, content, depth + 1)?;
+        }
+        return Ok(());
+    }
+
+    let data = decode_transfer_encoding(header_value(headers, "content-transfer-encoding"), body)?;
+    let (disposition, disposition_params) = header_value(headers, "content-disposition")
+        .map(parse_parameterized)
+        .unwrap_or_default();
+    let file_name =
+        parameter(&disposition_params, "filename").or_else(|| parameter(&type_params, "name"));
+    let is_attachment = disposition == "attachment" || file_name.is_some();
+
+    let charset = parameter(&type_params, "charset");
+    match mime_type.as_str() {
+        "text/plain" if !is_attachment && content.text.is_none() => {
+            content.text = Some(decode_text(&data, charset.as_deref()));
+        }
+        "text/html" if !is_attachment && content.html.is_none() => {
+            content.html = Some(decode_text(&data, charset.as_deref()));
+        }
+        _ => {
+            let content_id = header_value(headers, "content-id")
+                .map(|id| id.trim().trim_start_matches('<').trim_end_matches('>'))
+                .filter(|id| !id.is_empty())
+                .map(|id| format!("cid:{id}"));
+            content.attachments.push(Attachment {
+                name: file_name.unwrap_or_else(|| "attachment".to_owned()),
+                content: BASE64_STANDARD.encode(&data),
+                content_type: mime_type,
+                content_id,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Splits a message or part at the first empty line
+///
+/// Returns an empty body when there is no empty line.
+fn split_head_body(input: &[u8]) -> (&[u8], &[u8]) {
+    if let Some(body) = input
+        .strip_prefix(b"\r\n")
+        .or_else(|| input.strip_prefix(b"\n"))
+    {
+        return (&[], body);
+    }
+
+    let blank_line = input
+        .iter()
+        .enumerate()
+        .filter(|&(_, &byte)| byte == b'\n')
+        .find_map(|(idx, _)| {
+            let rest = &input[idx + 1..];
+            if rest.starts_with(b"\r\n") {
+                Some((idx + 1, idx + 3))
+            } else if rest.starts_with(b"\n") {
+                Some((idx + 1, idx + 2))
+            } else {
+                None
+            }
+        });
+    match blank_line {
+        Some((head_end, body_start)) => (&input[..head_end], &input[body_start..]),
+        None => (input, &[]),
+    }
+}
+
+/// Parses and unfolds the header section
+fn parse_headers(head: &[u8]) -> Result<Vec<RawHeader>, Error> {
+    let text = String::from_utf8_lossy(head);
+    let mut headers: Vec<RawHeader> = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        // Skip the mbox `From sender date` separator some tools prepend
+        if idx == 0 && line.starts_with("From ") {
+            continue;
+        }
+        if line.starts_with([' ', '\t']) {
+            let (_, value) = headers
+                .last_mut()
+                .ok_or_else(|| malformed("continuation line without a header"))?;
+            value.push_str(line);
+            continue;
+        }
+
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| malformed(&format!("invalid header line: {line}")))?;
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(malformed(&format!("invalid header name: {name}")));
+        }
+        headers.push((name.to_owned(), value.trim().to_owned()));
+    }
+    Ok(headers)
+}
+
+/// Returns the value of the first header with the given lowercase name
+fn header_value<'a>(headers: &'a [RawHeader], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(header, _)| header.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Parses a value such as `text/plain; charset="utf-8"`
+///
+/// The leading value is lowercased, parameter names are lowercased and
+/// their values unquoted.
+fn parse_parameterized(value: &str) -> (String, Vec<(String, String)>) {
+    let mut segments = split_outside_quotes(value, ';').into_iter();
+    let main = segments
+        .next()
+        .map(|main| main.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+    let params = segments
+        .filter_map(|segment| {
+            let (name, value) = segment.split_once('=')?;
+            Some((name.trim().to_ascii_lowercase(), unquote(value.trim())))
+        })
+        .collect();
+    (main, params)
+}
+
+/// Looks up a parameter, handling the RFC 2231 extended forms
+///
+/// Supports `name*=charset''value` and continuations such as `name*0=`
+/// and `name*1*=`.
+fn parameter(params: &[(String, String)], name: &str) -> Option<String> {
+    if let Some((_, value)) = params.iter().find(|(key, _)| key == name) {
+        return Some(value.clone());
+    }
+
+    let extended = format!("{name}*");
+    if let Some((_, value)) = params.iter().find(|(key, _)| *key == extended) {
+        return Some(decode_extended_value(value, true));
+    }
+
+    let mut sections = params
+        .iter()
+        .filter_map(|(key, value)| {
+            let section = key.strip_prefix(&extended)?;
+            let (number, encoded) = section
+                .strip_suffix('*')
+                .map_or((section, false), |number| (number, true));
+            Some((number.parse::<usize>().ok()?, encoded, value))
+        })
+        .collect::<Vec<_>>();
+    if sections.is_empty() {
+        return None;
+    }
+    sections.sort_by_key(|(number, _, _)| *number);
+    let joined = sections
+        .iter()
+        .enumerate()
+        .map(|(idx, (_, encoded, value))| {
+            if *encoded {
+                decode_extended_value(value, idx == 0)
+            } else {
+                (*value).clone()
+            }
+        })
+        .collect();
+    Some(joined)
+}
+
+/// Decodes a `charset'language'percent-encoded` parameter value
+///
+/// Only the first section of a continued value carries the charset.
+fn decode_extended_value(value: &str, with_charset: bool) -> String {
+    let (charset, encoded) = if with_charset {
+        let mut fields = value.splitn(3, '\'');
+        match (fields.next(), fields.next(), fields.next()) {
+            (Some(charset), Some(_language), Some(encoded)) => (Some(charset), encoded),
+            _ => (None, value),
+        }
+    } else {
+        (None, value)
+    };
+
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while let Some(&byte) = bytes.get(idx) {
+        let hex = bytes
+            .get(idx + 1..idx + 3)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u8::from_str_radix(digits, 16).ok());
+        match hex {
+            Some(value) if byte == b'%' => {
+                decoded.push(value);
+                idx += 3;
+            }
+            _ => {
+                decoded.push(byte);
+                idx += 1;
+            }
+        }
+    }
+    decode_charset(&decoded, charset)
+}
+
+/// Removes surrounding quotes and backslash escapes
+fn unquote(value: &str) -> String {
+    let Some(inner) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    else {
+        return value.to_owned();
+    };
+    let mut output = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            output.extend(chars.next());
+        } else {
+            output.push(c);
+        }
+    }
+    output
+}
+
+/// Splits on a separator that isn't inside a quoted string
+fn split_outside_quotes(value: &str, separator: char) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let mut start = 0;
+    for (idx, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            c if c == separator && !in_quotes => {
+                segments.push(&value[start..idx]);
+                start = idx + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    segments.push(&value[start..]);
+    segments
+}
+
+/// Splits a multipart body into its parts
+///
+/// The preamble and epilogue are ignored, and a missing closing delimiter
+/// is tolerated.
+fn split_multipart<'a>(body: &'a [u8], boundary: &str) -> Result<Vec<&'a [u8]>, Error> {
+    let delimiter = format!("--{boundary}");
+    let mut parts = Vec::new();
+    let mut part_start = None;
+    let mut found = false;
+    let mut line_start = 0;
+    while line_start < body.len() {
+        let line_end = body[line_start..]
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .map_or(body.len(), |idx| line_start + idx + 1);
+        let line = body[line_start..line_end].trim_ascii_end();
+
+        if let Some(rest) = line.strip_prefix(delimiter.as_bytes()) {
+            let closing = rest == b"--";
+            if closing || rest.is_empty() {
+                found = true;
+                if let Some(start) = part_start {
+                    parts.push(strip_line_ending(&body[start..line_start]));
+                }
+                if closing {
+                    part_start = None;
+                    break;
+                }
+                part_start = Some(line_end);
+            }
+        }
+        line_start = line_end;
+    }
+
+    if !found {
+        return Err(malformed(&format!("boundary {boundary} not found")));
+    }
+    if let Some(start) = part_start {
+        parts.push(&body[start..]);
+    }
+    Ok(parts)
+}
+
+/// Removes the line ending that belongs to the following delimiter
+fn strip_line_ending(part: &[u8]) -> &[u8] {
+    part.strip_suffix(b"\r\n")
+        .or_else(|| part.strip_suffix(b"\n"))
+        .unwrap_or(part)
+}
+
+/// Undoes the `Content-Transfer-Encoding` of a part
+fn decode_transfer_encoding(encoding: Option<&str>, body: &[u8]) -> Result<Vec<u8>, Error> {
+    let encoding = encoding.map(|encoding| encoding.trim().to_ascii_lowercase());
+    match encoding.as_deref() {
+        None | Some("7bit" | "8bit" | "binary") => Ok(body.to_vec()),
+        Some("quoted-printable") => Ok(decode_quoted_printable(body)),
+        Some("base64") => {
+            let compact = body
+                .iter()
+                .copied()
+                .filter(|byte| !byte.is_ascii_whitespace())
+                .collect::<Vec<_>>();
+            BASE64_STANDARD
+                .decode(compact)
+                .map_err(|err| malformed(&format!("invalid base64 content: {err}")))
+        }
+        Some(other) => Err(malformed(&format!(
+            "unsupported transfer encoding: {other}"
+        ))),
+    }
+}
+
+/// Decodes a text part and normalizes its line endings to `\n`
+fn decode_text(data: &[u8], charset: Option<&str>) -> String {
+    decode_charset(data, charset).replace("\r\n", "\n")
+}
+
+/// Parses an address list header such as `To` or `Cc`
+///
+/// Handles quoted and encoded display names, comments, and groups like
+/// `undisclosed-recipients:;`.
+fn parse_address_list(value: &str) -> Result<Vec<Mailbox>, Error> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let mut in_angle = false;
+    let mut comment_depth = 0_usize;
+    for c in value.chars() {
+        if escaped {
+            current.push(c);
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_quotes || comment_depth > 0 => {
+                if comment_depth == 0 {
+                    current.push(c);
+                }
+                escaped = true;
+            }
+            '"' if comment_depth == 0 => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            '(' if !in_quotes => comment_depth += 1,
+            ')' if !in_quotes && comment_depth > 0 => comment_depth -= 1,
+            _ if comment_depth > 0 => {}
+            '<' if !in_quotes => {
+                in_angle = true;
+                current.push(c);
+            }
+            '>' if !in_quotes => {
+                in_angle = false;
+                current.push(c);
+            }
+            // A group name ends at the colon, its members follow
+            ':' if !in_quotes && !in_angle => current.clear(),
+            ',' | ';' if !in_quotes && !in_angle => items.push(std::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    if in_quotes || in_angle || comment_depth > 0 {
+        return Err(malformed(&format!("unbalanced address list: {value}")));
+    }
+    items.push(current);
+
+    items
+        .iter()
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(parse_mailbox)
+        .collect()
+}
+
+/// Parses a single `address` or `name <address>` item
+fn parse_mailbox(item: &str) -> Result<Mailbox, Error> {
+    let invalid = || malformed(&format!("invalid address: {item}"));
+    let (name, email) = match item.rfind('<') {
+        Some(open) => {
+            let close = item[open..].find('>').ok_or_else(invalid)?;
+            (
+                decode_display_name(&item[..open]),
+                item[open + 1..open + close].trim(),
+            )
+        }
+        None => (None, item),
+    };
+    if !email.contains('@') || email.contains(char::is_whitespace) {
+        return Err(invalid());
+    }
+    Ok(Mailbox {
+        name,
+        email: email.to_owned(),
+    })
+}
+
+/// Decodes a display name made of quoted strings and encoded words
+fn decode_display_name(raw: &str) -> Option<String> {
+    let mut words = Vec::new();
+    let mut unquoted = String::new();
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c != '"' {
+            unquoted.push(c);
+            continue;
+        }
+        words.push(decode_words(&std::mem::take(&mut unquoted)));
+        let mut quoted = String::new();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => quoted.extend(chars.next()),
+                c => quoted.push(c),
+            }
+        }
+        words.push(quoted);
+    }
+    words.push(decode_words(&unquoted));
+
+    let name = words
+        .iter()
+        .flat_map(|word| word.split_whitespace())
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!name.is_empty()).then_some(name)
+}
+
+/// Creates a [`Error::MalformedMessage`]
+fn malformed(reason: &str) -> Error {
+    Error::MalformedMessage(reason.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use googletest::matchers::{anything, eq, err, none, some};
+    use googletest::{expect_that, gtest};
+
+    use super::*;
+    use crate::mime::MimeRenderer;
+
+    #[gtest]
+    fn parses_rendered_message_back() {
+        let original = EmailMessage {
+            from: Some(Mailbox::new(
+                "Maathai, Wangari",
+                "wangari.maathai@example.africa",
+            )),
+            to: vec![
+                Mailbox::new("Kwame Nkrumah", "kwame.nkrumah@example.africa"),
+                Mailbox::new("Béhanzin", "gbehanzin@example.africa"),
+            ]
+            .into(),
+            subject: "Mkutano wa Wanawake — Nairobi".to_owned(),
+            body: Body::Multipart {
+                text: "Karibu sana!".to_owned(),
+                html: "<p>Karibu sana!</p><img src=\"cid:logo\">".to_owned(),
+            },
+            cc: Some(vec!["steve.biko@example.africa"].into()),
+            bcc: None,
+            tag: None,
+            reply_to: None,
+            headers: Some(vec![Header {
+                name: "X-Movement-Id".to_owned(),
+                value: "green-belt-kenya-1977".to_owned(),
+            }]),
+            metadata: None,
+            attachments: Some(vec![
+                Attachment {
+                    name: "logo.png".to_owned(),
+                    content: "iVBORw0KGgo=".to_owned(),
+                    content_type: "image/png".to_owned(),
+                    content_id: Some("cid:logo".to_owned()),
+                },
+                Attachment {
+                    name: "ripoti ya miti é.pdf".to_owned(),
+                    content: "JVBERi0xLjQKJcfs".to_owned(),
+                    content_type: "application/pdf".to_owned(),
+                    content_id: None,
+                },
+            ]),
+            message_stream: None,
+            idempotency_key: None,
+        };
+        let renderer = MimeRenderer {
+            date: Some(UNIX_EPOCH + Duration::from_secs(1_792_148_400)),
+            message_id: Some("harambee.1@example.africa".to_owned()),
+            include_bcc: false,
+        };
+        let raw = renderer
+            .render_to_vec(&original)
+            .expect("rendering to succeed");
+
+        let email = parse_message(&raw).expect("parsing to succeed");
+
+        expect_that!(email.from, some(eq(original.from.as_ref().expect("from"))));
+        expect_that!(email.to.as_slice(), eq(original.to.as_slice()));
+        expect_that!(
+            email.cc.as_ref().map(|cc| cc.as_slice().to_vec()),
+            some(eq(&vec![Mailbox::from("steve.biko@example.africa")]))
+        );
+        expect_that!(email.subject, eq(&original.subject));
+        expect_that!(email.body.text(), some(eq("Karibu sana!")));
+        expect_that!(
+            email.body.html(),
+            some(eq("<p>Karibu sana!</p><img src=\"cid:logo\">"))
+        );
+
+        let headers = email.headers.expect("custom headers");
+        expect_that!(headers.len(), eq(1));
+        expect_that!(headers[0].name, eq("X-Movement-Id"));
+        expect_that!(headers[0].value, eq("green-belt-kenya-1977"));
+
+        let attachments = email.attachments.expect("attachments");
+        expect_that!(attachments.len(), eq(2));
+        expect_that!(attachments[0].name, eq("logo.png"));
+        expect_that!(attachments[0].content, eq("iVBORw0KGgo="));
+        expect_that!(attachments[0].content_id.as_deref(), some(eq("cid:logo")));
+        expect_that!(attachments[1].name, eq("ripoti ya miti é.pdf"));
+        expect_that!(attachments[1].content_type, eq("application/pdf"));
+        expect_that!(attachments[1].content_id, none());
+    }
+
+    #[gtest]
+    fn parses_legacy_message() {
+        let raw = b"From MAILER-DAEMON Fri Oct 16 11:00:00 2026\n\
+            Received: from relay.example.africa\n\
+            \tby mx.example.africa\n\
+            From: \"Sankara, Thomas\" (the president) <thomas.sankara@example.africa>\n\
+            To: undisclosed-recipients:;\n\
+            Bcc: Patrice Lumumba <patrice.lumumba@example.africa>,\n \
+            =?ISO-8859-1?Q?Am=EDlcar_Cabral?= <amilcar.cabral@example.africa>\n\
+            Subject: =?utf-8?q?Caf=C3=A9?= in\n Ouagadougou\n\
+            Content-Type: text/plain; charset=ISO-8859-1\n\
+            Content-Transfer-Encoding: quoted-printable\n\
+            \n\
+            Le caf=E9 est pr=EAt.=\n\
+            \n";
+
+        let email = parse_message(raw).expect("parsing to succeed");
+
+        expect_that!(
+            email.from,
+            some(eq(&Mailbox::new(
+                "Sankara, Thomas",
+                "thomas.sankara@example.africa"
+            )))
+        );
+        expect_that!(email.to.as_slice().len(), eq(0));
+        expect_that!(
+            email.bcc.as_ref().map(|bcc| bcc.as_slice().to_vec()),
+            some(eq(&vec![
+                Mailbox::new("Patrice Lumumba", "patrice.lumumba@example.africa"),
+                Mailbox::new("Amílcar Cabral", "amilcar.cabral@example.africa"),
+            ]))
+        );
+        expect_that!(email.subject, eq("Café in Ouagadougou"));
+        expect_that!(email.body.text(), some(eq("Le café est prêt.\n")));
+        expect_that!(
+            email
+                .headers
+                .as_ref()
+                .and_then(|headers| headers.first())
+                .map(|header| header.value.as_str()),
+            some(eq("from relay.example.africa\tby mx.example.africa"))
+        );
+    }
+
+    #[gtest]
+    fn parses_html_only_message_with_continued_file_name() {
+        let raw = b"From: miriam.makeba@example.africa\r\n\
+            To: yaa.asantewaa@example.africa\r\n\
+            Content-Type: multipart/mixed; boundary=\"outer\"\r\n\
+            \r\n\
+            This is the preamble.\r\n\
+            --outer\r\n\
+            Content-Type: text/html; charset=utf-8\r\n\
+            \r\n\
+            <p>Pata Pata</p>\r\n\
+            --outer\r\n\
+            Content-Type: audio/mpeg\r\n\
+            Content-Transfer-Encoding: base64\r\n\
+            Content-Disposition: attachment;\r\n \
+            filename*0*=UTF-8''Pata%20;\r\n \
+            filename*1=Pata.mp3\r\n\
+            \r\n\
+            SUQz\r\n\
+            BAA=\r\n\
+            --outer--\r\n\
+            epilogue\r\n";
+
+        let email = parse_message(raw).expect("parsing to succeed");
+
+        expect_that!(email.body.html(), some(eq("<p>Pata Pata</p>")));
+        expect_that!(email.body.text(), none());
+        let attachments = email.attachments.expect("attachments");
+        expect_that!(attachments[0].name, eq("Pata Pata.mp3"));
+        expect_that!(attachments[0].content, eq("SUQzBAA="));
+        expect_that!(attachments[0].content_type, eq("audio/mpeg"));
+    }
+
+    #[gtest]
+    fn rejects_malformed_messages() {
+        let missing_colon = b"From wangari.maathai@example.africa\r\nTo nobody\r\n\r\nHi";
+        expect_that!(parse_message(missing_colon), err(anything()));
+
+        let no_boundary = b"Content-Type: multipart/mixed\r\n\r\nHi";
+        expect_that!(parse_message(no_boundary), err(anything()));
+
+        let boundary_not_found = b"Content-Type: multipart/mixed; boundary=x\r\n\r\nHi";
+        expect_that!(parse_message(boundary_not_found), err(anything()));
+
+        let invalid_base64 = b"Content-Transfer-Encoding: base64\r\n\r\n!!!";
+        expect_that!(parse_message(invalid_base64), err(anything()));
+
+        let invalid_address = b"To: Kwame Nkrumah\r\n\r\nHi";
+        assert!(matches!(
+            parse_message(invalid_address),
+            Err(Error::MalformedMessage(_))
+        ));
+    }
+}
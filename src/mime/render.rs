@@ -0,0 +1,689 @@
+//! Rendering of email messages as MIME
+use std::hash::{BuildHasher, RandomState};
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::encoding::{
+    encoded_words, is_plain_header_text, parameter, quoted_printable, wrap_base64,
+};
+use crate::datetime::UtcDateTime;
+use crate::email::{Attachment, Body, EmailMessage, Mailbox};
+use crate::error::Error;
+
+/// Line length after which headers are folded
+const FOLD_AT: usize = 78;
+
+/// Headers written by the renderer that custom headers can't override
+const RESERVED_HEADERS: [&str; 11] = [
+    "Bcc",
+    "Cc",
+    "Content-Transfer-Encoding",
+    "Content-Type",
+    "Date",
+    "From",
+    "Message-ID",
+    "MIME-Version",
+    "Reply-To",
+    "Subject",
+    "To",
+];
+
+/// Renders an [`EmailMessage`] as an RFC 5322 message
+///
+/// Bodies with both parts become `multipart/alternative`, attachments with a
+/// `content_id` are placed next to the HTML in `multipart/related`, and other
+/// attachments go into `multipart/mixed`. Provider specific fields such as
+/// the tag, metadata and message stream aren't rendered.
+///
+/// ```
+/// use sendout::email::{Body, EmailMessage};
+/// use sendout::mime::MimeRenderer;
+///
+/// let email = EmailMessage {
+///     from: Some("wangari.maathai@example.africa".into()),
+///     to: vec!["kwame.nkrumah@example.africa"].into(),
+///     subject: "Tree planting".to_owned(),
+///     body: Body::Text("See you at dawn.".to_owned()),
+///     cc: None,
+///     bcc: None,
+///     tag: None,
+///     reply_to: None,
+///     headers: None,
+///     metadata: None,
+///     attachments: None,
+///     message_stream: None,
+///     idempotency_key: None,
+/// };
+///
+/// let mut eml = Vec::new();
+/// MimeRenderer::default().render(&email, &mut eml)?;
+/// assert!(eml.starts_with(b"Date: "));
+/// # Ok::<(), sendout::error::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "bon", derive(bon::Builder))]
+pub struct MimeRenderer {
+    /// Value of the `Date` header, the current time when unset
+    pub date: Option<SystemTime>,
+    /// Value of the `Message-ID` header without angle brackets
+    ///
+    /// A random identifier on the sender's domain is generated when unset.
+    pub message_id: Option<String>,
+    /// Whether to write the `Bcc` header
+    ///
+    /// Leave it off for messages handed to a transport, and turn it on for
+    /// archived copies.
+    #[cfg_attr(feature = "bon", builder(default))]
+    pub include_bcc: bool,
+}
+
+/// A node of the MIME tree
+#[derive(Debug)]
+enum Part<'a> {
+    /// A `text/*` leaf
+    Text {
+        /// Subtype such as `plain` or `html`
+        subtype: &'static str,
+        /// The text content
+        content: &'a str,
+    },
+    /// An attachment leaf
+    File {
+        /// The attachment to render
+        attachment: &'a Attachment,
+        /// Whether the attachment is displayed inline
+        inline: bool,
+    },
+    /// A `multipart/*` container
+    Multipart {
+        /// Subtype such as `mixed` or `alternative`
+        subtype: &'static str,
+        /// The nested parts
+        parts: Vec<Part<'a>>,
+    },
+}
+
+impl MimeRenderer {
+    /// Writes the message to the writer
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "MimeRenderer::render", skip_all, err(Debug))
+    )]
+    pub fn render(&self, email: &EmailMessage, writer: &mut impl Write) -> Result<(), Error> {
+        let output = self.render_to_string(email)?;
+        writer.write_all(output.as_bytes()).map_err(|err| {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?err);
+            Error::Mime(err.to_string())
+        })
+    }
+
+    /// Returns the message as bytes
+    pub fn render_to_vec(&self, email: &EmailMessage) -> Result<Vec<u8>, Error> {
+        self.render_to_string(email).map(String::into_bytes)
+    }
+
+    /// Builds the whole message, which is ASCII once encoded
+    fn render_to_string(&self, email: &EmailMessage) -> Result<String, Error> {
+        let from = email
+            .from
+            .as_ref()
+            .ok_or_else(|| Error::Mime("message has no sender".into()))?;
+        from.check_address()?;
+        let message_id = self
+            .message_id
+            .clone()
+            .unwrap_or_else(|| generate_message_id(from));
+        let valid_message_id = !message_id.is_empty()
+            && message_id
+                .chars()
+                .all(|c| !c.is_control() && !c.is_whitespace() && c != '<' && c != '>');
+        if !valid_message_id {
+            return Err(Error::Mime(format!(
+                "invalid message ID: {}",
+                message_id.escape_debug()
+            )));
+        }
+
+        let mut output = String::new();
+        let date = self.date.unwrap_or_else(SystemTime::now);
+        write_header(&mut output, "Date", &[format!(" {}", format_date(date))]);
+        write_header(
+            &mut output,
+            "From",
+            &address_tokens(std::slice::from_ref(from))?,
+        );
+        write_header(&mut output, "To", &address_tokens(email.to.as_slice())?);
+        if let Some(cc) = &email.cc {
+            write_header(&mut output, "Cc", &address_tokens(cc.as_slice())?);
+        }
+        if let Some(bcc) = email.bcc.as_ref().filter(|_| self.include_bcc) {
+            write_header(&mut output, "Bcc", &address_tokens(bcc.as_slice())?);
+        }
+        if let Some(reply_to) = &email.reply_to {
+            write_header(
+                &mut output,
+                "Reply-To",
+                &address_tokens(reply_to.as_slice())?,
+            );
+        }
+        write_header(&mut output, "Subject", &text_tokens(&email.subject));
+        write_header(&mut output, "Message-ID", &[format!(" <{message_id}>")]);
+        write_header(&mut output, "MIME-Version", &[" 1.0".to_owned()]);
+        for header in email.headers.iter().flatten() {
+            let valid_name = !header.name.is_empty()
+                && header
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_graphic() && c != ':');
+            if !valid_name {
+                return Err(Error::Mime(format!("invalid header name: {}", header.name)));
+            }
+            if RESERVED_HEADERS
+                .iter()
+                .any(|reserved| reserved.eq_ignore_ascii_case(&header.name))
+            {
+                return Err(Error::Mime(format!(
+                    "header {} is set by the renderer",
+                    header.name
+                )));
+            }
+            write_header(&mut output, &header.name, &text_tokens(&header.value));
+        }
+
+        let mut boundaries = Boundaries {
+            seed: fnv1a(message_id.as_bytes()),
+            count: 0,
+        };
+        write_part(&mut output, &body_tree(email), &mut boundaries);
+        Ok(output)
+    }
+}
+
+/// Generates unique multipart boundaries for a message
+///
+/// Boundaries start with `=_`, which never shows up in quoted-printable or
+/// base64 encoded content.
+struct Boundaries {
+    /// Value derived from the message ID
+    seed: u64,
+    /// Number of boundaries handed out so far
+    count: usize,
+}
+
+impl Boundaries {
+    /// Returns the next boundary
+    fn next(&mut self) -> String {
+        self.count += 1;
+        format!("=_{:016x}.{}", self.seed, self.count)
+    }
+}
+
+/// Hashes bytes with 64-bit FNV-1a
+///
+/// Unlike the standard hashers, the output is stable across Rust releases,
+/// so the same message ID always yields the same boundaries.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Arranges the body and attachments into a MIME tree
+fn body_tree(email: &EmailMessage) -> Part<'_> {
+    let mut content = match &email.body {
+        Body::Text(text) => Part::Text {
+            subtype: "plain",
+            content: text,
+        },
+        Body::Html(html) => Part::Text {
+            subtype: "html",
+            content: html,
+        },
+        Body::Multipart { text, html } => Part::Multipart {
+            subtype: "alternative",
+            parts: vec![
+                Part::Text {
+                    subtype: "plain",
+                    content: text,
+                },
+                Part::Text {
+                    subtype: "html",
+                    content: html,
+                },
+            ],
+        },
+    };
+
+    let (inline, regular): (Vec<_>, Vec<_>) = email
+        .attachments
+        .iter()
+        .flatten()
+        .partition(|attachment| attachment.content_id.is_some());
+    let mut files = regular
+        .into_iter()
+        .map(|attachment| Part::File {
+            attachment,
+            inline: false,
+        })
+        .collect::<Vec<_>>();
+    let inline = inline.into_iter().map(|attachment| Part::File {
+        attachment,
+        inline: true,
+    });
+
+    if email.body.html().is_some() {
+        let mut parts = vec![content];
+        parts.extend(inline);
+        content = if parts.len() > 1 {
+            Part::Multipart {
+                subtype: "related",
+                parts,
+            }
+        } else {
+            parts.remove(0)
+        };
+    } else {
+        // Without HTML nothing references the inline parts
+        files.extend(inline);
+    }
+
+    if files.is_empty() {
+        content
+    } else {
+        let mut parts = vec![content];
+        parts.extend(files);
+        Part::Multipart {
+            subtype: "mixed",
+            parts,
+        }
+    }
+}
+
+/// Writes the headers and content of a part
+fn write_part(output: &mut String, part: &Part<'_>, boundaries: &mut Boundaries) {
+    match part {
+        Part::Text { subtype, content } => {
+            write_header(
+                output,
+                "Content-Type",
+                &[format!(" text/{subtype};"), " charset=utf-8".to_owned()],
+            );
+            write_header(
+                output,
+                "Content-Transfer-Encoding",
+                &[" quoted-printable".to_owned()],
+            );
+            output.push_str("\r\n");
+            output.push_str(&quoted_printable(content));
+            output.push_str("\r\n");
+        }
+        Part::File { attachment, inline } => {
+            let content_type = if attachment.content_type.trim().is_empty() {
+                "application/octet-stream".to_owned()
+            } else {
+                single_line(&attachment.content_type)
+            };
+            write_header(
+                output,
+                "Content-Type",
+                &[
+                    format!(" {content_type};"),
+                    format!(" {}", parameter("name", &attachment.name)),
+                ],
+            );
+            write_header(output, "Content-Transfer-Encoding", &[" base64".to_owned()]);
+            let disposition = if *inline { "inline" } else { "attachment" };
+            write_header(
+                output,
+                "Content-Disposition",
+                &[
+                    format!(" {disposition};"),
+                    format!(" {}", parameter("filename", &attachment.name)),
+                ],
+            );
+            if let Some(content_id) = &attachment.content_id {
+                let content_id = content_id.strip_prefix("cid:").unwrap_or(content_id);
+                let content_id = content_id.trim_start_matches('<').trim_end_matches('>');
+                write_header(
+                    output,
+                    "Content-ID",
+                    &[format!(" <{}>", single_line(content_id))],
+                );
+            }
+            output.push_str("\r\n");
+            output.push_str(&wrap_base64(&attachment.content));
+            output.push_str("\r\n");
+        }
+        Part::Multipart { subtype, parts } => {
+            let boundary = boundaries.next();
+            write_header(
+                output,
+                "Content-Type",
+                &[
+                    format!(" multipart/{subtype};"),
+                    format!(" boundary=\"{boundary}\""),
+                ],
+            );
+            output.push_str("\r\n");
+            for part in parts {
+                output.push_str(&format!("--{boundary}\r\n"));
+                write_part(output, part, boundaries);
+            }
+            output.push_str(&format!("--{boundary}--\r\n"));
+        }
+    }
+}
+
+/// Writes a header, folding it between tokens to keep lines short
+///
+/// Every token starts with the whitespace that separates it from the
+/// previous one, so a fold never changes the value.
+fn write_header(output: &mut String, name: &str, tokens: &[String]) {
+    output.push_str(name);
+    output.push(':');
+    let start = name.len() + 1;
+    let mut width = start;
+    for token in tokens {
+        if width > start && width + token.len() > FOLD_AT {
+            output.push_str("\r\n");
+            width = 0;
+        }
+        output.push_str(token);
+        width += token.len();
+    }
+    output.push_str("\r\n");
+}
+
+/// Splits unstructured header text into tokens, encoding it when needed
+fn text_tokens(text: &str) -> Vec<String> {
+    let text = single_line(text);
+    if is_plain_header_text(&text) {
+        text.split(' ').map(|word| format!(" {word}")).collect()
+    } else {
+        encoded_words(&text)
+            .into_iter()
+            .map(|word| format!(" {word}"))
+            .collect()
+    }
+}
+
+/// Splits a list of mailboxes into comma separated tokens
+///
+/// Fails when an address could break out of the header.
+fn address_tokens(mailboxes: &[Mailbox]) -> Result<Vec<String>, Error> {
+    let mut tokens = Vec::new();
+    for (idx, mailbox) in mailboxes.iter().enumerate() {
+        mailbox.check_address()?;
+        let name = mailbox
+            .name
+            .as_deref()
+            .map(single_line)
+            .filter(|name| !name.is_empty());
+        match name {
+            Some(name) if !is_plain_header_text(&name) => {
+                tokens.extend(
+                    encoded_words(&name)
+                        .into_iter()
+                        .map(|word| format!(" {word}")),
+                );
+                tokens.push(format!(" <{}>", mailbox.email));
+            }
+            name => {
+                let mailbox = Mailbox {
+                    name,
+                    email: mailbox.email.clone(),
+                };
+                tokens.push(format!(" {mailbox}"));
+            }
+        }
+        if idx + 1 < mailboxes.len()
+            && let Some(last) = tokens.last_mut()
+        {
+            last.push(',');
+        }
+    }
+    Ok(tokens)
+}
+
+/// Replaces line breaks so a value can't inject extra headers
+fn single_line(value: &str) -> String {
+    value.replace(['\r', '\n'], " ")
+}
+
+/// Formats a time as an RFC 5322 date in UTC
+pub fn format_date(time: SystemTime) -> String {
+    /// Day names, starting from Monday
+    const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    /// Month names
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let date = UtcDateTime::from_system_time(time);
+    format!(
+        "{}, {} {} {} {:02}:{:02}:{:02} +0000",
+        WEEKDAYS[date.weekday as usize],
+        date.day,
+        MONTHS[(date.month - 1) as usize],
+        date.year,
+        date.hour,
+        date.minute,
+        date.second,
+    )
+}
+
+/// Generates a random message ID on the sender's domain
+pub fn generate_message_id(from: &Mailbox) -> String {
+    let domain = from
+        .email
+        .rsplit_once('@')
+        .map_or("localhost", |(_, domain)| domain);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_nanos());
+    let state = RandomState::new();
+    format!(
+        "{:016x}.{:016x}@{domain}",
+        state.hash_one(nanos),
+        state.hash_one(std::process::id())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use googletest::matchers::{anything, contains_substring, eq, err, le, not, pat};
+    use googletest::{expect_that, gtest};
+
+    use super::*;
+    use crate::email::Header;
+
+    fn renderer() -> MimeRenderer {
+        MimeRenderer {
+            date: Some(UNIX_EPOCH + Duration::from_secs(1_792_148_400)),
+            message_id: Some("harambee.1@example.africa".to_owned()),
+            include_bcc: false,
+        }
+    }
+
+    fn email_message(body: Body) -> EmailMessage {
+        EmailMessage {
+            from: Some(Mailbox::new(
+                "Maathai, Wangari",
+                "wangari.maathai@example.africa",
+            )),
+            to: vec![
+                Mailbox::new("Kwame Nkrumah", "kwame.nkrumah@example.africa"),
+                Mailbox::new("Béhanzin", "gbehanzin@example.africa"),
+            ]
+            .into(),
+            subject: "Green Belt Movement Monthly Update".to_owned(),
+            body,
+            cc: None,
+            bcc: Some(vec!["miriam.makeba@example.africa"].into()),
+            tag: None,
+            reply_to: None,
+            headers: None,
+            metadata: None,
+            attachments: None,
+            message_stream: None,
+            idempotency_key: None,
+        }
+    }
+
+    fn render(email: &EmailMessage) -> String {
+        let bytes = renderer()
+            .render_to_vec(email)
+            .expect("rendering to succeed");
+        String::from_utf8(bytes).expect("output to be ASCII")
+    }
+
+    #[gtest]
+    fn renders_plain_text_message() {
+        let email = email_message(Body::Text(
+            "We planted 10,000 trees across Kenya this month.".to_owned(),
+        ));
+        insta::assert_snapshot!(render(&email));
+    }
+
+    #[gtest]
+    fn renders_alternatives_with_inline_and_regular_attachments() {
+        let mut email = email_message(Body::Multipart {
+            text: "Karibu sana!".to_owned(),
+            html: "<p>Karibu sana!</p><img src=\"cid:logo\">".to_owned(),
+        });
+        email.subject = "Mkutano wa Wanawake — Nairobi".to_owned();
+        email.headers = Some(vec![Header {
+            name: "X-Movement-Id".to_owned(),
+            value: "green-belt-kenya-1977".to_owned(),
+        }]);
+        email.attachments = Some(vec![
+            Attachment {
+                name: "logo.png".to_owned(),
+                content: "iVBORw0KGgo=".to_owned(),
+                content_type: "image/png".to_owned(),
+                content_id: Some("cid:logo".to_owned()),
+            },
+            Attachment {
+                name: "ripoti ya miti.pdf".to_owned(),
+                content: "JVBERi0xLjQKJcfs".to_owned(),
+                content_type: "application/pdf".to_owned(),
+                content_id: None,
+            },
+        ]);
+        insta::assert_snapshot!(render(&email));
+    }
+
+    #[gtest]
+    fn writes_bcc_only_when_asked() {
+        let email = email_message(Body::Text("Harambee".to_owned()));
+        expect_that!(render(&email), not(contains_substring("Bcc:")));
+
+        let renderer = MimeRenderer {
+            include_bcc: true,
+            ..renderer()
+        };
+        let output = renderer
+            .render_to_vec(&email)
+            .expect("rendering to succeed");
+        expect_that!(
+            String::from_utf8_lossy(&output),
+            contains_substring("Bcc: miriam.makeba@example.africa\r\n")
+        );
+    }
+
+    #[gtest]
+    fn folds_long_headers() {
+        let mut email = email_message(Body::Text("Harambee".to_owned()));
+        email.subject = "Pull together ".repeat(20);
+        email.to = (1..=10)
+            .map(|count| {
+                Mailbox::new(
+                    format!("Member {count}"),
+                    format!("m{count}@example.africa"),
+                )
+            })
+            .collect::<Vec<_>>()
+            .into();
+
+        let output = render(&email);
+        for line in output.split("\r\n") {
+            expect_that!(line.len(), le(FOLD_AT));
+        }
+    }
+
+    #[gtest]
+    fn strips_line_breaks_from_header_values() {
+        let mut email = email_message(Body::Text("Harambee".to_owned()));
+        email.subject = "Hello\r\nBcc: intruder@example.com".to_owned();
+        expect_that!(
+            render(&email),
+            contains_substring("Subject: Hello  Bcc: intruder@example.com\r\n")
+        );
+    }
+
+    #[gtest]
+    fn rejects_invalid_messages() {
+        let mut email = email_message(Body::Text("Harambee".to_owned()));
+        email.from = None;
+        expect_that!(renderer().render_to_vec(&email), err(anything()));
+
+        let mut email = email_message(Body::Text("Harambee".to_owned()));
+        email.headers = Some(vec![Header {
+            name: "Message-ID".to_owned(),
+            value: "<spoofed@example.com>".to_owned(),
+        }]);
+        expect_that!(renderer().render_to_vec(&email), err(anything()));
+
+        email.headers = Some(vec![Header {
+            name: "X Bad".to_owned(),
+            value: "value".to_owned(),
+        }]);
+        expect_that!(renderer().render_to_vec(&email), err(anything()));
+    }
+
+    #[gtest]
+    fn rejects_line_breaks_in_addresses_and_message_ids() {
+        let mut email = email_message(Body::Text("Harambee".to_owned()));
+        email.to = vec!["x@example.com\r\nBcc: evil@attacker.com"].into();
+        expect_that!(
+            renderer().render_to_vec(&email),
+            err(pat!(Error::InvalidRecipient(anything())))
+        );
+
+        let mut email = email_message(Body::Text("Harambee".to_owned()));
+        email.from = Some("x@example.com\r\nBcc: evil@attacker.com".into());
+        expect_that!(
+            MimeRenderer::default().render_to_vec(&email),
+            err(anything())
+        );
+
+        let email = email_message(Body::Text("Harambee".to_owned()));
+        let renderer = MimeRenderer {
+            message_id: Some("id@example.com>\r\nBcc: evil@attacker.com".to_owned()),
+            ..renderer()
+        };
+        expect_that!(
+            renderer.render_to_vec(&email),
+            err(pat!(Error::Mime(anything())))
+        );
+    }
+
+    #[gtest]
+    fn formats_dates() {
+        expect_that!(
+            format_date(UNIX_EPOCH),
+            eq("Thu, 1 Jan 1970 00:00:00 +0000")
+        );
+        expect_that!(
+            format_date(UNIX_EPOCH + Duration::from_secs(951_782_400)),
+            eq("Tue, 29 Feb 2000 00:00:00 +0000")
+        );
+        expect_that!(
+            format_date(UNIX_EPOCH + Duration::from_secs(1_792_148_400)),
+            eq("Fri, 16 Oct 2026 11:00:00 +0000")
+        );
+    }
+}
@@ -0,0 +1,114 @@
+//! Persistent outbox for emails that must survive restarts
+//!
+//! Emails are stored in an [`Outbox`] before being sent, and an
+//! [`OutboxWorker`] drains it in the background. An entry is only removed
+//! once the provider accepted it, so every email is delivered at least once,
+//! even when the process stops in between.
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+pub mod file;
+pub mod worker;
+
+#[doc(inline)]
+pub use file::FileOutbox;
+#[doc(inline)]
+pub use worker::{DrainSummary, OutboxWorker};
+
+use crate::email::EmailMessage;
+use crate::error::Error;
+
+/// An email waiting in an [`Outbox`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    /// Message ID, unique within the outbox
+    pub id: String,
+    /// The email to send
+    pub email: EmailMessage,
+    /// Number of failed attempts so far
+    pub attempts: u32,
+    /// Earliest time of the next attempt
+    pub next_attempt_at: SystemTime,
+    /// Error of the last failed attempt
+    pub last_error: Option<String>,
+}
+
+impl OutboxEntry {
+    /// Creates an entry due right away
+    pub fn new(id: impl Into<String>, email: EmailMessage) -> Self {
+        Self {
+            id: id.into(),
+            email,
+            attempts: 0,
+            next_attempt_at: SystemTime::now(),
+            last_error: None,
+        }
+    }
+}
+
+/// Storage for emails waiting to be sent
+///
+/// Entries are keyed by their message ID. A single [`OutboxWorker`] is
+/// expected to drain an outbox at a time.
+#[async_trait]
+pub trait Outbox: Send + Sync {
+    /// Stores a new entry
+    ///
+    /// Returns `false` without storing anything when an entry with the same
+    /// ID is already pending or dead-lettered.
+    async fn enqueue(&self, entry: OutboxEntry) -> Result<bool, Error>;
+
+    /// Returns the pending entry with the ID, if any
+    async fn get(&self, id: &str) -> Result<Option<OutboxEntry>, Error>;
+
+    /// Returns up to `limit` pending entries due at `now`, earliest first
+    async fn due(&self, now: SystemTime, limit: usize) -> Result<Vec<OutboxEntry>, Error>;
+
+    /// Removes an entry once its email was sent
+    async fn complete(&self, id: &str) -> Result<(), Error>;
+
+    /// Replaces a pending entry after a failed attempt
+    ///
+    /// Returns `false` without storing anything when the entry is no longer
+    /// pending, so a completed entry is never brought back.
+    async fn reschedule(&self, entry: OutboxEntry) -> Result<bool, Error>;
+
+    /// Moves an entry that won't be retried out of the pending entries
+    async fn dead_letter(&self, entry: OutboxEntry) -> Result<(), Error>;
+
+    /// Returns the dead-lettered entries
+    async fn dead_letters(&self) -> Result<Vec<OutboxEntry>, Error>;
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::matchers::{eq, none};
+    use googletest::{expect_that, gtest};
+
+    use super::*;
+    use crate::fixtures;
+
+    /// Creates an entry due right away
+    pub fn entry(id: &str) -> OutboxEntry {
+        OutboxEntry::new(id, fixtures::email_message())
+    }
+
+    #[gtest]
+    fn entries_round_trip_through_json() {
+        let mut entry = entry("signup/42");
+        entry.attempts = 2;
+        entry.last_error = Some("rate limit exceeded".to_owned());
+
+        let json = serde_json::to_vec(&entry).expect("serialization to succeed");
+        let parsed: OutboxEntry =
+            serde_json::from_slice(&json).expect("deserialization to succeed");
+
+        expect_that!(parsed.id, eq("signup/42"));
+        expect_that!(parsed.attempts, eq(2));
+        expect_that!(parsed.next_attempt_at, eq(entry.next_attempt_at));
+        expect_that!(parsed.email.subject, eq(&entry.email.subject));
+        expect_that!(parsed.email.cc, none());
+    }
+}
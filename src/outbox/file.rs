@@ -0,0 +1,329 @@
+//! Outbox stored as JSON files in a directory
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use super::{Outbox, OutboxEntry};
+use crate::error::Error;
+use crate::json_store;
+
+/// Outbox keeping one JSON file per entry
+///
+/// Pending entries live in `pending/` and dead letters in `dead/`, under
+/// the outbox directory. File names are the hex-encoded SHA-256 hashes of
+/// the message IDs, so IDs of any length fit, and files are written to a
+/// temporary file first, then moved into place. Files that can't be parsed
+/// are renamed with a `.corrupt` extension and skipped, so one bad entry
+/// doesn't block the others.
+///
+/// Clones share a lock over the updates of pending entries, so a reschedule
+/// never brings back an entry completed by a clone. Separate processes
+/// using the same directory don't share it.
+#[derive(Debug, Clone)]
+pub struct FileOutbox {
+    /// Directory holding the entries
+    dir: PathBuf,
+    /// Held while pending entries are completed, rescheduled or moved
+    lock: Arc<Mutex<()>>,
+}
+
+impl FileOutbox {
+    /// Directory of the pending entries
+    const PENDING: &str = "pending";
+    /// Directory of the dead letters
+    const DEAD: &str = "dead";
+
+    /// Opens the outbox in the directory, creating it if needed
+    pub async fn open(dir: impl Into<PathBuf>) -> Result<Self, Error> {
+        let dir = dir.into();
+        for state in [Self::PENDING, Self::DEAD] {
+            json_store::create_dir(&dir.join(state)).await?;
+        }
+        Ok(Self {
+            dir,
+            lock: Arc::default(),
+        })
+    }
+
+    /// Returns the directory of the outbox
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the path of the entry with the ID in the given state
+    fn path(&self, state: &str, id: &str) -> PathBuf {
+        json_store::path(&self.dir.join(state), id)
+    }
+
+    /// Reads every entry in the given state
+    async fn read_all(&self, state: &str) -> Result<Vec<OutboxEntry>, Error> {
+        let entries = json_store::read_all(&self.dir.join(state)).await?;
+        Ok(entries.into_iter().map(|(_, entry)| entry).collect())
+    }
+}
+
+#[async_trait]
+impl Outbox for FileOutbox {
+    async fn enqueue(&self, entry: OutboxEntry) -> Result<bool, Error> {
+        if json_store::exists(&self.path(Self::DEAD, &entry.id)).await? {
+            return Ok(false);
+        }
+        json_store::create(&self.path(Self::PENDING, &entry.id), &entry).await
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<OutboxEntry>, Error> {
+        let entry: Option<OutboxEntry> = json_store::read(&self.path(Self::PENDING, id)).await?;
+        // Another ID with the same hash is treated as unknown
+        Ok(entry.filter(|entry| entry.id == id))
+    }
+
+    async fn due(&self, now: SystemTime, limit: usize) -> Result<Vec<OutboxEntry>, Error> {
+        let mut entries = self.read_all(Self::PENDING).await?;
+        entries.retain(|entry| entry.next_attempt_at <= now);
+        entries.sort_by_key(|entry| entry.next_attempt_at);
+        entries.truncate(limit);
+        Ok(entries)
+    }
+
+    async fn complete(&self, id: &str) -> Result<(), Error> {
+        let _guard = self.lock.lock().await;
+        json_store::remove(&self.path(Self::PENDING, id)).await
+    }
+
+    async fn reschedule(&self, entry: OutboxEntry) -> Result<bool, Error> {
+        let path = self.path(Self::PENDING, &entry.id);
+        let _guard = self.lock.lock().await;
+        if !json_store::exists(&path).await? {
+            return Ok(false);
+        }
+        json_store::write(&path, &entry).await?;
+        Ok(true)
+    }
+
+    async fn dead_letter(&self, entry: OutboxEntry) -> Result<(), Error> {
+        let _guard = self.lock.lock().await;
+        json_store::write(&self.path(Self::DEAD, &entry.id), &entry).await?;
+        json_store::remove(&self.path(Self::PENDING, &entry.id)).await
+    }
+
+    async fn dead_letters(&self) -> Result<Vec<OutboxEntry>, Error> {
+        self.read_all(Self::DEAD).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use googletest::matchers::{anything, elements_are, eq, len, none, pat, some};
+    use googletest::{expect_that, gtest};
+
+    use super::*;
+    use crate::fixtures::temp_outbox;
+    use crate::outbox::tests::entry;
+
+    #[tokio::test]
+    #[gtest]
+    async fn enqueues_each_id_once() {
+        let (_dir, outbox) = temp_outbox().await;
+
+        expect_that!(
+            outbox.enqueue(entry("signup/42")).await,
+            pat!(Ok(eq(&true)))
+        );
+        expect_that!(
+            outbox.enqueue(entry("signup/42")).await,
+            pat!(Ok(eq(&false)))
+        );
+
+        let due = outbox
+            .due(SystemTime::now(), 10)
+            .await
+            .expect("entries to be read");
+        expect_that!(
+            due,
+            elements_are![pat!(OutboxEntry {
+                id: eq("signup/42"),
+                ..
+            })]
+        );
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn concurrent_enqueues_store_the_entry_once() {
+        let (_dir, outbox) = temp_outbox().await;
+        let mut first = entry("signup/42");
+        first.email.subject = "first".to_owned();
+        let mut second = entry("signup/42");
+        second.email.subject = "second".to_owned();
+
+        let (first, second) = tokio::join!(outbox.enqueue(first), outbox.enqueue(second));
+        let created = [first, second]
+            .into_iter()
+            .map(|created| created.expect("entry to be stored"))
+            .filter(|&created| created)
+            .count();
+
+        expect_that!(created, eq(1));
+        expect_that!(
+            outbox.due(SystemTime::now(), 10).await,
+            pat!(Ok(len(eq(1))))
+        );
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn returns_due_entries_earliest_first() {
+        let (_dir, outbox) = temp_outbox().await;
+        let now = SystemTime::now();
+        for (id, delay) in [("later", 60), ("first", 0), ("second", 1)] {
+            let mut entry = entry(id);
+            entry.next_attempt_at = now - Duration::from_secs(10) + Duration::from_secs(delay);
+            outbox.enqueue(entry).await.expect("entry to be stored");
+        }
+
+        let due = outbox.due(now, 10).await.expect("entries to be read");
+        expect_that!(
+            due,
+            elements_are![
+                pat!(OutboxEntry {
+                    id: eq("first"),
+                    ..
+                }),
+                pat!(OutboxEntry {
+                    id: eq("second"),
+                    ..
+                })
+            ]
+        );
+        expect_that!(outbox.due(now, 1).await, pat!(Ok(len(eq(1)))));
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn stores_long_ids() {
+        let (_dir, outbox) = temp_outbox().await;
+        let id = "signup/".repeat(100);
+
+        expect_that!(outbox.enqueue(entry(&id)).await, pat!(Ok(eq(&true))));
+        expect_that!(
+            outbox.get(&id).await,
+            pat!(Ok(some(pat!(OutboxEntry { id: eq(&id), .. }))))
+        );
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn completes_and_dead_letters_entries() {
+        let (_dir, outbox) = temp_outbox().await;
+        outbox
+            .enqueue(entry("sent"))
+            .await
+            .expect("entry to be stored");
+        outbox
+            .enqueue(entry("failed"))
+            .await
+            .expect("entry to be stored");
+
+        outbox.complete("sent").await.expect("entry to be removed");
+        outbox
+            .dead_letter(entry("failed"))
+            .await
+            .expect("entry to be moved");
+
+        expect_that!(
+            outbox.due(SystemTime::now(), 10).await,
+            pat!(Ok(len(eq(0))))
+        );
+        expect_that!(
+            outbox.dead_letters().await,
+            pat!(Ok(elements_are![pat!(OutboxEntry {
+                id: eq("failed"),
+                ..
+            })]))
+        );
+        expect_that!(outbox.enqueue(entry("failed")).await, pat!(Ok(eq(&false))));
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn completed_entries_are_not_rescheduled() {
+        let (_dir, outbox) = temp_outbox().await;
+        outbox
+            .enqueue(entry("signup/42"))
+            .await
+            .expect("entry to be stored");
+
+        let mut pending = outbox
+            .get("signup/42")
+            .await
+            .expect("entry to be read")
+            .expect("entry to be pending");
+        outbox
+            .complete("signup/42")
+            .await
+            .expect("entry to be completed");
+        pending.next_attempt_at += Duration::from_secs(60);
+
+        expect_that!(outbox.reschedule(pending).await, pat!(Ok(eq(&false))));
+        expect_that!(outbox.get("signup/42").await, pat!(Ok(none())));
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn failed_writes_leave_no_temporary_files() {
+        let (_dir, outbox) = temp_outbox().await;
+        let pending = outbox.path(FileOutbox::PENDING, "signup/42");
+        // Renaming a file over a directory fails
+        tokio::fs::create_dir(&pending)
+            .await
+            .expect("directory to be created");
+
+        expect_that!(
+            outbox.reschedule(entry("signup/42")).await,
+            pat!(Err(pat!(Error::Storage(anything()))))
+        );
+        let mut files = tokio::fs::read_dir(outbox.dir().join(FileOutbox::PENDING))
+            .await
+            .expect("directory to be read");
+        let mut names = Vec::new();
+        while let Some(file) = files.next_entry().await.expect("directory to be read") {
+            names.push(file.path());
+        }
+        expect_that!(names, elements_are![eq(&pending)]);
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn quarantines_entries_that_cannot_be_parsed() {
+        let (_dir, outbox) = temp_outbox().await;
+        outbox
+            .enqueue(entry("signup/42"))
+            .await
+            .expect("entry to be stored");
+        let corrupt = outbox.path(FileOutbox::PENDING, "signup/43");
+        tokio::fs::write(&corrupt, b"{\"id\":")
+            .await
+            .expect("file to be written");
+
+        let due = outbox
+            .due(SystemTime::now(), 10)
+            .await
+            .expect("entries to be read");
+        expect_that!(
+            due,
+            elements_are![pat!(OutboxEntry {
+                id: eq("signup/42"),
+                ..
+            })]
+        );
+        expect_that!(
+            tokio::fs::try_exists(corrupt.with_extension("corrupt")).await,
+            pat!(Ok(eq(&true)))
+        );
+    }
+}
@@ -0,0 +1,352 @@
+//! Background delivery of the emails waiting in an outbox
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+
+use super::Outbox;
+use crate::clock::{Clock, SystemClock};
+use crate::error::Error;
+use crate::retry::RetryPolicy;
+use crate::service::DynEmailService;
+
+/// Outcome of one pass over the due entries of an outbox
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DrainSummary {
+    /// Emails accepted by the provider and removed from the outbox
+    pub sent: usize,
+    /// Emails that failed and will be tried again later
+    pub rescheduled: usize,
+    /// Emails that failed for good and were dead-lettered
+    pub dead_lettered: usize,
+}
+
+/// Sends the emails of an [`Outbox`] through an email service
+///
+/// Failed emails are rescheduled with the delays of the [`RetryPolicy`].
+/// Once the policy gives up, or when the error isn't retryable, the entry is
+/// dead-lettered. An entry is removed only after the service accepted its
+/// email, so an email may be sent twice if the process stops in between.
+#[derive(Debug)]
+pub struct OutboxWorker<O, S> {
+    /// Where the emails wait
+    outbox: O,
+    /// The service sending the emails
+    service: S,
+    /// When to retry and when to give up
+    policy: RetryPolicy,
+    /// Maximum number of entries sent per pass
+    batch_size: usize,
+    /// Pause between passes
+    poll_interval: Duration,
+    /// Source of the time entries are due against
+    clock: Arc<dyn Clock>,
+}
+
+impl<O, S> OutboxWorker<O, S>
+where
+    O: Outbox,
+    S: DynEmailService,
+{
+    /// Number of entries sent per pass unless set otherwise
+    pub const DEFAULT_BATCH_SIZE: usize = 100;
+    /// Pause between passes unless set otherwise
+    pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+    /// Longest a failed entry is put off, whatever the provider asks for
+    pub const MAX_RETRY_DELAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    /// Creates a worker draining the outbox through the service
+    pub fn new(outbox: O, service: S) -> Self {
+        Self {
+            outbox,
+            service,
+            policy: RetryPolicy::default(),
+            batch_size: Self::DEFAULT_BATCH_SIZE,
+            poll_interval: Self::DEFAULT_POLL_INTERVAL,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Sets the retry policy
+    ///
+    /// Entries are dead-lettered after [`RetryPolicy::max_attempts`] attempts.
+    #[must_use]
+    pub const fn with_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Sets the maximum number of entries sent per pass
+    #[must_use]
+    pub const fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Sets the pause between passes
+    #[must_use]
+    pub const fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Sets the clock used to find due entries and schedule retries
+    #[must_use]
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Returns the outbox
+    pub const fn outbox(&self) -> &O {
+        &self.outbox
+    }
+
+    /// Sends the entries that are due and records the outcomes
+    ///
+    /// Fails when the outbox cannot be read or updated. Emails sent before
+    /// the failure are kept in the outbox only if their completion failed.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "OutboxWorker::drain", skip(self), err(Debug))
+    )]
+    pub async fn drain(&self) -> Result<DrainSummary, Error> {
+        let mut summary = DrainSummary::default();
+        let entries = self.outbox.due(self.clock.now(), self.batch_size).await?;
+
+        for mut entry in entries {
+            match self.service.send(entry.email.clone()).await {
+                Ok(_delivery) => {
+                    self.outbox.complete(&entry.id).await?;
+                    summary.sent += 1;
+                }
+                Err(err) => {
+                    entry.attempts += 1;
+                    entry.last_error = Some(err.to_string());
+                    if self.policy.should_retry(entry.attempts, &err) {
+                        let delay = self.policy.delay(entry.attempts, &err);
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!(?err, id = %entry.id, attempts = entry.attempts, ?delay, "rescheduling email");
+                        let now = self.clock.now();
+                        entry.next_attempt_at = now
+                            .checked_add(delay.min(Self::MAX_RETRY_DELAY))
+                            .unwrap_or(now);
+                        // Entries cancelled while being sent stay gone
+                        if self.outbox.reschedule(entry).await? {
+                            summary.rescheduled += 1;
+                        }
+                    } else {
+                        #[cfg(feature = "tracing")]
+                        tracing::error!(?err, id = %entry.id, attempts = entry.attempts, "dead-lettering email");
+                        self.outbox.dead_letter(entry).await?;
+                        summary.dead_lettered += 1;
+                    }
+                }
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Drains the outbox forever, pausing between passes
+    ///
+    /// Storage errors are retried on the next pass.
+    pub async fn run(self) {
+        loop {
+            if let Err(_err) = self.drain().await {
+                #[cfg(feature = "tracing")]
+                tracing::error!(?_err, "outbox pass failed");
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Runs the worker on a tokio task
+    ///
+    /// Abort the returned handle to stop it.
+    pub fn spawn(self) -> JoinHandle<()>
+    where
+        O: 'static,
+        S: 'static,
+    {
+        tokio::spawn(self.run())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use googletest::matchers::{elements_are, eq, len, pat, some};
+    use googletest::{expect_that, gtest};
+
+    use super::*;
+    use crate::fixtures::Scripted;
+    use crate::fixtures::temp_outbox;
+    use crate::outbox::tests::entry;
+    use crate::outbox::{FileOutbox, OutboxEntry};
+
+    fn immediate_retries(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::ZERO,
+            jitter: false,
+            ..RetryPolicy::default()
+        }
+    }
+
+    fn outage() -> Error {
+        Error::SendFailed("connection failed".to_owned())
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn sends_and_removes_due_entries() {
+        let (_dir, outbox) = temp_outbox().await;
+        outbox
+            .enqueue(entry("a"))
+            .await
+            .expect("entry to be stored");
+        outbox
+            .enqueue(entry("b"))
+            .await
+            .expect("entry to be stored");
+        let service = Scripted::default();
+        let worker = OutboxWorker::new(outbox, service.clone());
+
+        let summary = worker.drain().await.expect("outbox to be drained");
+
+        expect_that!(summary.sent, eq(2));
+        expect_that!(service.calls(), eq(2));
+        expect_that!(
+            worker.outbox().due(SystemTime::now(), 10).await,
+            pat!(Ok(len(eq(0))))
+        );
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn reschedules_failures_until_they_succeed() {
+        let (_dir, outbox) = temp_outbox().await;
+        outbox
+            .enqueue(entry("a"))
+            .await
+            .expect("entry to be stored");
+        let service = Scripted::new([outage()]);
+        let worker = OutboxWorker::new(outbox, service.clone()).with_policy(immediate_retries(3));
+
+        let summary = worker.drain().await.expect("outbox to be drained");
+        expect_that!(summary.rescheduled, eq(1));
+        let due = worker
+            .outbox()
+            .due(SystemTime::now(), 10)
+            .await
+            .expect("entries to be read");
+        expect_that!(
+            due,
+            elements_are![pat!(OutboxEntry {
+                attempts: eq(&1),
+                last_error: some(eq("failed to send email: connection failed")),
+                ..
+            })]
+        );
+
+        let summary = worker.drain().await.expect("outbox to be drained");
+        expect_that!(summary.sent, eq(1));
+        expect_that!(service.calls(), eq(2));
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn caps_the_delay_requested_by_the_provider() {
+        let (_dir, outbox) = temp_outbox().await;
+        outbox
+            .enqueue(entry("a"))
+            .await
+            .expect("entry to be stored");
+        let service = Scripted::new([Error::RateLimitExceeded {
+            retry_after: Some(Duration::from_secs(u64::MAX)),
+        }]);
+        let worker = OutboxWorker::new(outbox, service).with_policy(immediate_retries(3));
+
+        let before = SystemTime::now();
+        let summary = worker.drain().await.expect("outbox to be drained");
+        expect_that!(summary.rescheduled, eq(1));
+
+        let latest = SystemTime::now() + OutboxWorker::<FileOutbox, Scripted>::MAX_RETRY_DELAY;
+        let due = worker
+            .outbox()
+            .due(latest, 10)
+            .await
+            .expect("entries to be read");
+        expect_that!(due, len(eq(1)));
+        expect_that!(
+            due.first().map(|entry| entry.next_attempt_at > before),
+            some(eq(true))
+        );
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn dead_letters_after_the_last_attempt() {
+        let (_dir, outbox) = temp_outbox().await;
+        outbox
+            .enqueue(entry("a"))
+            .await
+            .expect("entry to be stored");
+        let service = Scripted::new([outage(), outage()]);
+        let worker = OutboxWorker::new(outbox, service).with_policy(immediate_retries(2));
+
+        worker.drain().await.expect("outbox to be drained");
+        let summary = worker.drain().await.expect("outbox to be drained");
+
+        expect_that!(summary.dead_lettered, eq(1));
+        expect_that!(
+            worker.outbox().dead_letters().await,
+            pat!(Ok(elements_are![pat!(OutboxEntry {
+                attempts: eq(&2),
+                ..
+            })]))
+        );
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn dead_letters_permanent_failures_right_away() {
+        let (_dir, outbox) = temp_outbox().await;
+        outbox
+            .enqueue(entry("a"))
+            .await
+            .expect("entry to be stored");
+        let service = Scripted::new([Error::InvalidRecipient("nobody".to_owned())]);
+        let worker = OutboxWorker::new(outbox, service).with_policy(immediate_retries(5));
+
+        let summary = worker.drain().await.expect("outbox to be drained");
+
+        expect_that!(summary.dead_lettered, eq(1));
+        expect_that!(summary.rescheduled, eq(0));
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn spawned_worker_drains_the_outbox() {
+        let (_dir, outbox) = temp_outbox().await;
+        outbox
+            .enqueue(entry("a"))
+            .await
+            .expect("entry to be stored");
+        let service = Scripted::default();
+        let handle = OutboxWorker::new(outbox.clone(), service.clone())
+            .with_poll_interval(Duration::from_millis(10))
+            .spawn();
+
+        for _ in 0..100 {
+            if service.calls() > 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        handle.abort();
+
+        expect_that!(service.calls(), eq(1));
+    }
+}
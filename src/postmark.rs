@@ -1,12 +1,20 @@
 //! Email sending module with Postmark
 
 pub mod client;
+pub mod error;
 pub mod request;
 pub mod response;
+pub mod template;
 
 #[doc(inline)]
 pub use client::PostmarkClient;
 #[doc(inline)]
-pub use request::PostmarkEmailRequest;
+pub use error::PostmarkErrorCode;
 #[doc(inline)]
-pub use response::PostmarkEmailResponse;
+pub use request::{PostmarkBatchEmailRequest, PostmarkEmailRequest};
+#[doc(inline)]
+pub use response::{PostmarkBatchEmailResponse, PostmarkEmailResponse, PostmarkErrorResponse};
+#[doc(inline)]
+pub use template::{
+    PostmarkBatchTemplateEmailRequest, PostmarkTemplate, PostmarkTemplateEmailRequest,
+};
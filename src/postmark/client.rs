@@ -1,14 +1,24 @@
 //! The HTTP client that talks to the Postmark API
 use bytes::Bytes;
-use http::Request;
+use http::{Request, Response};
 use secrecy::ExposeSecret;
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "hyper")]
+pub mod hyper;
 #[cfg(feature = "reqwest")]
 pub mod reqwest;
+mod service;
+#[cfg(feature = "tower")]
+pub mod tower;
 
-use crate::api::ApiRequest;
+use crate::api::{ApiRequest, encode_body};
 use crate::config::ServiceConfig;
+use crate::email::{EmailMessage, MessageDefaults};
 use crate::error::Error;
+use crate::execute::parse_response;
+use crate::postmark::PostmarkErrorResponse;
 
 /// Client for interacting with Postmark APIs
 #[derive(Debug)]
@@ -17,6 +27,8 @@ pub struct PostmarkClient<C> {
     pub config: ServiceConfig,
     /// HTTP Client
     pub client: C,
+    /// Defaults applied to every [`EmailMessage`] before it is sent
+    pub defaults: MessageDefaults,
 }
 
 impl<C> PostmarkClient<C> {
@@ -27,7 +39,23 @@ impl<C> PostmarkClient<C> {
 
     /// Creates new [`PostmarkClient`] instance
     pub const fn new(client: C, config: ServiceConfig) -> Self {
-        Self { client, config }
+        Self {
+            client,
+            config,
+            defaults: MessageDefaults::new(),
+        }
+    }
+
+    /// Sets the defaults applied to outgoing messages
+    #[must_use]
+    pub fn with_defaults(mut self, defaults: MessageDefaults) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    /// Applies [`MessageDefaults::prepare`] with [`ServiceConfig::from_email`]
+    pub fn prepare_email(&self, email: EmailMessage) -> Result<EmailMessage, Error> {
+        self.defaults.prepare(email, &self.config.from_email)
     }
 
     /// Creates new HTTP request for Postmark API
@@ -40,19 +68,13 @@ impl<C> PostmarkClient<C> {
         )
     )]
     pub fn new_http_request<R: ApiRequest>(&self, request: &R) -> Result<Request<Bytes>, Error> {
-        let body = serde_json::to_vec(request)
-            .map(Bytes::from)
-            .map_err(|err| {
-                #[cfg(feature = "tracing")]
-                tracing::error!(?err);
-                Error::SendFailed(format!("failed to serialize email: {err}"))
-            })?;
+        let encoded = encode_body(request)?;
         let uri = format!("{}{}", self.config.base_url, R::ENDPOINT);
 
         let mut request = Request::builder()
             .method(R::METHOD)
             .uri(uri)
-            .header("content-type", "application/json")
+            .header("content-type", encoded.content_type)
             .header("accept", "application/json")
             .header(
                 Self::X_POSTMARK_SERVER_TOKEN,
@@ -66,10 +88,21 @@ impl<C> PostmarkClient<C> {
             );
         }
 
-        request.body(body).map_err(|err| {
+        request.body(encoded.body).map_err(|err| {
             #[cfg(feature = "tracing")]
             tracing::error!(?err);
-            Error::SendFailed(format!("failed to build HTTP request: {err}"))
+            Error::InvalidRequest(format!("failed to build HTTP request: {err}"))
         })
     }
+
+    /// Parses a Postmark HTTP response into the expected type
+    ///
+    /// Non-success statuses become errors, with `429 Too Many Requests`
+    /// mapped to [`Error::RateLimitExceeded`].
+    pub fn parse_response<Res>(response: Response<Bytes>) -> Result<Res, Error>
+    where
+        Res: TryFrom<Response<Bytes>, Error = Error>,
+    {
+        parse_response(response, PostmarkErrorResponse::error_from_response)
+    }
 }
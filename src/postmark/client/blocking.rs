@@ -0,0 +1,60 @@
+//! Blocking implementations for [`PostmarkClient<reqwest::blocking::Client>`]
+use bytes::Bytes;
+use http::{Request, Response};
+
+use super::PostmarkClient;
+use crate::blocking::{EmailService, Execute};
+use crate::email::{EmailDelivery, EmailMessage};
+use crate::error::Error;
+use crate::execute::build_response;
+use crate::postmark::{PostmarkEmailRequest, PostmarkEmailResponse};
+
+impl Execute for PostmarkClient<reqwest::blocking::Client> {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "PostmarkClient::execute", skip(self, request), err(Debug))
+    )]
+    fn execute<Req, Res>(&self, request: Req) -> Result<Res, Error>
+    where
+        Req: Into<Request<Bytes>>,
+        Res: TryFrom<Response<Bytes>, Error = Error>,
+    {
+        let request = request.into();
+        let reqwest_request = request.try_into().inspect_err(|_err| {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?_err);
+        })?;
+
+        let response = self.client.execute(reqwest_request)?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.bytes().inspect_err(|_err| {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?_err)
+        })?;
+
+        let http_response = build_response(status, headers, body)?;
+        Self::parse_response(http_response)
+    }
+}
+
+impl<C> EmailService<EmailMessage, EmailDelivery> for PostmarkClient<C>
+where
+    Self: Execute,
+{
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "PostmarkClient::send_email", skip(self))
+    )]
+    fn send_email(&self, email: EmailMessage) -> Result<EmailDelivery, Error> {
+        let postmark_request: PostmarkEmailRequest = self.prepare_email(email)?.into();
+        let request = self.new_http_request(&postmark_request)?;
+
+        let response: PostmarkEmailResponse = self.execute(request).inspect_err(|_err| {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?_err);
+        })?;
+
+        Ok(response.into())
+    }
+}
@@ -0,0 +1,41 @@
+//! Execute trait implementation for [`PostmarkClient`] over a `hyper` client
+use async_trait::async_trait;
+use bytes::Bytes;
+use http::{Request, Response};
+use http_body_util::{BodyExt, Full};
+use hyper_util::client::legacy::Client;
+use hyper_util::client::legacy::connect::Connect;
+
+use super::PostmarkClient;
+use crate::error::Error;
+use crate::execute::{Execute, build_response};
+
+#[async_trait]
+impl<C> Execute for PostmarkClient<Client<C, Full<Bytes>>>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "PostmarkClient::execute", skip(self, request), err(Debug))
+    )]
+    async fn execute<Req, Res>(&self, request: Req) -> Result<Res, Error>
+    where
+        Req: Into<Request<Bytes>> + Send,
+        Res: TryFrom<Response<Bytes>, Error = Error>,
+    {
+        let request = request.into().map(Full::new);
+        let response = self.client.request(request).await.inspect_err(|_err| {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?_err);
+        })?;
+        let (parts, body) = response.into_parts();
+        let body = body.collect().await.inspect_err(|_err| {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?_err)
+        })?;
+
+        let http_response = build_response(parts.status, parts.headers, body.to_bytes())?;
+        Self::parse_response(http_response)
+    }
+}
@@ -0,0 +1,139 @@
+//! EmailService implementations for every [`PostmarkClient`] that can execute requests
+use async_trait::async_trait;
+use serde::Serialize;
+
+use super::PostmarkClient;
+use crate::email::{EmailDelivery, EmailMessage};
+use crate::error::Error;
+use crate::execute::Execute;
+use crate::postmark::{
+    PostmarkBatchEmailRequest, PostmarkBatchEmailResponse, PostmarkBatchTemplateEmailRequest,
+    PostmarkEmailRequest, PostmarkEmailResponse, PostmarkTemplateEmailRequest,
+};
+use crate::service::{BatchEmailService, EmailService};
+
+#[async_trait]
+impl<C> EmailService<EmailMessage, EmailDelivery> for PostmarkClient<C>
+where
+    Self: Execute,
+{
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "PostmarkClient::send_email", skip(self))
+    )]
+    async fn send_email(&self, email: EmailMessage) -> Result<EmailDelivery, Error> {
+        let postmark_request: PostmarkEmailRequest = self.prepare_email(email)?.into();
+        let request = self.new_http_request(&postmark_request)?;
+
+        let response: PostmarkEmailResponse = self.execute(request).await.inspect_err(|_err| {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?_err);
+        })?;
+
+        Ok(response.into())
+    }
+}
+
+#[async_trait]
+impl<C> BatchEmailService<EmailMessage, EmailDelivery> for PostmarkClient<C>
+where
+    Self: Execute,
+{
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "PostmarkClient::send_batch", skip(self, emails), fields(batch_size = emails.len()))
+    )]
+    async fn send_batch(
+        &self,
+        emails: Vec<EmailMessage>,
+    ) -> Result<Vec<Result<EmailDelivery, Error>>, Error> {
+        if emails.is_empty() {
+            return Ok(Vec::new());
+        }
+        if emails.len() > PostmarkBatchEmailRequest::MAX_MESSAGES {
+            return Err(Error::InvalidRequest(format!(
+                "batch of {} emails exceeds the limit of {}",
+                emails.len(),
+                PostmarkBatchEmailRequest::MAX_MESSAGES
+            )));
+        }
+
+        let batch_request = emails
+            .into_iter()
+            .map(|email| self.prepare_email(email).map(Into::into))
+            .collect::<Result<Vec<PostmarkEmailRequest>, _>>()
+            .map(PostmarkBatchEmailRequest::from)?;
+        let request = self.new_http_request(&batch_request)?;
+
+        let response: PostmarkBatchEmailResponse =
+            self.execute(request).await.inspect_err(|_err| {
+                #[cfg(feature = "tracing")]
+                tracing::error!(?_err);
+            })?;
+
+        Ok(response.into_results())
+    }
+}
+
+#[async_trait]
+impl<C, T> EmailService<PostmarkTemplateEmailRequest<T>, EmailDelivery> for PostmarkClient<C>
+where
+    Self: Execute,
+    T: Serialize + Send + Sync + 'static,
+{
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "PostmarkClient::send_template_email", skip(self, email))
+    )]
+    async fn send_email(
+        &self,
+        email: PostmarkTemplateEmailRequest<T>,
+    ) -> Result<EmailDelivery, Error> {
+        let request = self.new_http_request(&email)?;
+
+        let response: PostmarkEmailResponse = self.execute(request).await.inspect_err(|_err| {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?_err);
+        })?;
+
+        Ok(response.into())
+    }
+}
+
+#[async_trait]
+impl<C, T> BatchEmailService<PostmarkTemplateEmailRequest<T>, EmailDelivery> for PostmarkClient<C>
+where
+    Self: Execute,
+    T: Serialize + Send + Sync + 'static,
+{
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "PostmarkClient::send_template_batch", skip(self, emails), fields(batch_size = emails.len()))
+    )]
+    async fn send_batch(
+        &self,
+        emails: Vec<PostmarkTemplateEmailRequest<T>>,
+    ) -> Result<Vec<Result<EmailDelivery, Error>>, Error> {
+        if emails.is_empty() {
+            return Ok(Vec::new());
+        }
+        if emails.len() > PostmarkBatchTemplateEmailRequest::<T>::MAX_MESSAGES {
+            return Err(Error::InvalidRequest(format!(
+                "batch of {} emails exceeds the limit of {}",
+                emails.len(),
+                PostmarkBatchTemplateEmailRequest::<T>::MAX_MESSAGES
+            )));
+        }
+
+        let batch_request = PostmarkBatchTemplateEmailRequest::from(emails);
+        let request = self.new_http_request(&batch_request)?;
+
+        let response: PostmarkBatchEmailResponse =
+            self.execute(request).await.inspect_err(|_err| {
+                #[cfg(feature = "tracing")]
+                tracing::error!(?_err);
+            })?;
+
+        Ok(response.into_results())
+    }
+}
@@ -0,0 +1,31 @@
+//! Execute trait implementation for [`PostmarkClient<TowerClient<S>>`]
+use async_trait::async_trait;
+use bytes::Bytes;
+use http::{Request, Response};
+use tower_service::Service;
+
+use super::PostmarkClient;
+use crate::error::Error;
+use crate::execute::Execute;
+use crate::tower::{BoxError, TowerClient};
+
+#[async_trait]
+impl<S> Execute for PostmarkClient<TowerClient<S>>
+where
+    S: Service<Request<Bytes>, Response = Response<Bytes>> + Clone + Send + Sync,
+    S::Future: Send,
+    S::Error: Into<BoxError>,
+{
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "PostmarkClient::execute", skip(self, request), err(Debug))
+    )]
+    async fn execute<Req, Res>(&self, request: Req) -> Result<Res, Error>
+    where
+        Req: Into<Request<Bytes>> + Send,
+        Res: TryFrom<Response<Bytes>, Error = Error>,
+    {
+        let response = self.client.call(request.into()).await?;
+        Self::parse_response(response)
+    }
+}
@@ -0,0 +1,199 @@
+//! Postmark API error codes
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ErrorClass;
+
+/// Error codes documented by the Postmark API
+///
+/// Codes that aren't listed here are kept as [`PostmarkErrorCode::Unknown`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "u16", into = "u16")]
+pub enum PostmarkErrorCode {
+    /// Bad or missing API token (10)
+    BadApiToken,
+    /// Postmark is under maintenance (100)
+    Maintenance,
+    /// Invalid email request (300)
+    InvalidEmailRequest,
+    /// Sender signature not found (400)
+    SenderSignatureNotFound,
+    /// Sender signature not confirmed (401)
+    SenderSignatureNotConfirmed,
+    /// Invalid JSON (402)
+    InvalidJson,
+    /// Incompatible JSON (403)
+    IncompatibleJson,
+    /// Not allowed to send, the account ran out of credits (405)
+    NotAllowedToSend,
+    /// The recipient is inactive because of a bounce or spam complaint (406)
+    InactiveRecipient,
+    /// Bounce not found (407)
+    BounceNotFound,
+    /// Bounce query exception (408)
+    BounceQueryException,
+    /// The request body must be JSON (409)
+    JsonRequired,
+    /// Too many messages in a batch request (410)
+    TooManyBatchMessages,
+    /// Forbidden attachment type (411)
+    ForbiddenAttachmentType,
+    /// The account is pending approval (412)
+    AccountPending,
+    /// The account may not send (413)
+    AccountMayNotSend,
+    /// Rate limit exceeded (429)
+    RateLimitExceeded,
+    /// Template not found (1101)
+    TemplateNotFound,
+    /// A code not covered by this enum
+    Unknown(u16),
+}
+
+impl PostmarkErrorCode {
+    /// Returns the numeric code as sent by Postmark
+    pub const fn code(self) -> u16 {
+        match self {
+            Self::BadApiToken => 10,
+            Self::Maintenance => 100,
+            Self::InvalidEmailRequest => 300,
+            Self::SenderSignatureNotFound => 400,
+            Self::SenderSignatureNotConfirmed => 401,
+            Self::InvalidJson => 402,
+            Self::IncompatibleJson => 403,
+            Self::NotAllowedToSend => 405,
+            Self::InactiveRecipient => 406,
+            Self::BounceNotFound => 407,
+            Self::BounceQueryException => 408,
+            Self::JsonRequired => 409,
+            Self::TooManyBatchMessages => 410,
+            Self::ForbiddenAttachmentType => 411,
+            Self::AccountPending => 412,
+            Self::AccountMayNotSend => 413,
+            Self::RateLimitExceeded => 429,
+            Self::TemplateNotFound => 1101,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    /// Classifies the failure to help decide whether to retry
+    ///
+    /// Unknown codes are treated as permanent so they aren't retried blindly.
+    pub const fn class(self) -> ErrorClass {
+        match self {
+            Self::Maintenance | Self::RateLimitExceeded => ErrorClass::Retryable,
+            Self::BadApiToken
+            | Self::SenderSignatureNotFound
+            | Self::SenderSignatureNotConfirmed
+            | Self::NotAllowedToSend
+            | Self::AccountPending
+            | Self::AccountMayNotSend
+            | Self::TemplateNotFound => ErrorClass::Configuration,
+            Self::InvalidEmailRequest
+            | Self::InvalidJson
+            | Self::IncompatibleJson
+            | Self::InactiveRecipient
+            | Self::BounceNotFound
+            | Self::BounceQueryException
+            | Self::JsonRequired
+            | Self::TooManyBatchMessages
+            | Self::ForbiddenAttachmentType
+            | Self::Unknown(_) => ErrorClass::Permanent,
+        }
+    }
+}
+
+impl From<u16> for PostmarkErrorCode {
+    fn from(code: u16) -> Self {
+        match code {
+            10 => Self::BadApiToken,
+            100 => Self::Maintenance,
+            300 => Self::InvalidEmailRequest,
+            400 => Self::SenderSignatureNotFound,
+            401 => Self::SenderSignatureNotConfirmed,
+            402 => Self::InvalidJson,
+            403 => Self::IncompatibleJson,
+            405 => Self::NotAllowedToSend,
+            406 => Self::InactiveRecipient,
+            407 => Self::BounceNotFound,
+            408 => Self::BounceQueryException,
+            409 => Self::JsonRequired,
+            410 => Self::TooManyBatchMessages,
+            411 => Self::ForbiddenAttachmentType,
+            412 => Self::AccountPending,
+            413 => Self::AccountMayNotSend,
+            429 => Self::RateLimitExceeded,
+            1101 => Self::TemplateNotFound,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<PostmarkErrorCode> for u16 {
+    fn from(code: PostmarkErrorCode) -> Self {
+        code.code()
+    }
+}
+
+impl fmt::Display for PostmarkErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::matchers::eq;
+    use googletest::{expect_that, gtest};
+
+    use super::*;
+
+    #[gtest]
+    fn known_codes_round_trip() {
+        for code in [
+            10, 100, 300, 400, 401, 402, 403, 405, 406, 407, 408, 409, 410, 411, 412, 413, 429,
+            1101,
+        ] {
+            let error_code = PostmarkErrorCode::from(code);
+            assert!(!matches!(error_code, PostmarkErrorCode::Unknown(_)));
+            expect_that!(u16::from(error_code), eq(code));
+        }
+    }
+
+    #[gtest]
+    fn unknown_code_is_kept() {
+        expect_that!(
+            PostmarkErrorCode::from(1234),
+            eq(PostmarkErrorCode::Unknown(1234))
+        );
+        expect_that!(PostmarkErrorCode::Unknown(1234).code(), eq(1234));
+    }
+
+    #[gtest]
+    fn classifies_codes() {
+        expect_that!(
+            PostmarkErrorCode::BadApiToken.class(),
+            eq(ErrorClass::Configuration)
+        );
+        expect_that!(
+            PostmarkErrorCode::InactiveRecipient.class(),
+            eq(ErrorClass::Permanent)
+        );
+        expect_that!(
+            PostmarkErrorCode::RateLimitExceeded.class(),
+            eq(ErrorClass::Retryable)
+        );
+        expect_that!(
+            PostmarkErrorCode::Unknown(9999).class(),
+            eq(ErrorClass::Permanent)
+        );
+    }
+
+    #[gtest]
+    fn deserializes_from_number() {
+        let code: PostmarkErrorCode =
+            serde_json::from_str("406").expect("deserialization to succeed");
+        expect_that!(code, eq(PostmarkErrorCode::InactiveRecipient));
+    }
+}
@@ -9,7 +9,7 @@ use serde_with::formats::CommaSeparator;
 use serde_with::{StringWithSeparator, serde_as};
 
 use crate::api::ApiRequest;
-use crate::email::{Attachment, Body, EmailMessage, Header, Recipients};
+use crate::email::{Attachment, Body, EmailMessage, Header, Mailbox, Recipients};
 
 /// Postmark email request
 #[serde_as]
@@ -18,13 +18,15 @@ use crate::email::{Attachment, Body, EmailMessage, Header, Recipients};
 #[serde(rename_all = "PascalCase")]
 #[cfg_attr(feature = "garde", derive(Validate))]
 pub struct PostmarkEmailRequest {
-    /// The sender email address
-    #[cfg_attr(feature = "garde", garde(email))]
-    pub from: String,
-    /// Recipient email addresses
-    #[cfg_attr(feature = "garde", garde(length(min = 1, max = 50), inner(email)))]
-    #[serde_as(as = "StringWithSeparator::<CommaSeparator, String>")]
-    pub to: Vec<String>,
+    /// The sender mailbox
+    ///
+    /// Postmark rejects requests without one.
+    #[cfg_attr(feature = "garde", garde(required, dive))]
+    pub from: Option<Mailbox>,
+    /// Recipient mailboxes
+    #[cfg_attr(feature = "garde", garde(length(min = 1, max = 50), dive))]
+    #[serde_as(as = "StringWithSeparator::<CommaSeparator, Mailbox>")]
+    pub to: Vec<Mailbox>,
     /// Email subject
     #[cfg_attr(feature = "garde", garde(skip))]
     pub subject: String,
@@ -32,21 +34,21 @@ pub struct PostmarkEmailRequest {
     #[cfg_attr(feature = "garde", garde(skip))]
     #[serde(flatten)]
     pub body: PostmarkBody,
-    /// Cc recipient email addresses
-    #[cfg_attr(feature = "garde", garde(length(max = 50), inner(inner(email))))]
-    #[serde_as(as = "Option<StringWithSeparator::<CommaSeparator, String>>")]
-    pub cc: Option<Vec<String>>,
-    /// Bcc recipient email addresses
-    #[cfg_attr(feature = "garde", garde(length(max = 50), inner(inner(email))))]
-    #[serde_as(as = "Option<StringWithSeparator::<CommaSeparator, String>>")]
-    pub bcc: Option<Vec<String>>,
+    /// Cc recipient mailboxes
+    #[cfg_attr(feature = "garde", garde(length(max = 50), dive))]
+    #[serde_as(as = "Option<StringWithSeparator::<CommaSeparator, Mailbox>>")]
+    pub cc: Option<Vec<Mailbox>>,
+    /// Bcc recipient mailboxes
+    #[cfg_attr(feature = "garde", garde(length(max = 50), dive))]
+    #[serde_as(as = "Option<StringWithSeparator::<CommaSeparator, Mailbox>>")]
+    pub bcc: Option<Vec<Mailbox>>,
     /// Email tag for categorization (max 1000 chars for Postmark)
     #[cfg_attr(feature = "garde", garde(length(max = 1000)))]
     pub tag: Option<String>,
     /// Reply-To override
-    #[cfg_attr(feature = "garde", garde(inner(inner(email))))]
-    #[serde_as(as = "Option<StringWithSeparator::<CommaSeparator, String>>")]
-    pub reply_to: Option<Vec<String>>,
+    #[cfg_attr(feature = "garde", garde(dive))]
+    #[serde_as(as = "Option<StringWithSeparator::<CommaSeparator, Mailbox>>")]
+    pub reply_to: Option<Vec<Mailbox>>,
     /// Custom headers
     #[cfg_attr(feature = "garde", garde(skip))]
     pub headers: Option<Vec<PostmarkHeader>>,
@@ -68,6 +70,14 @@ pub enum PostmarkBody {
     TextBody(String),
     /// HTML email body
     HtmlBody(String),
+    /// Plain text and HTML email bodies sent together
+    #[serde(untagged, rename_all = "PascalCase")]
+    Multipart {
+        /// Plain text email body
+        text_body: String,
+        /// HTML email body
+        html_body: String,
+    },
 }
 
 /// Postmark custom header
@@ -94,16 +104,58 @@ pub struct PostmarkAttachment {
     pub content_id: Option<String>,
 }
 
+/// Postmark batch email request
+///
+/// Serialized as a JSON array of [`PostmarkEmailRequest`], up to
+/// [`PostmarkBatchEmailRequest::MAX_MESSAGES`] per call.
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+#[cfg_attr(feature = "garde", derive(Validate))]
+#[cfg_attr(feature = "garde", garde(transparent))]
+pub struct PostmarkBatchEmailRequest(
+    #[cfg_attr(feature = "garde", garde(length(min = 1, max = 500), dive))]
+    Vec<PostmarkEmailRequest>,
+);
+
+impl PostmarkBatchEmailRequest {
+    /// Maximum number of messages Postmark accepts in a single batch
+    pub const MAX_MESSAGES: usize = 500;
+
+    /// Returns the number of messages in the batch
+    pub const fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the batch contains no messages
+    pub const fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Consumes self and returns the inner list of requests
+    pub fn into_inner(self) -> Vec<PostmarkEmailRequest> {
+        self.0
+    }
+}
+
 impl ApiRequest for PostmarkEmailRequest {
     const METHOD: Method = Method::POST;
     const ENDPOINT: &'static str = "/email";
 }
 
+impl ApiRequest for PostmarkBatchEmailRequest {
+    const METHOD: Method = Method::POST;
+    const ENDPOINT: &'static str = "/email/batch";
+}
+
 impl From<Body> for PostmarkBody {
     fn from(body: Body) -> Self {
         match body {
             Body::Text(text) => PostmarkBody::TextBody(text),
             Body::Html(html) => PostmarkBody::HtmlBody(html),
+            Body::Multipart { text, html } => PostmarkBody::Multipart {
+                text_body: text,
+                html_body: html,
+            },
         }
     }
 }
@@ -151,6 +203,18 @@ impl From<EmailMessage> for PostmarkEmailRequest {
     }
 }
 
+impl From<Vec<PostmarkEmailRequest>> for PostmarkBatchEmailRequest {
+    fn from(requests: Vec<PostmarkEmailRequest>) -> Self {
+        Self(requests)
+    }
+}
+
+impl From<Vec<EmailMessage>> for PostmarkBatchEmailRequest {
+    fn from(emails: Vec<EmailMessage>) -> Self {
+        Self(emails.into_iter().map(Into::into).collect())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use googletest::matchers::{eq, none, some};
@@ -166,7 +230,7 @@ mod tests {
     /// Create a minimal email data with given body variant.
     fn minimal_email(body: Body) -> EmailMessage {
         EmailMessage {
-            from: "wangari.maathai@example.africa".to_owned(),
+            from: Some("wangari.maathai@example.africa".into()),
             to: vec!["kwame.nkrumah@example.africa"].into(),
             subject: "Green Belt Movement Monthly Update".to_owned(),
             body,
@@ -178,6 +242,7 @@ mod tests {
             metadata: None,
             attachments: None,
             message_stream: None,
+            idempotency_key: None,
         }
     }
 
@@ -196,9 +261,12 @@ mod tests {
         let email = minimal_email(Body::Text("Hello".to_owned()));
         let postmark: PostmarkEmailRequest = email.into();
 
-        expect_that!(postmark.from.as_str(), eq("wangari.maathai@example.africa"));
         expect_that!(
-            postmark.to.first().map(|r| r.as_str()),
+            postmark.from.as_ref().map(|from| from.email.as_str()),
+            some(eq("wangari.maathai@example.africa"))
+        );
+        expect_that!(
+            postmark.to.first().map(|r| r.email.as_str()),
             some(eq("kwame.nkrumah@example.africa"))
         );
         expect_that!(
@@ -221,13 +289,44 @@ mod tests {
         assert!(matches!(postmark.body, PostmarkBody::HtmlBody(ref body) if body == "<p>html</p>"));
     }
 
+    #[gtest]
+    fn from_email_request_converts_multipart_body() {
+        let email = minimal_email(Body::Multipart {
+            text: "plain text".to_owned(),
+            html: "<p>html</p>".to_owned(),
+        });
+        let postmark: PostmarkEmailRequest = email.into();
+        assert!(matches!(
+            postmark.body,
+            PostmarkBody::Multipart { ref text_body, ref html_body }
+                if text_body == "plain text" && html_body == "<p>html</p>"
+        ));
+    }
+
+    #[gtest]
+    fn from_email_request_with_text_alternative() {
+        let email =
+            minimal_email(Body::Html("<p>Pull together.</p>".to_owned())).with_text_alternative();
+        let postmark: PostmarkEmailRequest = email.into();
+        let json: Value = serde_json::to_value(&postmark).expect("serialization to succeed");
+
+        expect_that!(
+            json.get("TextBody").and_then(|v| v.as_str()),
+            some(eq("Pull together."))
+        );
+        expect_that!(
+            json.get("HtmlBody").and_then(|v| v.as_str()),
+            some(eq("<p>Pull together.</p>"))
+        );
+    }
+
     #[gtest]
     fn from_email_request_maps_optional_fields() {
         let mut metadata = HashMap::new();
         metadata.insert("key".to_owned(), "value".to_owned());
 
         let email = EmailMessage {
-            from: "chimamanda.adichie@example.africa".to_owned(),
+            from: Some("chimamanda.adichie@example.africa".into()),
             to: vec!["yaa.asantewaa@example.africa"].into(),
             subject: "Subject".to_owned(),
             body: Body::Text("Body".to_owned()),
@@ -251,6 +350,7 @@ mod tests {
                 content_id: Some("ci:yeyi".to_owned()),
             }]),
             message_stream: Some("outbound".to_owned()),
+            idempotency_key: None,
         };
 
         let postmark: PostmarkEmailRequest = email.into();
@@ -260,7 +360,7 @@ mod tests {
                 .cc
                 .as_ref()
                 .and_then(|v| v.first())
-                .map(|s| s.as_str()),
+                .map(|s| s.email.as_str()),
             some(eq("steve.biko@example.africa"))
         );
         expect_that!(
@@ -268,7 +368,7 @@ mod tests {
                 .bcc
                 .as_ref()
                 .and_then(|v| v.first())
-                .map(|s| s.as_str()),
+                .map(|s| s.email.as_str()),
             some(eq("miriam.makeba@example.africa"))
         );
         expect_that!(postmark.tag.as_deref(), some(eq("tag-value")));
@@ -277,7 +377,7 @@ mod tests {
                 .reply_to
                 .as_ref()
                 .and_then(|v| v.first())
-                .map(|s| s.as_str()),
+                .map(|s| s.email.as_str()),
             some(eq("gbehanzin@example.africa"))
         );
         expect_that!(
@@ -337,7 +437,7 @@ mod tests {
     #[gtest]
     fn pascal_case_serialization_optional_fields() {
         let email = EmailMessage {
-            from: "kwame.nkrumah@example.africa".to_owned(),
+            from: Some("kwame.nkrumah@example.africa".into()),
             to: vec!["yaa.asantewaa@example.africa"].into(),
             subject: "Pan-African Congress Invitation".to_owned(),
             body: Body::Text("Africa must unite for true independence.".to_owned()),
@@ -349,6 +449,7 @@ mod tests {
             metadata: None,
             attachments: None,
             message_stream: Some("independence-movement".to_owned()),
+            idempotency_key: None,
         };
 
         let postmark: PostmarkEmailRequest = email.into();
@@ -422,6 +523,22 @@ mod tests {
             some(eq("<h1>Pan-African Unity Conference</h1>"))
         );
         expect_that!(json.get("TextBody"), none());
+
+        let email = minimal_email(Body::Multipart {
+            text: "Pan-African Unity Conference".to_owned(),
+            html: "<h1>Pan-African Unity Conference</h1>".to_owned(),
+        });
+        let postmark: PostmarkEmailRequest = email.into();
+        let json: Value = serde_json::to_value(&postmark).expect("serialization to succeed");
+        expect_that!(
+            json.get("TextBody").and_then(|v| v.as_str()),
+            some(eq("Pan-African Unity Conference"))
+        );
+        expect_that!(
+            json.get("HtmlBody").and_then(|v| v.as_str()),
+            some(eq("<h1>Pan-African Unity Conference</h1>"))
+        );
+        expect_that!(json.get("Multipart"), none());
     }
 
     #[gtest]
@@ -472,11 +589,11 @@ mod tests {
     #[gtest]
     fn multiple_recipients_comma_separated() {
         let postmark = PostmarkEmailRequest {
-            from: "sender@example.africa".to_owned(),
+            from: Some("sender@example.africa".into()),
             to: vec![
-                "wangari.maathai@example.africa".to_owned(),
-                "thomas.sankara@example.africa".to_owned(),
-                "miriam.makeba@example.africa".to_owned(),
+                "wangari.maathai@example.africa".into(),
+                "thomas.sankara@example.africa".into(),
+                "miriam.makeba@example.africa".into(),
             ],
             subject: "Multi-recipient".to_owned(),
             body: PostmarkBody::TextBody("Hello all".to_owned()),
@@ -506,16 +623,16 @@ mod tests {
         metadata.insert("literary_genre".to_owned(), "african-fiction".to_owned());
 
         let postmark = PostmarkEmailRequest {
-            from: "chimamanda.adichie@example.africa".to_owned(),
-            to: vec!["yaa.asantewaa@example.africa".to_owned()],
+            from: Some("chimamanda.adichie@example.africa".into()),
+            to: vec!["yaa.asantewaa@example.africa".into()],
             subject: "New Novel Draft Ready for Review".to_owned(),
             body: PostmarkBody::TextBody(
                 "The story of our ancestors deserves to be told.".to_owned(),
             ),
-            cc: Some(vec!["steve.biko@example.africa".to_owned()]),
-            bcc: Some(vec!["miriam.makeba@example.africa".to_owned()]),
+            cc: Some(vec!["steve.biko@example.africa".into()]),
+            bcc: Some(vec!["miriam.makeba@example.africa".into()]),
             tag: Some("african-literature".to_owned()),
-            reply_to: Some(vec!["gbehanzin@example.africa".to_owned()]),
+            reply_to: Some(vec!["gbehanzin@example.africa".into()]),
             headers: Some(vec![PostmarkHeader {
                 name: "X-Manuscript-Id".to_owned(),
                 value: "half-of-a-yellow-sun-draft".to_owned(),
@@ -576,6 +693,34 @@ mod tests {
         );
     }
 
+    #[gtest]
+    fn batch_api_request_endpoint_is_email_batch() {
+        expect_that!(PostmarkBatchEmailRequest::METHOD, eq(Method::POST));
+        expect_that!(PostmarkBatchEmailRequest::ENDPOINT, eq("/email/batch"));
+    }
+
+    #[gtest]
+    fn batch_serializes_as_array_of_requests() {
+        let batch = PostmarkBatchEmailRequest::from(vec![
+            minimal_email(Body::Text("First reminder".to_owned())),
+            minimal_email(Body::Html("<p>Second reminder</p>".to_owned())),
+        ]);
+        expect_that!(batch.len(), eq(2));
+
+        let json: Value = serde_json::to_value(&batch).expect("serialization to succeed");
+        let messages = json.as_array().expect("batch to be an array");
+
+        expect_that!(messages.len(), eq(2));
+        expect_that!(
+            messages[0].get("TextBody").and_then(|v| v.as_str()),
+            some(eq("First reminder"))
+        );
+        expect_that!(
+            messages[1].get("HtmlBody").and_then(|v| v.as_str()),
+            some(eq("<p>Second reminder</p>"))
+        );
+    }
+
     #[cfg(feature = "garde")]
     mod validation_tests {
         use garde::Validate;
@@ -587,7 +732,7 @@ mod tests {
         fn tag_max_length_1000_fails() {
             let long_tag = "x".repeat(1001);
             let email = EmailMessage {
-                from: "miriam.makeba@example.africa".to_owned(),
+                from: Some("miriam.makeba@example.africa".into()),
                 to: Recipients::from(vec!["gbehanzin@example.africa".to_owned()]),
                 subject: "Mama Africa World Tour Dates".to_owned(),
                 body: Body::Text("Music carries the voice of our people across oceans.".to_owned()),
@@ -599,6 +744,7 @@ mod tests {
                 metadata: None,
                 attachments: None,
                 message_stream: None,
+                idempotency_key: None,
             };
 
             let postmark: PostmarkEmailRequest = email.into();
@@ -609,7 +755,7 @@ mod tests {
         fn tag_at_max_length_1000_passes() {
             let max_tag = "y".repeat(1000);
             let email = EmailMessage {
-                from: "wangari.maathai@example.africa".to_owned(),
+                from: Some("wangari.maathai@example.africa".into()),
                 to: Recipients::from(vec!["thomas.sankara@example.africa".to_owned()]),
                 subject: "Reforestation Partnership Proposal".to_owned(),
                 body: Body::Text(
@@ -623,6 +769,7 @@ mod tests {
                 metadata: None,
                 attachments: None,
                 message_stream: None,
+                idempotency_key: None,
             };
 
             let postmark: PostmarkEmailRequest = email.into();
@@ -631,11 +778,11 @@ mod tests {
 
         #[gtest]
         fn recipients_max_50_passes() {
-            let emails: Vec<String> = (1..=50)
-                .map(|count| format!("member{count}@example.africa"))
+            let emails: Vec<Mailbox> = (1..=50)
+                .map(|count| format!("member{count}@example.africa").into())
                 .collect();
             let postmark = PostmarkEmailRequest {
-                from: "sender@example.africa".to_owned(),
+                from: Some("sender@example.africa".into()),
                 to: emails,
                 subject: "Subject".to_owned(),
                 body: PostmarkBody::TextBody("Body".to_owned()),
@@ -653,11 +800,11 @@ mod tests {
 
         #[gtest]
         fn recipients_exceeds_50_fails() {
-            let emails: Vec<String> = (1..=51)
-                .map(|count| format!("overflow{count}@example.africa"))
+            let emails: Vec<Mailbox> = (1..=51)
+                .map(|count| format!("overflow{count}@example.africa").into())
                 .collect();
             let postmark = PostmarkEmailRequest {
-                from: "sender@example.africa".to_owned(),
+                from: Some("sender@example.africa".into()),
                 to: emails,
                 subject: "Subject".to_owned(),
                 body: PostmarkBody::TextBody("Body".to_owned()),
@@ -672,5 +819,20 @@ mod tests {
             };
             expect_that!(postmark.validate(), err(anything()));
         }
+
+        #[gtest]
+        fn batch_exceeds_500_messages_fails() {
+            let emails: Vec<EmailMessage> = (0..=PostmarkBatchEmailRequest::MAX_MESSAGES)
+                .map(|_| minimal_email(Body::Text("Body".to_owned())))
+                .collect();
+            let batch = PostmarkBatchEmailRequest::from(emails);
+            expect_that!(batch.validate(), err(anything()));
+        }
+
+        #[gtest]
+        fn batch_empty_fails() {
+            let batch = PostmarkBatchEmailRequest::from(Vec::<EmailMessage>::new());
+            expect_that!(batch.validate(), err(anything()));
+        }
     }
 }
@@ -1,9 +1,10 @@
 //! Postmark-specific response types
 
 use bytes::Bytes;
-use http::Response;
+use http::{Response, StatusCode};
 use serde::Deserialize;
 
+use super::error::PostmarkErrorCode;
 use crate::email::EmailDelivery;
 use crate::error::Error;
 
@@ -24,12 +25,143 @@ pub struct PostmarkEmailResponse {
     pub message: String,
 }
 
+impl PostmarkEmailResponse {
+    /// Returns the typed API error code
+    pub fn code(&self) -> PostmarkErrorCode {
+        self.error_code.into()
+    }
+}
+
 impl TryFrom<Response<Bytes>> for PostmarkEmailResponse {
     type Error = Error;
 
     fn try_from(response: Response<Bytes>) -> Result<Self, Self::Error> {
         serde_json::from_slice(response.body())
-            .map_err(|err| Error::SendFailed(format!("failed to parse response: {err}")))
+            .map_err(|err| Error::UnexpectedResponse(format!("failed to parse response: {err}")))
+    }
+}
+
+/// Postmark error response body
+///
+/// Returned alongside non-success HTTP statuses, typically `422 Unprocessable Entity`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PostmarkErrorResponse {
+    /// API error code
+    pub error_code: u16,
+    /// Human-readable error message
+    pub message: String,
+}
+
+impl PostmarkErrorResponse {
+    /// Returns the typed API error code
+    pub fn code(&self) -> PostmarkErrorCode {
+        self.error_code.into()
+    }
+
+    /// Converts the error body into an [`Error::Api`] with the given HTTP status
+    pub fn into_error(self, status: StatusCode) -> Error {
+        Error::Api {
+            status,
+            error_code: self.error_code,
+            class: Some(self.code().class()),
+            message: self.message,
+        }
+    }
+
+    /// Builds the [`Error`] for a non-success HTTP response
+    ///
+    /// Falls back to [`Error::unexpected_status`] when the body isn't a
+    /// Postmark error.
+    pub fn error_from_response(response: &Response<Bytes>) -> Error {
+        let status = response.status();
+        match serde_json::from_slice::<Self>(response.body()) {
+            Ok(error) => error.into_error(status),
+            Err(_err) => {
+                #[cfg(feature = "tracing")]
+                tracing::error!(?_err, %status);
+                Error::unexpected_status(status)
+            }
+        }
+    }
+}
+
+/// Postmark batch email response
+///
+/// Holds one result per message, in the same order as the batch request.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(transparent)]
+pub struct PostmarkBatchEmailResponse(pub Vec<PostmarkBatchResult>);
+
+/// Outcome of a single message within a Postmark batch
+///
+/// Postmark omits the recipient, timestamp and message ID for messages
+/// it rejected, so those fields are optional.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PostmarkBatchResult {
+    /// Recipient email address
+    pub to: Option<String>,
+    /// Submission timestamp
+    pub submitted_at: Option<String>,
+    /// Postmark message ID
+    #[serde(rename = "MessageID")]
+    pub message_id: Option<String>,
+    /// API error code
+    pub error_code: u16,
+    /// Human-readable response message
+    pub message: String,
+}
+
+impl PostmarkBatchResult {
+    /// Returns the typed API error code
+    pub fn code(&self) -> PostmarkErrorCode {
+        self.error_code.into()
+    }
+}
+
+impl PostmarkBatchEmailResponse {
+    /// Converts every message result into a delivery receipt or an error
+    pub fn into_results(self) -> Vec<Result<EmailDelivery, Error>> {
+        self.0.into_iter().map(EmailDelivery::try_from).collect()
+    }
+}
+
+impl TryFrom<Response<Bytes>> for PostmarkBatchEmailResponse {
+    type Error = Error;
+
+    fn try_from(response: Response<Bytes>) -> Result<Self, Self::Error> {
+        serde_json::from_slice(response.body()).map_err(|err| {
+            Error::UnexpectedResponse(format!("failed to parse batch response: {err}"))
+        })
+    }
+}
+
+impl TryFrom<PostmarkBatchResult> for EmailDelivery {
+    type Error = Error;
+
+    fn try_from(result: PostmarkBatchResult) -> Result<Self, Self::Error> {
+        if result.error_code != 0 {
+            // Postmark rejects single sends with the same error codes under a 422
+            let error = PostmarkErrorResponse {
+                error_code: result.error_code,
+                message: result.message,
+            };
+            return Err(error.into_error(StatusCode::UNPROCESSABLE_ENTITY));
+        }
+
+        let message_id = result
+            .message_id
+            .ok_or_else(|| Error::UnexpectedResponse("batch result is missing MessageID".into()))?;
+
+        Ok(Self {
+            to: result.to.unwrap_or_default(),
+            submitted_at: result.submitted_at.unwrap_or_default(),
+            message_id,
+            error_code: result.error_code,
+            message: result.message,
+            provider: None,
+        })
     }
 }
 
@@ -41,16 +173,18 @@ impl From<PostmarkEmailResponse> for EmailDelivery {
             message_id: res.message_id,
             error_code: res.error_code,
             message: res.message,
+            provider: None,
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use googletest::matchers::{anything, eq, err};
+    use googletest::matchers::{anything, eq, err, none, pat};
     use googletest::{expect_that, gtest};
 
     use super::*;
+    use crate::error::ErrorClass;
 
     fn make_json(
         to: &str,
@@ -165,4 +299,132 @@ mod tests {
         let result = PostmarkEmailResponse::try_from(http_response);
         expect_that!(result, err(anything()));
     }
+
+    #[gtest]
+    fn batch_response_reports_each_message() {
+        let json = r#"[
+            {"To": "kwame.nkrumah@example.africa", "SubmittedAt": "2026-02-09T08:30:00Z", "MessageID": "sendout-msg-b1", "ErrorCode": 0, "Message": "OK"},
+            {"ErrorCode": 406, "Message": "You tried to send to a recipient that has been marked as inactive."}
+        ]"#;
+        let http_response = http::Response::builder()
+            .status(200)
+            .body(bytes::Bytes::from(json))
+            .expect("valid response");
+
+        let results = PostmarkBatchEmailResponse::try_from(http_response)
+            .expect("successful parse")
+            .into_results();
+
+        expect_that!(results.len(), eq(2));
+        let delivery = results[0].as_ref().expect("first message delivered");
+        expect_that!(delivery.message_id, eq("sendout-msg-b1"));
+        expect_that!(delivery.to, eq("kwame.nkrumah@example.africa"));
+        assert!(matches!(
+            results[1],
+            Err(Error::Api {
+                error_code: 406,
+                ..
+            })
+        ));
+    }
+
+    #[gtest]
+    fn error_response_becomes_api_error() {
+        let http_response = http::Response::builder()
+            .status(422)
+            .body(bytes::Bytes::from(
+                r#"{"ErrorCode": 406, "Message": "You tried to send to a recipient that has been marked as inactive."}"#,
+            ))
+            .expect("valid response");
+
+        let body: PostmarkErrorResponse =
+            serde_json::from_slice(http_response.body()).expect("deserialization to succeed");
+        expect_that!(body.code(), eq(PostmarkErrorCode::InactiveRecipient));
+
+        let error = PostmarkErrorResponse::error_from_response(&http_response);
+        assert!(matches!(
+            error,
+            Error::Api {
+                status: StatusCode::UNPROCESSABLE_ENTITY,
+                error_code: 406,
+                ..
+            }
+        ));
+        expect_that!(error.class(), eq(ErrorClass::Permanent));
+    }
+
+    #[gtest]
+    fn error_codes_decide_the_class() {
+        let http_response = http::Response::builder()
+            .status(422)
+            .body(bytes::Bytes::from(
+                r#"{"ErrorCode": 412, "Message": "Your account is pending approval."}"#,
+            ))
+            .expect("valid response");
+
+        let error = PostmarkErrorResponse::error_from_response(&http_response);
+        expect_that!(error.class(), eq(ErrorClass::Configuration));
+    }
+
+    #[gtest]
+    fn unknown_codes_are_permanent_whatever_the_status() {
+        let http_response = http::Response::builder()
+            .status(503)
+            .body(bytes::Bytes::from(
+                r#"{"ErrorCode": 9999, "Message": "Something new went wrong."}"#,
+            ))
+            .expect("valid response");
+
+        let error = PostmarkErrorResponse::error_from_response(&http_response);
+        expect_that!(error.class(), eq(ErrorClass::Permanent));
+    }
+
+    #[gtest]
+    fn error_response_without_body_is_classified_by_status() {
+        let http_response = http::Response::builder()
+            .status(503)
+            .body(bytes::Bytes::from("Service Unavailable"))
+            .expect("valid response");
+
+        let error = PostmarkErrorResponse::error_from_response(&http_response);
+        expect_that!(
+            error,
+            pat!(Error::Api {
+                status: eq(&StatusCode::SERVICE_UNAVAILABLE),
+                class: none(),
+                ..
+            })
+        );
+        expect_that!(error.class(), eq(ErrorClass::Retryable));
+    }
+
+    #[gtest]
+    fn client_errors_without_postmark_body_are_not_retried() {
+        for (status, class) in [
+            (StatusCode::UNAUTHORIZED, ErrorClass::Configuration),
+            (StatusCode::NOT_FOUND, ErrorClass::Permanent),
+            (StatusCode::PAYLOAD_TOO_LARGE, ErrorClass::Permanent),
+        ] {
+            let http_response = http::Response::builder()
+                .status(status)
+                .body(bytes::Bytes::from("<html>Request rejected</html>"))
+                .expect("valid response");
+
+            let error = PostmarkErrorResponse::error_from_response(&http_response);
+            expect_that!(error.class(), eq(class));
+        }
+    }
+
+    #[gtest]
+    fn batch_result_without_message_id_fails() {
+        let result = PostmarkBatchResult {
+            to: Some("yaa.asantewaa@example.africa".to_owned()),
+            submitted_at: None,
+            message_id: None,
+            error_code: 0,
+            message: "OK".to_owned(),
+        };
+
+        expect_that!(EmailDelivery::try_from(result), err(anything()));
+    }
 }
@@ -0,0 +1,274 @@
+//! Postmark template request types
+use std::collections::HashMap;
+
+#[cfg(feature = "garde")]
+use garde::Validate;
+use http::Method;
+use serde::Serialize;
+use serde_with::formats::CommaSeparator;
+use serde_with::{StringWithSeparator, serde_as};
+
+use super::request::{PostmarkAttachment, PostmarkBatchEmailRequest, PostmarkHeader};
+use crate::api::ApiRequest;
+use crate::email::Mailbox;
+
+/// Postmark email request rendered from a server-side template
+///
+/// The `template_model` holds the variables the template expects and can
+/// be any serializable type.
+#[serde_as]
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[cfg_attr(feature = "garde", derive(Validate))]
+pub struct PostmarkTemplateEmailRequest<T> {
+    /// The sender mailbox
+    #[cfg_attr(feature = "garde", garde(dive))]
+    pub from: Mailbox,
+    /// Recipient mailboxes
+    #[cfg_attr(feature = "garde", garde(length(min = 1, max = 50), dive))]
+    #[serde_as(as = "StringWithSeparator::<CommaSeparator, Mailbox>")]
+    pub to: Vec<Mailbox>,
+    /// The template to render
+    #[cfg_attr(feature = "garde", garde(skip))]
+    #[serde(flatten)]
+    pub template: PostmarkTemplate,
+    /// Variables made available to the template
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub template_model: T,
+    /// Whether Postmark should inline the CSS in `<style>` tags
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub inline_css: Option<bool>,
+    /// Cc recipient mailboxes
+    #[cfg_attr(feature = "garde", garde(length(max = 50), dive))]
+    #[serde_as(as = "Option<StringWithSeparator::<CommaSeparator, Mailbox>>")]
+    pub cc: Option<Vec<Mailbox>>,
+    /// Bcc recipient mailboxes
+    #[cfg_attr(feature = "garde", garde(length(max = 50), dive))]
+    #[serde_as(as = "Option<StringWithSeparator::<CommaSeparator, Mailbox>>")]
+    pub bcc: Option<Vec<Mailbox>>,
+    /// Email tag for categorization (max 1000 chars for Postmark)
+    #[cfg_attr(feature = "garde", garde(length(max = 1000)))]
+    pub tag: Option<String>,
+    /// Reply-To override
+    #[cfg_attr(feature = "garde", garde(dive))]
+    #[serde_as(as = "Option<StringWithSeparator::<CommaSeparator, Mailbox>>")]
+    pub reply_to: Option<Vec<Mailbox>>,
+    /// Custom headers
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub headers: Option<Vec<PostmarkHeader>>,
+    /// Custom metadata key/value pairs
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub metadata: Option<HashMap<String, String>>,
+    /// File attachments
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub attachments: Option<Vec<PostmarkAttachment>>,
+    /// Message stream ID
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub message_stream: Option<String>,
+}
+
+/// Identifies the Postmark template to use
+#[derive(Debug, Clone, Serialize)]
+pub enum PostmarkTemplate {
+    /// Numeric template ID assigned by Postmark
+    TemplateId(u64),
+    /// Template alias chosen when creating the template
+    TemplateAlias(String),
+}
+
+/// Postmark batch templated email request
+///
+/// Serialized as `{"Messages": [...]}`, up to
+/// [`PostmarkBatchEmailRequest::MAX_MESSAGES`] per call.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[cfg_attr(feature = "garde", derive(Validate))]
+pub struct PostmarkBatchTemplateEmailRequest<T> {
+    /// The templated messages to send
+    #[cfg_attr(feature = "garde", garde(length(min = 1, max = 500), dive))]
+    pub messages: Vec<PostmarkTemplateEmailRequest<T>>,
+}
+
+impl<T> PostmarkTemplateEmailRequest<T> {
+    /// Creates a templated request with the required fields
+    ///
+    /// Every optional field is left unset.
+    pub fn new(
+        from: impl Into<Mailbox>,
+        to: Vec<Mailbox>,
+        template: PostmarkTemplate,
+        template_model: T,
+    ) -> Self {
+        Self {
+            from: from.into(),
+            to,
+            template,
+            template_model,
+            inline_css: None,
+            cc: None,
+            bcc: None,
+            tag: None,
+            reply_to: None,
+            headers: None,
+            metadata: None,
+            attachments: None,
+            message_stream: None,
+        }
+    }
+}
+
+impl<T> PostmarkBatchTemplateEmailRequest<T> {
+    /// Maximum number of messages Postmark accepts in a single batch
+    pub const MAX_MESSAGES: usize = PostmarkBatchEmailRequest::MAX_MESSAGES;
+}
+
+impl<T: Serialize> ApiRequest for PostmarkTemplateEmailRequest<T> {
+    const METHOD: Method = Method::POST;
+    const ENDPOINT: &'static str = "/email/withTemplate";
+}
+
+impl<T: Serialize> ApiRequest for PostmarkBatchTemplateEmailRequest<T> {
+    const METHOD: Method = Method::POST;
+    const ENDPOINT: &'static str = "/email/batchWithTemplates";
+}
+
+impl<T> From<Vec<PostmarkTemplateEmailRequest<T>>> for PostmarkBatchTemplateEmailRequest<T> {
+    fn from(messages: Vec<PostmarkTemplateEmailRequest<T>>) -> Self {
+        Self { messages }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::matchers::{eq, none, some};
+    use googletest::{expect_that, gtest};
+    use serde_json::Value;
+
+    use super::*;
+
+    /// Template variables for a password reset email
+    #[derive(Debug, Clone, Serialize)]
+    struct PasswordReset {
+        /// Name of the account holder
+        name: String,
+        /// Link to reset the password
+        action_url: String,
+    }
+
+    fn password_reset(template: PostmarkTemplate) -> PostmarkTemplateEmailRequest<PasswordReset> {
+        PostmarkTemplateEmailRequest::new(
+            "wangari.maathai@example.africa",
+            vec!["kwame.nkrumah@example.africa".into()],
+            template,
+            PasswordReset {
+                name: "Kwame".to_owned(),
+                action_url: "https://example.africa/reset".to_owned(),
+            },
+        )
+    }
+
+    #[gtest]
+    fn api_request_endpoints() {
+        expect_that!(
+            PostmarkTemplateEmailRequest::<PasswordReset>::METHOD,
+            eq(Method::POST)
+        );
+        expect_that!(
+            PostmarkTemplateEmailRequest::<PasswordReset>::ENDPOINT,
+            eq("/email/withTemplate")
+        );
+        expect_that!(
+            PostmarkBatchTemplateEmailRequest::<PasswordReset>::ENDPOINT,
+            eq("/email/batchWithTemplates")
+        );
+    }
+
+    #[gtest]
+    fn template_id_serializes_with_model() {
+        let mut request = password_reset(PostmarkTemplate::TemplateId(4_242));
+        request.inline_css = Some(true);
+
+        let json: Value = serde_json::to_value(&request).expect("serialization to succeed");
+
+        expect_that!(
+            json.get("TemplateId").and_then(Value::as_u64),
+            some(eq(4_242))
+        );
+        expect_that!(json.get("TemplateAlias"), none());
+        expect_that!(json.get("Template"), none());
+        expect_that!(
+            json.get("TemplateModel")
+                .and_then(|m| m.get("action_url"))
+                .and_then(Value::as_str),
+            some(eq("https://example.africa/reset"))
+        );
+        expect_that!(
+            json.get("InlineCss").and_then(Value::as_bool),
+            some(eq(true))
+        );
+        expect_that!(
+            json.get("To").and_then(Value::as_str),
+            some(eq("kwame.nkrumah@example.africa"))
+        );
+        expect_that!(json.get("Cc"), none());
+        expect_that!(json.get("MessageStream"), none());
+    }
+
+    #[gtest]
+    fn template_alias_serializes() {
+        let request = password_reset(PostmarkTemplate::TemplateAlias("password-reset".to_owned()));
+        let json: Value = serde_json::to_value(&request).expect("serialization to succeed");
+
+        expect_that!(
+            json.get("TemplateAlias").and_then(Value::as_str),
+            some(eq("password-reset"))
+        );
+        expect_that!(json.get("TemplateId"), none());
+        expect_that!(json.get("InlineCss"), none());
+    }
+
+    #[gtest]
+    fn batch_serializes_messages_key() {
+        let batch = PostmarkBatchTemplateEmailRequest::from(vec![
+            password_reset(PostmarkTemplate::TemplateId(1)),
+            password_reset(PostmarkTemplate::TemplateAlias("welcome".to_owned())),
+        ]);
+        let json: Value = serde_json::to_value(&batch).expect("serialization to succeed");
+
+        expect_that!(
+            json.get("Messages").and_then(Value::as_array).map(Vec::len),
+            some(eq(2))
+        );
+    }
+
+    #[cfg(feature = "garde")]
+    mod validation_tests {
+        use garde::Validate;
+        use googletest::matchers::{anything, err, ok};
+
+        use super::*;
+
+        #[gtest]
+        fn valid_template_request_passes() {
+            let request = password_reset(PostmarkTemplate::TemplateId(1));
+            expect_that!(request.validate(), ok(anything()));
+        }
+
+        #[gtest]
+        fn invalid_sender_fails() {
+            let mut request = password_reset(PostmarkTemplate::TemplateId(1));
+            request.from = "not-an-email".into();
+            expect_that!(request.validate(), err(anything()));
+        }
+
+        #[gtest]
+        fn batch_exceeds_500_messages_fails() {
+            let messages = (0..=PostmarkBatchTemplateEmailRequest::<PasswordReset>::MAX_MESSAGES)
+                .map(|_| password_reset(PostmarkTemplate::TemplateId(1)))
+                .collect::<Vec<_>>();
+            let batch = PostmarkBatchTemplateEmailRequest::from(messages);
+            expect_that!(batch.validate(), err(anything()));
+        }
+    }
+}
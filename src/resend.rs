@@ -0,0 +1,12 @@
+//! Email sending module with Resend
+
+pub mod client;
+pub mod request;
+pub mod response;
+
+#[doc(inline)]
+pub use client::ResendClient;
+#[doc(inline)]
+pub use request::{ResendBatchEmailRequest, ResendEmailRequest};
+#[doc(inline)]
+pub use response::{ResendBatchEmailResponse, ResendEmailResponse, ResendErrorResponse};
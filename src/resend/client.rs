@@ -0,0 +1,101 @@
+//! The HTTP client that talks to the Resend API
+use bytes::Bytes;
+use http::{Request, Response};
+use secrecy::ExposeSecret;
+
+#[cfg(feature = "reqwest")]
+pub mod reqwest;
+
+use crate::api::{ApiRequest, encode_body};
+use crate::config::ServiceConfig;
+use crate::email::{EmailMessage, MessageDefaults};
+use crate::error::Error;
+use crate::execute::parse_response;
+use crate::resend::ResendErrorResponse;
+
+/// Client for interacting with Resend APIs
+///
+/// [`ServiceConfig::base_url`] is usually `https://api.resend.com`, and
+/// [`ServiceConfig::server_token`] holds the API key.
+#[derive(Debug)]
+pub struct ResendClient<C> {
+    /// Service provider configuration
+    pub config: ServiceConfig,
+    /// HTTP Client
+    pub client: C,
+    /// Defaults applied to every [`EmailMessage`] before it is sent
+    pub defaults: MessageDefaults,
+}
+
+impl<C> ResendClient<C> {
+    /// Header carrying [`ApiRequest::idempotency_key`]
+    const IDEMPOTENCY_KEY: &str = "Idempotency-Key";
+
+    /// Creates new [`ResendClient`] instance
+    pub const fn new(client: C, config: ServiceConfig) -> Self {
+        Self {
+            client,
+            config,
+            defaults: MessageDefaults::new(),
+        }
+    }
+
+    /// Sets the defaults applied to outgoing messages
+    #[must_use]
+    pub fn with_defaults(mut self, defaults: MessageDefaults) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    /// Applies [`MessageDefaults::prepare`] with [`ServiceConfig::from_email`]
+    pub fn prepare_email(&self, email: EmailMessage) -> Result<EmailMessage, Error> {
+        self.defaults.prepare(email, &self.config.from_email)
+    }
+
+    /// Creates new HTTP request for Resend API
+    ///
+    /// The request's idempotency key, if any, is sent in the
+    /// `Idempotency-Key` header.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "ResendClient::new_http_request",
+            skip(self, request),
+            err(Debug)
+        )
+    )]
+    pub fn new_http_request<R: ApiRequest>(&self, request: &R) -> Result<Request<Bytes>, Error> {
+        let encoded = encode_body(request)?;
+        let uri = format!("{}{}", self.config.base_url, R::ENDPOINT);
+
+        let mut builder = Request::builder()
+            .method(R::METHOD)
+            .uri(uri)
+            .header("content-type", encoded.content_type)
+            .header("accept", "application/json")
+            .header(
+                "authorization",
+                format!("Bearer {}", self.config.server_token.expose_secret()),
+            );
+        if let Some(idempotency_key) = request.idempotency_key() {
+            builder = builder.header(Self::IDEMPOTENCY_KEY, idempotency_key);
+        }
+
+        builder.body(encoded.body).map_err(|err| {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?err);
+            Error::InvalidRequest(format!("failed to build HTTP request: {err}"))
+        })
+    }
+
+    /// Parses a Resend HTTP response into the expected type
+    ///
+    /// Non-success statuses become errors, with `429 Too Many Requests`
+    /// mapped to [`Error::RateLimitExceeded`].
+    pub fn parse_response<Res>(response: Response<Bytes>) -> Result<Res, Error>
+    where
+        Res: TryFrom<Response<Bytes>, Error = Error>,
+    {
+        parse_response(response, ResendErrorResponse::error_from_response)
+    }
+}
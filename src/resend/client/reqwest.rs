@@ -0,0 +1,119 @@
+//! EmailService and Execute trait implementation for [`ResendClient<reqwest::Client>`]
+use async_trait::async_trait;
+use bytes::Bytes;
+use http::Request;
+use reqwest::Client;
+
+use super::ResendClient;
+use crate::email::{EmailDelivery, EmailMessage};
+use crate::error::Error;
+use crate::execute::{Execute, send_with_reqwest};
+use crate::resend::{
+    ResendBatchEmailRequest, ResendBatchEmailResponse, ResendEmailRequest, ResendEmailResponse,
+};
+use crate::service::{BatchEmailService, EmailService};
+
+#[async_trait]
+impl EmailService<EmailMessage, EmailDelivery> for ResendClient<Client> {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "ResendClient::send_email")
+    )]
+    async fn send_email(&self, email: EmailMessage) -> Result<EmailDelivery, Error> {
+        let resend_request: ResendEmailRequest = self.prepare_email(email)?.into();
+        EmailService::<ResendEmailRequest, EmailDelivery>::send_email(self, resend_request).await
+    }
+}
+
+#[async_trait]
+impl EmailService<ResendEmailRequest, EmailDelivery> for ResendClient<Client> {
+    /// Sends a prepared request, which may carry an idempotency key
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "ResendClient::send_request", skip(email))
+    )]
+    async fn send_email(&self, email: ResendEmailRequest) -> Result<EmailDelivery, Error> {
+        let request = self.new_http_request(&email)?;
+
+        let response: ResendEmailResponse = self.execute(request).await.inspect_err(|_err| {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?_err);
+        })?;
+
+        Ok(response.into_delivery(&email.to))
+    }
+}
+
+#[async_trait]
+impl BatchEmailService<EmailMessage, EmailDelivery> for ResendClient<Client> {
+    /// Sends the emails in a single batch request
+    ///
+    /// Resend doesn't accept attachments in batches, so batches with any are
+    /// rejected before sending. Idempotency keys of the individual emails
+    /// are ignored, as Resend only takes one for the whole batch.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "ResendClient::send_batch", skip(emails), fields(batch_size = emails.len()))
+    )]
+    async fn send_batch(
+        &self,
+        emails: Vec<EmailMessage>,
+    ) -> Result<Vec<Result<EmailDelivery, Error>>, Error> {
+        if emails.is_empty() {
+            return Ok(Vec::new());
+        }
+        if emails.len() > ResendBatchEmailRequest::MAX_MESSAGES {
+            return Err(Error::InvalidRequest(format!(
+                "batch of {} emails exceeds the limit of {}",
+                emails.len(),
+                ResendBatchEmailRequest::MAX_MESSAGES
+            )));
+        }
+        if emails.iter().any(|email| {
+            email
+                .attachments
+                .as_ref()
+                .is_some_and(|atts| !atts.is_empty())
+        }) {
+            return Err(Error::InvalidRequest(
+                "Resend batches don't support attachments".to_owned(),
+            ));
+        }
+
+        let batch_request = emails
+            .into_iter()
+            .map(|email| self.prepare_email(email).map(Into::into))
+            .collect::<Result<Vec<ResendEmailRequest>, _>>()
+            .map(ResendBatchEmailRequest::from)?;
+        let recipients = batch_request
+            .emails
+            .iter()
+            .map(|email| email.to.clone())
+            .collect::<Vec<_>>();
+        let request = self.new_http_request(&batch_request)?;
+
+        let response: ResendBatchEmailResponse =
+            self.execute(request).await.inspect_err(|_err| {
+                #[cfg(feature = "tracing")]
+                tracing::error!(?_err);
+            })?;
+
+        Ok(response.into_results(&recipients))
+    }
+}
+
+#[async_trait]
+impl Execute for ResendClient<reqwest::Client> {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "ResendClient::execute", skip(self, request), err(Debug))
+    )]
+    async fn execute<Req, Res>(&self, request: Req) -> Result<Res, Error>
+    where
+        Req: Into<Request<Bytes>> + Send,
+        Res: TryFrom<http::Response<Bytes>, Error = Error>,
+    {
+        let response = send_with_reqwest(&self.client, request.into()).await?;
+        Self::parse_response(response)
+    }
+}
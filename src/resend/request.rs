@@ -0,0 +1,291 @@
+//! Resend-specific request types
+use std::collections::HashMap;
+
+#[cfg(feature = "garde")]
+use garde::Validate;
+use http::Method;
+use serde::Serialize;
+
+use crate::api::ApiRequest;
+use crate::email::{Attachment, Body, EmailMessage, Mailbox, Recipients};
+
+/// Resend email request
+///
+/// The tag and metadata become Resend tags, and the message stream has no
+/// Resend equivalent so it is dropped.
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "garde", derive(Validate))]
+pub struct ResendEmailRequest {
+    /// The sender mailbox
+    ///
+    /// Resend rejects requests without one.
+    #[cfg_attr(feature = "garde", garde(required, dive))]
+    pub from: Option<Mailbox>,
+    /// Recipient mailboxes
+    #[cfg_attr(feature = "garde", garde(length(min = 1, max = 50), dive))]
+    pub to: Vec<Mailbox>,
+    /// Email subject
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub subject: String,
+    /// Cc recipient mailboxes
+    #[cfg_attr(feature = "garde", garde(dive))]
+    pub cc: Option<Vec<Mailbox>>,
+    /// Bcc recipient mailboxes
+    #[cfg_attr(feature = "garde", garde(dive))]
+    pub bcc: Option<Vec<Mailbox>>,
+    /// Reply-To mailboxes
+    #[cfg_attr(feature = "garde", garde(dive))]
+    pub reply_to: Option<Vec<Mailbox>>,
+    /// HTML body
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub html: Option<String>,
+    /// Plain text body
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub text: Option<String>,
+    /// Custom headers
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub headers: Option<HashMap<String, String>>,
+    /// File attachments, rejected by Resend in batches
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub attachments: Option<Vec<ResendAttachment>>,
+    /// Tags passed back in webhooks
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub tags: Option<Vec<ResendTag>>,
+    /// Sent as the `Idempotency-Key` header
+    #[serde(skip)]
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub idempotency_key: Option<String>,
+}
+
+/// Resend attachment
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, Serialize)]
+pub struct ResendAttachment {
+    /// Base64-encoded content
+    pub content: String,
+    /// File name
+    pub filename: String,
+    /// MIME content type
+    pub content_type: Option<String>,
+    /// Content ID referenced from the HTML body
+    pub content_id: Option<String>,
+}
+
+/// Resend tag
+///
+/// Names and values may only contain ASCII letters, numbers, underscores
+/// and dashes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResendTag {
+    /// Tag name
+    pub name: String,
+    /// Tag value
+    pub value: String,
+}
+
+impl ResendTag {
+    /// Name of the tag carrying [`EmailMessage::tag`]
+    pub const TAG: &str = "tag";
+}
+
+/// Resend batch email request
+///
+/// Serialized as a JSON array of [`ResendEmailRequest`], up to
+/// [`ResendBatchEmailRequest::MAX_MESSAGES`] per call. Idempotency keys of
+/// the individual emails are ignored in favor of the batch's own, and Resend
+/// rejects the whole batch when an email has attachments.
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+#[cfg_attr(feature = "garde", derive(Validate))]
+pub struct ResendBatchEmailRequest {
+    /// Emails of the batch
+    #[cfg_attr(feature = "garde", garde(length(min = 1, max = 100), dive))]
+    pub emails: Vec<ResendEmailRequest>,
+    /// Sent as the `Idempotency-Key` header
+    #[serde(skip)]
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub idempotency_key: Option<String>,
+}
+
+impl ResendBatchEmailRequest {
+    /// Maximum number of messages Resend accepts in a single batch
+    pub const MAX_MESSAGES: usize = 100;
+}
+
+impl ApiRequest for ResendEmailRequest {
+    const METHOD: Method = Method::POST;
+    const ENDPOINT: &'static str = "/emails";
+
+    fn idempotency_key(&self) -> Option<&str> {
+        self.idempotency_key.as_deref()
+    }
+}
+
+impl ApiRequest for ResendBatchEmailRequest {
+    const METHOD: Method = Method::POST;
+    const ENDPOINT: &'static str = "/emails/batch";
+
+    fn idempotency_key(&self) -> Option<&str> {
+        self.idempotency_key.as_deref()
+    }
+}
+
+impl From<Vec<ResendEmailRequest>> for ResendBatchEmailRequest {
+    fn from(emails: Vec<ResendEmailRequest>) -> Self {
+        Self {
+            emails,
+            idempotency_key: None,
+        }
+    }
+}
+
+impl From<EmailMessage> for ResendEmailRequest {
+    fn from(email: EmailMessage) -> Self {
+        let (text, html) = match email.body {
+            Body::Text(text) => (Some(text), None),
+            Body::Html(html) => (None, Some(html)),
+            Body::Multipart { text, html } => (Some(text), Some(html)),
+        };
+
+        let mut metadata = email
+            .metadata
+            .unwrap_or_default()
+            .into_iter()
+            .collect::<Vec<_>>();
+        metadata.sort_unstable();
+        let tags = email
+            .tag
+            .map(|tag| (ResendTag::TAG.to_owned(), tag))
+            .into_iter()
+            .chain(metadata)
+            .map(|(name, value)| ResendTag { name, value })
+            .collect::<Vec<_>>();
+
+        Self {
+            from: email.from,
+            to: email.to.into_inner(),
+            subject: email.subject,
+            cc: email.cc.map(Recipients::into_inner),
+            bcc: email.bcc.map(Recipients::into_inner),
+            reply_to: email.reply_to.map(Recipients::into_inner),
+            html,
+            text,
+            headers: email.headers.map(|headers| {
+                headers
+                    .into_iter()
+                    .map(|header| (header.name, header.value))
+                    .collect()
+            }),
+            attachments: email
+                .attachments
+                .map(|atts| atts.into_iter().map(Into::into).collect()),
+            tags: Some(tags).filter(|tags| !tags.is_empty()),
+            idempotency_key: email.idempotency_key,
+        }
+    }
+}
+
+impl From<Attachment> for ResendAttachment {
+    fn from(attachment: Attachment) -> Self {
+        Self {
+            content: attachment.content,
+            filename: attachment.name,
+            content_type: Some(attachment.content_type).filter(|value| !value.is_empty()),
+            content_id: attachment
+                .content_id
+                .map(|id| id.strip_prefix("cid:").map(str::to_owned).unwrap_or(id)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::matchers::{eq, none, some};
+    use googletest::{expect_that, gtest};
+    use serde_json::json;
+
+    use super::*;
+
+    fn email_message() -> EmailMessage {
+        EmailMessage {
+            from: Some(Mailbox::new(
+                "Wangari Maathai",
+                "wangari.maathai@example.africa",
+            )),
+            to: vec!["kwame.nkrumah@example.africa"].into(),
+            subject: "Green Belt Movement Monthly Update".to_owned(),
+            body: Body::Multipart {
+                text: "We planted 10,000 trees.".to_owned(),
+                html: "<p>We planted 10,000 trees.</p>".to_owned(),
+            },
+            cc: None,
+            bcc: None,
+            tag: Some("monthly".to_owned()),
+            reply_to: Some(vec!["replies@example.africa"].into()),
+            headers: None,
+            metadata: Some(HashMap::from([
+                ("region".to_owned(), "nyeri".to_owned()),
+                ("campaign".to_owned(), "green-belt".to_owned()),
+            ])),
+            attachments: None,
+            message_stream: Some("outbound".to_owned()),
+            idempotency_key: None,
+        }
+    }
+
+    #[gtest]
+    fn serializes_request() {
+        let request = ResendEmailRequest::from(email_message());
+        let json = serde_json::to_value(&request).expect("serialization to succeed");
+
+        expect_that!(
+            json,
+            eq(&json!({
+                "from": "Wangari Maathai <wangari.maathai@example.africa>",
+                "to": ["kwame.nkrumah@example.africa"],
+                "subject": "Green Belt Movement Monthly Update",
+                "reply_to": ["replies@example.africa"],
+                "html": "<p>We planted 10,000 trees.</p>",
+                "text": "We planted 10,000 trees.",
+                "tags": [
+                    {"name": "tag", "value": "monthly"},
+                    {"name": "campaign", "value": "green-belt"},
+                    {"name": "region", "value": "nyeri"},
+                ],
+            }))
+        );
+    }
+
+    #[gtest]
+    fn takes_the_idempotency_key_of_the_message() {
+        let mut email = email_message();
+        email.idempotency_key = Some("receipt/42".to_owned());
+
+        let request = ResendEmailRequest::from(email);
+        expect_that!(request.idempotency_key(), some(eq("receipt/42")));
+    }
+
+    #[gtest]
+    fn idempotency_key_is_not_serialized() {
+        let mut request = ResendEmailRequest::from(email_message());
+        expect_that!(request.idempotency_key(), none());
+
+        request.idempotency_key = Some("welcome/42".to_owned());
+        let json = serde_json::to_value(&request).expect("serialization to succeed");
+        expect_that!(json.get("idempotency_key"), none());
+        expect_that!(request.idempotency_key(), some(eq("welcome/42")));
+    }
+
+    #[gtest]
+    fn batch_serializes_as_array() {
+        let batch = ResendBatchEmailRequest {
+            emails: vec![ResendEmailRequest::from(email_message())],
+            idempotency_key: Some("batch/1".to_owned()),
+        };
+        let json = serde_json::to_value(&batch).expect("serialization to succeed");
+
+        expect_that!(json.as_array().map(Vec::len), some(eq(1)));
+        expect_that!(batch.idempotency_key(), some(eq("batch/1")));
+    }
+}
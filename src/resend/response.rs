@@ -0,0 +1,239 @@
+//! Resend response types
+use std::time::SystemTime;
+
+use bytes::Bytes;
+use http::{Response, StatusCode};
+use serde::Deserialize;
+
+use crate::datetime::UtcDateTime;
+use crate::email::{EmailDelivery, Mailbox};
+use crate::error::Error;
+
+/// Resend email response
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResendEmailResponse {
+    /// Email ID assigned by Resend
+    pub id: String,
+}
+
+impl ResendEmailResponse {
+    /// Converts the response into a delivery receipt for the given recipients
+    ///
+    /// Resend only returns the email ID, so the submission time is the time
+    /// of the conversion.
+    pub fn into_delivery(self, to: &[Mailbox]) -> EmailDelivery {
+        EmailDelivery {
+            to: to
+                .iter()
+                .map(|mailbox| mailbox.email.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            submitted_at: UtcDateTime::from_system_time(SystemTime::now()).to_iso8601(),
+            message_id: self.id,
+            error_code: 0,
+            message: "OK".to_owned(),
+            provider: None,
+        }
+    }
+}
+
+impl TryFrom<Response<Bytes>> for ResendEmailResponse {
+    type Error = Error;
+
+    fn try_from(response: Response<Bytes>) -> Result<Self, Self::Error> {
+        serde_json::from_slice(response.body())
+            .map_err(|err| Error::UnexpectedResponse(format!("failed to parse response: {err}")))
+    }
+}
+
+/// Resend batch email response
+///
+/// Holds one ID per message, in the same order as the batch request.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResendBatchEmailResponse {
+    /// Created emails
+    pub data: Vec<ResendEmailResponse>,
+}
+
+impl ResendBatchEmailResponse {
+    /// Converts every created email into a delivery receipt
+    ///
+    /// `to` holds the recipients of each email of the batch, in order.
+    pub fn into_results(self, to: &[Vec<Mailbox>]) -> Vec<Result<EmailDelivery, Error>> {
+        if self.data.len() != to.len() {
+            let error = Error::UnexpectedResponse(format!(
+                "batch response has {} emails instead of {}",
+                self.data.len(),
+                to.len()
+            ));
+            return vec![Err(error); to.len()];
+        }
+        self.data
+            .into_iter()
+            .zip(to)
+            .map(|(response, to)| Ok(response.into_delivery(to)))
+            .collect()
+    }
+}
+
+impl TryFrom<Response<Bytes>> for ResendBatchEmailResponse {
+    type Error = Error;
+
+    fn try_from(response: Response<Bytes>) -> Result<Self, Self::Error> {
+        serde_json::from_slice(response.body()).map_err(|err| {
+            Error::UnexpectedResponse(format!("failed to parse batch response: {err}"))
+        })
+    }
+}
+
+/// Resend error response body
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResendErrorResponse {
+    /// Error name, such as `validation_error`
+    #[serde(default)]
+    pub name: String,
+    /// Human readable error message
+    pub message: String,
+}
+
+impl ResendErrorResponse {
+    /// Converts the error into an [`Error::Api`] with the given status
+    pub fn into_error(self, status: StatusCode) -> Error {
+        let message = if self.name.is_empty() {
+            self.message
+        } else {
+            format!("{}: {}", self.name, self.message)
+        };
+        Error::Api {
+            status,
+            error_code: 0,
+            message,
+            class: None,
+        }
+    }
+
+    /// Builds the [`Error`] for a non-success HTTP response
+    pub fn error_from_response(response: &Response<Bytes>) -> Error {
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Error::rate_limited(response.headers());
+        }
+
+        match serde_json::from_slice::<Self>(response.body()) {
+            Ok(error) => error.into_error(status),
+            Err(_err) => {
+                #[cfg(feature = "tracing")]
+                tracing::error!(?_err, %status);
+                Error::unexpected_status(status)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::matchers::{anything, elements_are, eq, len, none, pat};
+    use googletest::{expect_that, gtest};
+
+    use super::*;
+
+    fn response(status: StatusCode, body: &'static str) -> Response<Bytes> {
+        Response::builder()
+            .status(status)
+            .body(Bytes::from_static(body.as_bytes()))
+            .expect("a valid response")
+    }
+
+    #[gtest]
+    fn converts_id_into_delivery() {
+        let response = ResendEmailResponse::try_from(response(
+            StatusCode::OK,
+            r#"{"id": "49a3999c-0ce1-4ea6-ab68-afcd6dc2e794"}"#,
+        ))
+        .expect("response to parse");
+        let delivery = response.into_delivery(&["kwame.nkrumah@example.africa".into()]);
+
+        expect_that!(
+            delivery.message_id,
+            eq("49a3999c-0ce1-4ea6-ab68-afcd6dc2e794")
+        );
+        expect_that!(delivery.to, eq("kwame.nkrumah@example.africa"));
+        expect_that!(delivery.error_code, eq(0));
+    }
+
+    #[gtest]
+    fn batch_ids_pair_with_recipients() {
+        let response = ResendBatchEmailResponse::try_from(response(
+            StatusCode::OK,
+            r#"{"data": [{"id": "first"}, {"id": "second"}]}"#,
+        ))
+        .expect("response to parse");
+        let results = response.into_results(&[
+            vec!["kwame.nkrumah@example.africa".into()],
+            vec!["julius.nyerere@example.africa".into()],
+        ]);
+
+        expect_that!(
+            results,
+            elements_are![
+                pat!(Ok(pat!(EmailDelivery {
+                    message_id: eq("first"),
+                    to: eq("kwame.nkrumah@example.africa"),
+                    ..
+                }))),
+                pat!(Ok(pat!(EmailDelivery {
+                    message_id: eq("second"),
+                    to: eq("julius.nyerere@example.africa"),
+                    ..
+                }))),
+            ]
+        );
+    }
+
+    #[gtest]
+    fn short_batch_responses_fail_every_email() {
+        let response = ResendBatchEmailResponse {
+            data: vec![ResendEmailResponse { id: "first".into() }],
+        };
+        let results = response.into_results(&[Vec::new(), Vec::new()]);
+
+        expect_that!(results, len(eq(2)));
+        expect_that!(
+            results[1],
+            pat!(Err(pat!(Error::UnexpectedResponse(anything()))))
+        );
+    }
+
+    #[gtest]
+    fn error_bodies_are_api_errors() {
+        let error = ResendErrorResponse::error_from_response(&response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            r#"{"statusCode": 422, "name": "validation_error", "message": "Invalid `to` field."}"#,
+        ));
+
+        expect_that!(
+            error,
+            pat!(Error::Api {
+                status: eq(&StatusCode::UNPROCESSABLE_ENTITY),
+                message: eq("validation_error: Invalid `to` field."),
+                ..
+            })
+        );
+    }
+
+    #[gtest]
+    fn unknown_bodies_are_classified_by_status() {
+        let error = ResendErrorResponse::error_from_response(&response(
+            StatusCode::BAD_GATEWAY,
+            "<html>Bad Gateway</html>",
+        ));
+        expect_that!(
+            error,
+            pat!(Error::Api {
+                status: eq(&StatusCode::BAD_GATEWAY),
+                class: none(),
+                ..
+            })
+        );
+    }
+}
@@ -0,0 +1,241 @@
+//! Retrying wrapper around any email service
+//!
+//! Failed sends are retried with exponential backoff and jitter, but only
+//! when the error is classified as retryable.
+use std::hash::{BuildHasher, RandomState};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::error::Error;
+use crate::service::{BatchEmailService, EmailService};
+
+/// Controls how many times and how long to wait between attempts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound for the computed delay
+    pub max_backoff: Duration,
+    /// Upper bound for a delay requested by the provider with `Retry-After`
+    pub max_retry_after: Duration,
+    /// Factor the delay grows by after each attempt
+    pub multiplier: u32,
+    /// Randomize each delay to avoid retrying in lockstep
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            max_retry_after: Duration::from_secs(5 * 60),
+            multiplier: 2,
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns the delay to wait after the given failed attempt
+    ///
+    /// Attempts are numbered from `1`. When the error carries a `Retry-After`
+    /// delay, it takes precedence over the computed backoff, up to
+    /// [`RetryPolicy::max_retry_after`].
+    pub fn delay(&self, attempt: u32, error: &Error) -> Duration {
+        if let Some(retry_after) = error.retry_after() {
+            return retry_after.min(self.max_retry_after);
+        }
+
+        let factor = self
+            .multiplier
+            .checked_pow(attempt.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff);
+
+        if self.jitter {
+            // Equal jitter: keep half the backoff and randomize the other half
+            let half = backoff / 2;
+            let nanos = u64::try_from(half.as_nanos()).unwrap_or(u64::MAX);
+            let random = RandomState::new().hash_one(attempt);
+            half + Duration::from_nanos(random % nanos.saturating_add(1))
+        } else {
+            backoff
+        }
+    }
+
+    /// Returns `true` if another attempt should follow the failed one
+    pub fn should_retry(&self, attempt: u32, error: &Error) -> bool {
+        attempt < self.max_attempts && error.class().is_retryable()
+    }
+}
+
+/// Email service that retries failed sends according to a [`RetryPolicy`]
+#[derive(Debug, Clone)]
+pub struct Retry<S> {
+    /// The wrapped service
+    inner: S,
+    /// When and how often to retry
+    policy: RetryPolicy,
+}
+
+impl<S> Retry<S> {
+    /// Wraps the service with the given retry policy
+    pub const fn new(inner: S, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    /// Returns a reference to the wrapped service
+    pub const fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Returns the retry policy
+    pub const fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Consumes self and returns the wrapped service
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+#[async_trait]
+impl<S, Email, Response> EmailService<Email, Response> for Retry<S>
+where
+    S: EmailService<Email, Response>,
+    Email: Serialize + Clone + Send + Sync + 'static,
+    Response: DeserializeOwned + Send + 'static,
+{
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Retry::send_email", skip(self, email))
+    )]
+    async fn send_email(&self, email: Email) -> Result<Response, Error> {
+        let mut attempt = 1;
+        loop {
+            match self.inner.send_email(email.clone()).await {
+                Err(err) if self.policy.should_retry(attempt, &err) => {
+                    let delay = self.policy.delay(attempt, &err);
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(?err, attempt, ?delay, "retrying email");
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<S, Email, Response> BatchEmailService<Email, Response> for Retry<S>
+where
+    S: BatchEmailService<Email, Response>,
+    Email: Serialize + Clone + Send + Sync + 'static,
+    Response: DeserializeOwned + Send + 'static,
+{
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Retry::send_batch", skip(self, emails))
+    )]
+    async fn send_batch(&self, emails: Vec<Email>) -> Result<Vec<Result<Response, Error>>, Error> {
+        let mut attempt = 1;
+        loop {
+            match self.inner.send_batch(emails.clone()).await {
+                Err(err) if self.policy.should_retry(attempt, &err) => {
+                    let delay = self.policy.delay(attempt, &err);
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(?err, attempt, ?delay, "retrying batch");
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::matchers::{eq, ge, le};
+    use googletest::{expect_that, gtest};
+
+    use super::*;
+
+    fn policy_without_jitter() -> RetryPolicy {
+        RetryPolicy {
+            jitter: false,
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[gtest]
+    fn backoff_grows_exponentially() {
+        let policy = policy_without_jitter();
+        let error = Error::SendFailed("connection failed".into());
+
+        expect_that!(policy.delay(1, &error), eq(Duration::from_millis(500)));
+        expect_that!(policy.delay(2, &error), eq(Duration::from_secs(1)));
+        expect_that!(policy.delay(3, &error), eq(Duration::from_secs(2)));
+    }
+
+    #[gtest]
+    fn backoff_is_capped() {
+        let policy = policy_without_jitter();
+        let error = Error::SendFailed("connection failed".into());
+
+        expect_that!(policy.delay(64, &error), eq(Duration::from_secs(30)));
+    }
+
+    #[gtest]
+    fn jitter_stays_within_half_and_full_backoff() {
+        let policy = RetryPolicy::default();
+        let error = Error::SendFailed("connection failed".into());
+
+        for _ in 0..32 {
+            let delay = policy.delay(2, &error);
+            expect_that!(delay, ge(Duration::from_millis(500)));
+            expect_that!(delay, le(Duration::from_secs(1)));
+        }
+    }
+
+    #[gtest]
+    fn retry_after_takes_precedence() {
+        let policy = RetryPolicy::default();
+        let error = Error::RateLimitExceeded {
+            retry_after: Some(Duration::from_secs(42)),
+        };
+
+        expect_that!(policy.delay(1, &error), eq(Duration::from_secs(42)));
+    }
+
+    #[gtest]
+    fn retry_after_is_capped() {
+        let policy = RetryPolicy::default();
+        let error = Error::RateLimitExceeded {
+            retry_after: Some(Duration::from_secs(u64::MAX)),
+        };
+
+        expect_that!(policy.delay(1, &error), eq(policy.max_retry_after));
+    }
+
+    #[gtest]
+    fn only_retryable_errors_are_retried() {
+        let policy = RetryPolicy::default();
+
+        assert!(policy.should_retry(1, &Error::SendFailed("timeout".into())));
+        assert!(!policy.should_retry(1, &Error::InvalidRecipient("nobody".into())));
+        assert!(!policy.should_retry(3, &Error::SendFailed("timeout".into())));
+    }
+}
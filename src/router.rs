@@ -0,0 +1,448 @@
+//! Routing emails between several backends
+//!
+//! A [`Router`] asks its rules, in order, which backend should send each
+//! email. Rules can split traffic by weight or match on the message stream,
+//! tag, recipient domain or metadata, and custom rules implement
+//! [`RoutingRule`].
+use std::fmt;
+use std::hash::{BuildHasher, RandomState};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+
+use crate::email::{EmailDelivery, EmailMessage};
+use crate::error::Error;
+use crate::service::{BoxEmailService, DynEmailService, EmailService};
+
+/// Picks the backend for an email
+pub trait RoutingRule: Send + Sync {
+    /// Returns the name of the backend for the email
+    ///
+    /// Returning `None` leaves the decision to the next rule.
+    fn route(&self, email: &EmailMessage) -> Option<String>;
+
+    /// Describes the rule in [`RoutingDecision`]s
+    fn describe(&self) -> String;
+}
+
+/// The backend chosen for an email and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoutingDecision {
+    /// Name of the backend sending the email
+    pub backend: String,
+    /// Description of the rule that chose the backend, `None` for the default
+    pub rule: Option<String>,
+}
+
+impl fmt::Display for RoutingDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.rule {
+            Some(rule) => write!(f, "{} ({rule})", self.backend),
+            None => write!(f, "{} (default)", self.backend),
+        }
+    }
+}
+
+/// A property of an email that a [`MatchRule`] looks for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    /// The message stream is the given one
+    MessageStream(String),
+    /// The tag is the given one
+    Tag(String),
+    /// A To recipient has an address on the domain, ignoring case
+    RecipientDomain(String),
+    /// The metadata has the key, with the value when one is given
+    Metadata {
+        /// Metadata key
+        key: String,
+        /// Expected value, or any value when `None`
+        value: Option<String>,
+    },
+}
+
+impl Condition {
+    /// Returns `true` if the email satisfies the condition
+    pub fn matches(&self, email: &EmailMessage) -> bool {
+        match self {
+            Self::MessageStream(stream) => email.message_stream.as_ref() == Some(stream),
+            Self::Tag(tag) => email.tag.as_ref() == Some(tag),
+            Self::RecipientDomain(domain) => email.to.as_slice().iter().any(|mailbox| {
+                mailbox
+                    .email
+                    .rsplit_once('@')
+                    .is_some_and(|(_, recipient)| recipient.eq_ignore_ascii_case(domain))
+            }),
+            Self::Metadata { key, value } => email
+                .metadata
+                .as_ref()
+                .and_then(|metadata| metadata.get(key))
+                .is_some_and(|found| value.as_ref().is_none_or(|value| value == found)),
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MessageStream(stream) => write!(f, "message stream {stream}"),
+            Self::Tag(tag) => write!(f, "tag {tag}"),
+            Self::RecipientDomain(domain) => write!(f, "recipient domain {domain}"),
+            Self::Metadata { key, value: None } => write!(f, "metadata {key}"),
+            Self::Metadata {
+                key,
+                value: Some(value),
+            } => write!(f, "metadata {key}={value}"),
+        }
+    }
+}
+
+/// Sends the emails satisfying a [`Condition`] to a backend
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchRule {
+    /// What the email must satisfy
+    pub condition: Condition,
+    /// Backend for matching emails
+    pub backend: String,
+}
+
+impl MatchRule {
+    /// Creates a rule sending the emails that satisfy the condition to the backend
+    pub fn new(condition: Condition, backend: impl Into<String>) -> Self {
+        Self {
+            condition,
+            backend: backend.into(),
+        }
+    }
+}
+
+impl RoutingRule for MatchRule {
+    fn route(&self, email: &EmailMessage) -> Option<String> {
+        self.condition.matches(email).then(|| self.backend.clone())
+    }
+
+    fn describe(&self) -> String {
+        self.condition.to_string()
+    }
+}
+
+/// Splits emails randomly between backends in proportion to their weights
+///
+/// With weights of 90 and 10, about nine emails out of ten go to the first
+/// backend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeightedRule {
+    /// Backends and their weights
+    pub backends: Vec<(String, u32)>,
+}
+
+impl WeightedRule {
+    /// Creates a rule splitting emails between the weighted backends
+    pub fn new<I, N>(backends: I) -> Self
+    where
+        I: IntoIterator<Item = (N, u32)>,
+        N: Into<String>,
+    {
+        Self {
+            backends: backends
+                .into_iter()
+                .map(|(name, weight)| (name.into(), weight))
+                .collect(),
+        }
+    }
+
+    /// Returns the backend for a random roll
+    ///
+    /// Returns `None` when every weight is zero.
+    pub fn pick(&self, roll: u64) -> Option<&str> {
+        let total = self
+            .backends
+            .iter()
+            .map(|(_, weight)| u64::from(*weight))
+            .sum::<u64>();
+        if total == 0 {
+            return None;
+        }
+
+        let mut remaining = roll % total;
+        self.backends.iter().find_map(|(name, weight)| {
+            let weight = u64::from(*weight);
+            if remaining < weight {
+                Some(name.as_str())
+            } else {
+                remaining -= weight;
+                None
+            }
+        })
+    }
+}
+
+impl RoutingRule for WeightedRule {
+    fn route(&self, _email: &EmailMessage) -> Option<String> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_nanos());
+        self.pick(RandomState::new().hash_one(nanos))
+            .map(str::to_owned)
+    }
+
+    fn describe(&self) -> String {
+        let weights = self
+            .backends
+            .iter()
+            .map(|(name, weight)| format!("{name}={weight}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("weighted {weights}")
+    }
+}
+
+/// A named backend of a [`Router`]
+struct Backend {
+    /// Name used by the rules
+    name: String,
+    /// The service sending the emails
+    service: BoxEmailService,
+}
+
+/// Email service sending each email through the backend its rules pick
+///
+/// Rules are asked in the order they were added, and the first backend
+/// added sends the emails no rule picks. The chosen backend is recorded in
+/// [`EmailDelivery::provider`].
+#[derive(Default)]
+pub struct Router {
+    /// Backends, the first one being the default
+    backends: Vec<Backend>,
+    /// Rules in order of precedence
+    rules: Vec<Box<dyn RoutingRule>>,
+}
+
+impl fmt::Debug for Router {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rules = self
+            .rules
+            .iter()
+            .map(|rule| rule.describe())
+            .collect::<Vec<_>>();
+        f.debug_struct("Router")
+            .field(
+                "backends",
+                &self
+                    .backends
+                    .iter()
+                    .map(|backend| backend.name.as_str())
+                    .collect::<Vec<_>>(),
+            )
+            .field("rules", &rules)
+            .finish()
+    }
+}
+
+impl Router {
+    /// Creates a [`Router`] without backends or rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a named backend
+    #[must_use]
+    pub fn with_backend<S>(mut self, name: impl Into<String>, service: S) -> Self
+    where
+        S: DynEmailService + 'static,
+    {
+        self.backends.push(Backend {
+            name: name.into(),
+            service: Box::new(service),
+        });
+        self
+    }
+
+    /// Adds a rule after the existing ones
+    #[must_use]
+    pub fn with_rule(mut self, rule: impl RoutingRule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// Picks the backend for the email without sending it
+    pub fn decide(&self, email: &EmailMessage) -> Result<RoutingDecision, Error> {
+        self.pick_backend(email).map(|(_, decision)| decision)
+    }
+
+    /// Picks the backend for the email, returning it with the decision
+    fn pick_backend(&self, email: &EmailMessage) -> Result<(&Backend, RoutingDecision), Error> {
+        let decision = self
+            .rules
+            .iter()
+            .find_map(|rule| {
+                rule.route(email).map(|backend| RoutingDecision {
+                    backend,
+                    rule: Some(rule.describe()),
+                })
+            })
+            .or_else(|| {
+                self.backends.first().map(|backend| RoutingDecision {
+                    backend: backend.name.clone(),
+                    rule: None,
+                })
+            })
+            .ok_or_else(|| Error::ConfigError("no email backend configured".into()))?;
+
+        let backend = self
+            .backends
+            .iter()
+            .find(|backend| backend.name == decision.backend)
+            .ok_or_else(|| {
+                Error::ConfigError(format!("no email backend named {}", decision.backend))
+            })?;
+        Ok((backend, decision))
+    }
+}
+
+#[async_trait]
+impl EmailService<EmailMessage, EmailDelivery> for Router {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Router::send_email", skip(self, email), err(Debug))
+    )]
+    async fn send_email(&self, email: EmailMessage) -> Result<EmailDelivery, Error> {
+        let (backend, decision) = self.pick_backend(&email)?;
+        #[cfg(feature = "tracing")]
+        tracing::info!(%decision, "routing email");
+
+        let mut delivery = backend.service.send(email).await?;
+        delivery.provider = Some(decision.backend);
+        Ok(delivery)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use googletest::matchers::{anything, eq, none, pat, some};
+    use googletest::{expect_that, gtest};
+
+    use super::*;
+    use crate::fixtures::{self, Scripted};
+
+    fn email_message() -> EmailMessage {
+        EmailMessage {
+            to: vec!["kwame.nkrumah@Example.Africa"].into(),
+            tag: Some("monthly".to_owned()),
+            metadata: Some(HashMap::from([("tier".to_owned(), "gold".to_owned())])),
+            message_stream: Some("broadcast".to_owned()),
+            ..fixtures::email_message()
+        }
+    }
+
+    #[gtest]
+    fn conditions_match_the_email() {
+        let email = email_message();
+
+        expect_that!(
+            Condition::MessageStream("broadcast".into()).matches(&email),
+            eq(true)
+        );
+        expect_that!(Condition::Tag("welcome".into()).matches(&email), eq(false));
+        expect_that!(
+            Condition::RecipientDomain("example.africa".into()).matches(&email),
+            eq(true)
+        );
+        expect_that!(
+            Condition::Metadata {
+                key: "tier".into(),
+                value: None
+            }
+            .matches(&email),
+            eq(true)
+        );
+        expect_that!(
+            Condition::Metadata {
+                key: "tier".into(),
+                value: Some("silver".into())
+            }
+            .matches(&email),
+            eq(false)
+        );
+    }
+
+    #[gtest]
+    fn weights_split_the_rolls() {
+        let rule = WeightedRule::new([("postmark-a", 90), ("postmark-b", 10)]);
+
+        expect_that!(rule.pick(0), some(eq("postmark-a")));
+        expect_that!(rule.pick(89), some(eq("postmark-a")));
+        expect_that!(rule.pick(90), some(eq("postmark-b")));
+        expect_that!(rule.pick(199), some(eq("postmark-b")));
+        expect_that!(WeightedRule::new([("postmark-a", 0)]).pick(7), none());
+    }
+
+    #[gtest]
+    fn first_matching_rule_decides() {
+        let router = Router::new()
+            .with_backend("postmark", Scripted::default())
+            .with_backend("ses", Scripted::default())
+            .with_rule(MatchRule::new(Condition::Tag("welcome".into()), "postmark"))
+            .with_rule(MatchRule::new(
+                Condition::MessageStream("broadcast".into()),
+                "ses",
+            ));
+
+        let decision = router.decide(&email_message()).expect("a decision");
+        expect_that!(
+            decision,
+            eq(&RoutingDecision {
+                backend: "ses".to_owned(),
+                rule: Some("message stream broadcast".to_owned()),
+            })
+        );
+        expect_that!(decision.to_string(), eq("ses (message stream broadcast)"));
+    }
+
+    #[gtest]
+    fn falls_back_to_the_first_backend() {
+        let router = Router::new()
+            .with_backend("postmark", Scripted::default())
+            .with_rule(MatchRule::new(Condition::Tag("welcome".into()), "ses"));
+
+        let mut email = email_message();
+        email.tag = None;
+        expect_that!(
+            router.decide(&email),
+            pat!(Ok(pat!(RoutingDecision {
+                backend: eq("postmark"),
+                rule: none(),
+            })))
+        );
+    }
+
+    #[gtest]
+    fn unknown_backends_are_configuration_errors() {
+        let router = Router::new()
+            .with_backend("postmark", Scripted::default())
+            .with_rule(MatchRule::new(Condition::Tag("monthly".into()), "ses"));
+
+        expect_that!(
+            router.decide(&email_message()),
+            pat!(Err(pat!(Error::ConfigError(anything()))))
+        );
+    }
+
+    #[tokio::test]
+    #[gtest]
+    async fn records_the_backend_in_the_delivery() {
+        let router = Router::new()
+            .with_backend("postmark", Scripted::default())
+            .with_backend("ses", Scripted::default())
+            .with_rule(WeightedRule::new([("ses", 1)]));
+
+        let delivery = router
+            .send_email(email_message())
+            .await
+            .expect("email to be sent");
+
+        expect_that!(delivery.provider, some(eq("ses")));
+    }
+}
@@ -0,0 +1,306 @@
+//! Sending emails at a future time
+//!
+//! Scheduled emails are stored in an [`Outbox`] as entries due at their
+//! send time, and an [`OutboxWorker`] sends them once that time comes.
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::clock::{Clock, SystemClock};
+use crate::email::EmailMessage;
+use crate::error::Error;
+use crate::outbox::{Outbox, OutboxEntry, OutboxWorker};
+use crate::service::DynEmailService;
+
+/// Schedules, cancels and reschedules emails by ID
+///
+/// Emails are sent by the worker returned from [`Scheduler::worker`], at
+/// most one poll interval after their send time. An email already picked
+/// up by the worker is sent even if it is cancelled in the meantime, and
+/// rescheduling it has no effect: the worker either completes the entry or
+/// writes back its own retry time, replacing the new send time.
+#[derive(Debug, Clone)]
+pub struct Scheduler<O> {
+    /// Where scheduled emails wait
+    outbox: O,
+    /// Source of the current time
+    clock: Arc<dyn Clock>,
+}
+
+impl<O> Scheduler<O>
+where
+    O: Outbox + Clone,
+{
+    /// Creates a scheduler storing emails in the outbox
+    pub fn new(outbox: O) -> Self {
+        Self {
+            outbox,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Sets the clock deciding when emails are due
+    #[must_use]
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Schedules the email to be sent at the given time
+    ///
+    /// Times in the past send the email on the next pass. Returns `false`
+    /// when an email with the same ID is already scheduled.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Scheduler::schedule", skip(self, id, email), err(Debug))
+    )]
+    pub async fn schedule(
+        &self,
+        id: impl Into<String>,
+        email: EmailMessage,
+        send_at: SystemTime,
+    ) -> Result<bool, Error> {
+        let mut entry = OutboxEntry::new(id, email);
+        entry.next_attempt_at = send_at;
+        self.outbox.enqueue(entry).await
+    }
+
+    /// Returns when the email with the ID will be sent, if it is scheduled
+    pub async fn send_time(&self, id: &str) -> Result<Option<SystemTime>, Error> {
+        let entry = self.outbox.get(id).await?;
+        Ok(entry.map(|entry| entry.next_attempt_at))
+    }
+
+    /// Moves the email with the ID to a new send time
+    ///
+    /// Returns `false` when no email with the ID is waiting to be sent,
+    /// including when the worker sent it since it was looked up. If the
+    /// worker is still sending it, the new time is replaced by the worker's
+    /// retry time, or dropped once the email is sent.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Scheduler::reschedule", skip(self), err(Debug))
+    )]
+    pub async fn reschedule(&self, id: &str, send_at: SystemTime) -> Result<bool, Error> {
+        let Some(mut entry) = self.outbox.get(id).await? else {
+            return Ok(false);
+        };
+        entry.next_attempt_at = send_at;
+        self.outbox.reschedule(entry).await
+    }
+
+    /// Cancels the email with the ID
+    ///
+    /// Returns `false` when no email with the ID is waiting to be sent.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Scheduler::cancel", skip(self), err(Debug))
+    )]
+    pub async fn cancel(&self, id: &str) -> Result<bool, Error> {
+        if self.outbox.get(id).await?.is_none() {
+            return Ok(false);
+        }
+        self.outbox.complete(id).await?;
+        Ok(true)
+    }
+
+    /// Returns the worker sending the scheduled emails through the service
+    ///
+    /// The worker shares the outbox and the clock of the scheduler.
+    pub fn worker<S>(&self, service: S) -> OutboxWorker<O, S>
+    where
+        S: DynEmailService,
+    {
+        OutboxWorker::new(self.outbox.clone(), service).with_clock(Arc::clone(&self.clock))
+    }
+}
+
+cfg_test! {
+    mod tests {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        use googletest::matchers::{eq, none, pat, some};
+        use async_trait::async_trait;
+        use googletest::{expect_that, gtest};
+        use tempfile::TempDir;
+
+        use super::*;
+        use crate::clock::ManualClock;
+        use crate::fixtures::{self, Scripted, temp_outbox};
+        use crate::outbox::FileOutbox;
+
+        /// Nine in the morning UTC, as seconds since the Unix epoch
+        const NINE_AM: Duration = Duration::from_secs(1_791_968_400);
+
+        fn reminder(subject: &str) -> EmailMessage {
+            EmailMessage {
+                subject: subject.to_owned(),
+                ..fixtures::email_message()
+            }
+        }
+
+        /// Creates a scheduler over a new temporary outbox, an hour before nine
+        ///
+        /// The outbox directory is removed once the returned guard is dropped.
+        async fn scheduler() -> (TempDir, Scheduler<FileOutbox>, ManualClock) {
+            let (dir, outbox) = temp_outbox().await;
+            let clock = ManualClock::new(UNIX_EPOCH + NINE_AM - Duration::from_secs(3600));
+            let scheduler = Scheduler::new(outbox).with_clock(clock.clone());
+            (dir, scheduler, clock)
+        }
+
+        #[tokio::test]
+        #[gtest]
+        async fn sends_once_the_time_comes() {
+            let (_dir, scheduler, clock) = scheduler().await;
+            let service = Scripted::default();
+            let worker = scheduler.worker(service.clone());
+            scheduler
+                .schedule(
+                    "reminder/1",
+                    reminder("Planting today"),
+                    UNIX_EPOCH + NINE_AM,
+                )
+                .await
+                .expect("email to be scheduled");
+
+            worker.drain().await.expect("outbox to be drained");
+            expect_that!(service.subjects().len(), eq(0));
+
+            clock.advance(Duration::from_secs(3600));
+            worker.drain().await.expect("outbox to be drained");
+            expect_that!(service.subjects(), eq(&["Planting today"]));
+            expect_that!(scheduler.send_time("reminder/1").await, pat!(Ok(none())));
+        }
+
+        #[tokio::test]
+        #[gtest]
+        async fn cancelled_emails_are_not_sent() {
+            let (_dir, scheduler, clock) = scheduler().await;
+            let service = Scripted::default();
+            scheduler
+                .schedule(
+                    "reminder/1",
+                    reminder("Planting today"),
+                    UNIX_EPOCH + NINE_AM,
+                )
+                .await
+                .expect("email to be scheduled");
+
+            expect_that!(scheduler.cancel("reminder/1").await, pat!(Ok(eq(&true))));
+            expect_that!(scheduler.cancel("reminder/1").await, pat!(Ok(eq(&false))));
+
+            clock.advance(Duration::from_secs(7200));
+            scheduler
+                .worker(service.clone())
+                .drain()
+                .await
+                .expect("outbox to be drained");
+            expect_that!(service.subjects().len(), eq(0));
+        }
+
+        #[tokio::test]
+        #[gtest]
+        async fn rescheduled_emails_move_to_the_new_time() {
+            let (_dir, scheduler, clock) = scheduler().await;
+            let service = Scripted::default();
+            let worker = scheduler.worker(service.clone());
+            scheduler
+                .schedule(
+                    "reminder/1",
+                    reminder("Planting today"),
+                    UNIX_EPOCH + NINE_AM,
+                )
+                .await
+                .expect("email to be scheduled");
+
+            let later = UNIX_EPOCH + NINE_AM + Duration::from_secs(1800);
+            expect_that!(
+                scheduler.reschedule("reminder/1", later).await,
+                pat!(Ok(eq(&true)))
+            );
+            expect_that!(
+                scheduler.send_time("reminder/1").await,
+                pat!(Ok(some(eq(&later))))
+            );
+
+            clock.advance(Duration::from_secs(3600));
+            worker.drain().await.expect("outbox to be drained");
+            expect_that!(service.subjects().len(), eq(0));
+
+            clock.advance(Duration::from_secs(1800));
+            worker.drain().await.expect("outbox to be drained");
+            expect_that!(service.subjects().len(), eq(1));
+        }
+
+        /// Outbox whose entries are sent right after being looked up
+        #[derive(Debug, Clone)]
+        struct SentOnceRead(FileOutbox);
+
+        #[async_trait]
+        impl Outbox for SentOnceRead {
+            async fn enqueue(&self, entry: OutboxEntry) -> Result<bool, Error> {
+                self.0.enqueue(entry).await
+            }
+
+            async fn get(&self, id: &str) -> Result<Option<OutboxEntry>, Error> {
+                let entry = self.0.get(id).await?;
+                self.0.complete(id).await?;
+                Ok(entry)
+            }
+
+            async fn due(&self, now: SystemTime, limit: usize) -> Result<Vec<OutboxEntry>, Error> {
+                self.0.due(now, limit).await
+            }
+
+            async fn complete(&self, id: &str) -> Result<(), Error> {
+                self.0.complete(id).await
+            }
+
+            async fn reschedule(&self, entry: OutboxEntry) -> Result<bool, Error> {
+                self.0.reschedule(entry).await
+            }
+
+            async fn dead_letter(&self, entry: OutboxEntry) -> Result<(), Error> {
+                self.0.dead_letter(entry).await
+            }
+
+            async fn dead_letters(&self) -> Result<Vec<OutboxEntry>, Error> {
+                self.0.dead_letters().await
+            }
+        }
+
+        #[tokio::test]
+        #[gtest]
+        async fn emails_sent_while_rescheduling_stay_sent() {
+            let (_dir, outbox) = temp_outbox().await;
+            let scheduler = Scheduler::new(SentOnceRead(outbox.clone()));
+            scheduler
+                .schedule(
+                    "reminder/1",
+                    reminder("Planting today"),
+                    UNIX_EPOCH + NINE_AM,
+                )
+                .await
+                .expect("email to be scheduled");
+
+            let later = UNIX_EPOCH + NINE_AM + Duration::from_secs(1800);
+            expect_that!(
+                scheduler.reschedule("reminder/1", later).await,
+                pat!(Ok(eq(&false)))
+            );
+            expect_that!(outbox.get("reminder/1").await, pat!(Ok(none())));
+        }
+
+        #[tokio::test]
+        #[gtest]
+        async fn unknown_ids_cannot_be_rescheduled() {
+            let (_dir, scheduler, _clock) = scheduler().await;
+            expect_that!(
+                scheduler
+                    .reschedule("reminder/404", UNIX_EPOCH + NINE_AM)
+                    .await,
+                pat!(Ok(eq(&false)))
+            );
+        }
+    }
+}
@@ -0,0 +1,12 @@
+//! Email sending module with SendGrid
+
+pub mod client;
+pub mod request;
+pub mod response;
+
+#[doc(inline)]
+pub use client::SendGridClient;
+#[doc(inline)]
+pub use request::SendGridEmailRequest;
+#[doc(inline)]
+pub use response::{SendGridEmailResponse, SendGridError, SendGridErrorResponse};
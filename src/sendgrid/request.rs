@@ -0,0 +1,301 @@
+//! SendGrid-specific request types
+use std::collections::HashMap;
+
+#[cfg(feature = "garde")]
+use garde::Validate;
+use http::Method;
+use serde::Serialize;
+
+use crate::api::ApiRequest;
+use crate::email::{Attachment, Body, EmailMessage, Mailbox, Recipients};
+
+/// SendGrid v3 mail send request
+///
+/// The recipients of an [`EmailMessage`] go into a single personalization.
+/// The tag becomes a category, metadata becomes custom arguments, and the
+/// message stream has no SendGrid equivalent so it is dropped.
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "garde", derive(Validate))]
+pub struct SendGridEmailRequest {
+    /// Recipients and per-recipient settings
+    #[cfg_attr(feature = "garde", garde(length(min = 1, max = 1000), dive))]
+    pub personalizations: Vec<SendGridPersonalization>,
+    /// The sender
+    ///
+    /// SendGrid rejects requests without one.
+    #[cfg_attr(feature = "garde", garde(required))]
+    pub from: Option<SendGridAddress>,
+    /// Single Reply-To address
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub reply_to: Option<SendGridAddress>,
+    /// Reply-To addresses, used when there are several
+    #[cfg_attr(feature = "garde", garde(length(max = 1000)))]
+    pub reply_to_list: Option<Vec<SendGridAddress>>,
+    /// Email subject
+    #[cfg_attr(feature = "garde", garde(length(min = 1)))]
+    pub subject: String,
+    /// Message bodies, plain text first
+    #[cfg_attr(feature = "garde", garde(length(min = 1)))]
+    pub content: Vec<SendGridContent>,
+    /// File attachments
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub attachments: Option<Vec<SendGridAttachment>>,
+    /// Custom headers
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub headers: Option<HashMap<String, String>>,
+    /// Categories used to group statistics
+    #[cfg_attr(feature = "garde", garde(length(max = 10)))]
+    pub categories: Option<Vec<String>>,
+    /// Custom arguments passed back in event webhooks
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub custom_args: Option<HashMap<String, String>>,
+}
+
+/// Recipients of a SendGrid message
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "garde", derive(Validate))]
+pub struct SendGridPersonalization {
+    /// To recipients
+    #[cfg_attr(feature = "garde", garde(length(min = 1)))]
+    pub to: Vec<SendGridAddress>,
+    /// Cc recipients
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub cc: Option<Vec<SendGridAddress>>,
+    /// Bcc recipients
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub bcc: Option<Vec<SendGridAddress>>,
+}
+
+/// SendGrid email address with an optional display name
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SendGridAddress {
+    /// Email address
+    pub email: String,
+    /// Display name
+    pub name: Option<String>,
+}
+
+/// A body of a SendGrid message
+#[derive(Debug, Clone, Serialize)]
+pub struct SendGridContent {
+    /// MIME type of the body, such as `text/plain`
+    #[serde(rename = "type")]
+    pub content_type: String,
+    /// The body itself
+    pub value: String,
+}
+
+/// SendGrid attachment
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, Serialize)]
+pub struct SendGridAttachment {
+    /// Base64-encoded content
+    pub content: String,
+    /// File name
+    pub filename: String,
+    /// MIME content type
+    #[serde(rename = "type")]
+    pub content_type: Option<String>,
+    /// `attachment`, or `inline` for attachments with a content ID
+    pub disposition: String,
+    /// Content ID referenced from the HTML body
+    pub content_id: Option<String>,
+}
+
+impl ApiRequest for SendGridEmailRequest {
+    const METHOD: Method = Method::POST;
+    const ENDPOINT: &'static str = "/v3/mail/send";
+}
+
+impl From<EmailMessage> for SendGridEmailRequest {
+    fn from(email: EmailMessage) -> Self {
+        let addresses = |recipients: Recipients| {
+            recipients
+                .into_inner()
+                .into_iter()
+                .map(SendGridAddress::from)
+                .collect::<Vec<_>>()
+        };
+        let mut reply_to_list = email.reply_to.map(addresses);
+        let reply_to = match reply_to_list.as_deref() {
+            Some([_]) => reply_to_list
+                .take()
+                .and_then(|list| list.into_iter().next()),
+            _ => None,
+        };
+
+        Self {
+            personalizations: vec![SendGridPersonalization {
+                to: addresses(email.to),
+                cc: email.cc.map(addresses),
+                bcc: email.bcc.map(addresses),
+            }],
+            from: email.from.map(Into::into),
+            reply_to,
+            reply_to_list,
+            subject: email.subject,
+            content: email.body.into(),
+            attachments: email
+                .attachments
+                .map(|atts| atts.into_iter().map(Into::into).collect()),
+            headers: email.headers.map(|headers| {
+                headers
+                    .into_iter()
+                    .map(|header| (header.name, header.value))
+                    .collect()
+            }),
+            categories: email.tag.map(|tag| vec![tag]),
+            custom_args: email.metadata,
+        }
+    }
+}
+
+impl From<Mailbox> for SendGridAddress {
+    fn from(mailbox: Mailbox) -> Self {
+        Self {
+            email: mailbox.email,
+            name: mailbox.name,
+        }
+    }
+}
+
+impl From<Body> for Vec<SendGridContent> {
+    fn from(body: Body) -> Self {
+        let content = |content_type: &str, value| SendGridContent {
+            content_type: content_type.to_owned(),
+            value,
+        };
+        match body {
+            Body::Text(text) => vec![content("text/plain", text)],
+            Body::Html(html) => vec![content("text/html", html)],
+            Body::Multipart { text, html } => {
+                vec![content("text/plain", text), content("text/html", html)]
+            }
+        }
+    }
+}
+
+impl From<Attachment> for SendGridAttachment {
+    fn from(attachment: Attachment) -> Self {
+        let disposition = if attachment.content_id.is_some() {
+            "inline"
+        } else {
+            "attachment"
+        };
+        Self {
+            content: attachment.content,
+            filename: attachment.name,
+            content_type: Some(attachment.content_type).filter(|value| !value.is_empty()),
+            disposition: disposition.to_owned(),
+            content_id: attachment
+                .content_id
+                .map(|id| id.strip_prefix("cid:").map(str::to_owned).unwrap_or(id)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::matchers::{eq, none, some};
+    use googletest::{expect_that, gtest};
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::email::Header;
+
+    fn email_message() -> EmailMessage {
+        EmailMessage {
+            from: Some(Mailbox::new(
+                "Wangari Maathai",
+                "wangari.maathai@example.africa",
+            )),
+            to: vec!["kwame.nkrumah@example.africa"].into(),
+            subject: "Green Belt Movement Monthly Update".to_owned(),
+            body: Body::Multipart {
+                text: "We planted 10,000 trees.".to_owned(),
+                html: "<p>We planted 10,000 trees.</p>".to_owned(),
+            },
+            cc: Some(vec!["miriam.makeba@example.africa"].into()),
+            bcc: None,
+            tag: Some("monthly".to_owned()),
+            reply_to: Some(vec!["replies@example.africa"].into()),
+            headers: Some(vec![Header {
+                name: "X-Campaign".to_owned(),
+                value: "green-belt".to_owned(),
+            }]),
+            metadata: Some(HashMap::from([("region".to_owned(), "nyeri".to_owned())])),
+            attachments: None,
+            message_stream: Some("outbound".to_owned()),
+            idempotency_key: None,
+        }
+    }
+
+    #[gtest]
+    fn api_request_posts_to_mail_send() {
+        expect_that!(SendGridEmailRequest::METHOD, eq(Method::POST));
+        expect_that!(SendGridEmailRequest::ENDPOINT, eq("/v3/mail/send"));
+    }
+
+    #[gtest]
+    fn serializes_personalization() {
+        let request = SendGridEmailRequest::from(email_message());
+        let json = serde_json::to_value(&request).expect("serialization to succeed");
+
+        expect_that!(
+            json,
+            eq(&json!({
+                "personalizations": [{
+                    "to": [{"email": "kwame.nkrumah@example.africa"}],
+                    "cc": [{"email": "miriam.makeba@example.africa"}],
+                }],
+                "from": {"email": "wangari.maathai@example.africa", "name": "Wangari Maathai"},
+                "reply_to": {"email": "replies@example.africa"},
+                "subject": "Green Belt Movement Monthly Update",
+                "content": [
+                    {"type": "text/plain", "value": "We planted 10,000 trees."},
+                    {"type": "text/html", "value": "<p>We planted 10,000 trees.</p>"},
+                ],
+                "headers": {"X-Campaign": "green-belt"},
+                "categories": ["monthly"],
+                "custom_args": {"region": "nyeri"},
+            }))
+        );
+    }
+
+    #[gtest]
+    fn several_reply_to_addresses_use_the_list() {
+        let mut email = email_message();
+        email.reply_to = Some(vec!["replies@example.africa", "desk@example.africa"].into());
+        let request = SendGridEmailRequest::from(email);
+
+        expect_that!(request.reply_to, none());
+        expect_that!(request.reply_to_list.as_ref().map(Vec::len), some(eq(2)));
+    }
+
+    #[gtest]
+    fn maps_attachments() {
+        let mut email = email_message();
+        email.attachments = Some(vec![Attachment {
+            name: "logo.png".to_owned(),
+            content: "iVBORw0KGgo=".to_owned(),
+            content_type: "image/png".to_owned(),
+            content_id: Some("cid:logo".to_owned()),
+        }]);
+        let request = SendGridEmailRequest::from(email);
+        let json: Value = serde_json::to_value(&request).expect("serialization to succeed");
+
+        expect_that!(
+            json["attachments"][0],
+            eq(&json!({
+                "content": "iVBORw0KGgo=",
+                "filename": "logo.png",
+                "type": "image/png",
+                "disposition": "inline",
+                "content_id": "logo",
+            }))
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! SendGrid response types
+use std::time::SystemTime;
+
+use bytes::Bytes;
+use http::{Response, StatusCode};
+use serde::Deserialize;
+
+use crate::datetime::UtcDateTime;
+use crate::email::{EmailDelivery, Mailbox};
+use crate::error::Error;
+
+/// Header carrying the ID of an accepted message
+const MESSAGE_ID_HEADER: &str = "x-message-id";
+
+/// SendGrid mail send response
+///
+/// SendGrid answers `202 Accepted` with an empty body, and the message ID
+/// in the `X-Message-Id` header.
+#[derive(Debug, Clone)]
+pub struct SendGridEmailResponse {
+    /// Message ID assigned by SendGrid
+    ///
+    /// `None` when the header is missing. The message was still accepted,
+    /// so this isn't an error.
+    pub message_id: Option<String>,
+}
+
+impl SendGridEmailResponse {
+    /// Converts the response into a delivery receipt for the given recipients
+    ///
+    /// The submission time is the time of the conversion. The message ID is
+    /// empty when SendGrid didn't send one.
+    pub fn into_delivery(self, to: &[Mailbox]) -> EmailDelivery {
+        EmailDelivery {
+            to: to
+                .iter()
+                .map(|mailbox| mailbox.email.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            submitted_at: UtcDateTime::from_system_time(SystemTime::now()).to_iso8601(),
+            message_id: self.message_id.unwrap_or_default(),
+            error_code: 0,
+            message: "OK".to_owned(),
+            provider: None,
+        }
+    }
+}
+
+impl TryFrom<Response<Bytes>> for SendGridEmailResponse {
+    type Error = Error;
+
+    fn try_from(response: Response<Bytes>) -> Result<Self, Self::Error> {
+        let message_id = response
+            .headers()
+            .get(MESSAGE_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned);
+        #[cfg(feature = "tracing")]
+        if message_id.is_none() {
+            tracing::warn!("accepted response has no {MESSAGE_ID_HEADER}");
+        }
+        Ok(Self { message_id })
+    }
+}
+
+/// SendGrid error response body
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SendGridErrorResponse {
+    /// Errors found in the request
+    #[serde(default)]
+    pub errors: Vec<SendGridError>,
+}
+
+/// A single SendGrid error
+#[derive(Debug, Clone, Deserialize)]
+pub struct SendGridError {
+    /// Human readable error message
+    #[serde(default)]
+    pub message: String,
+    /// Request field the error refers to
+    pub field: Option<String>,
+}
+
+impl SendGridErrorResponse {
+    /// Converts the errors into an [`Error::Api`] with the given status
+    ///
+    /// The messages are joined, each prefixed with its field when there is one.
+    pub fn into_error(self, status: StatusCode) -> Error {
+        let message = self
+            .errors
+            .into_iter()
+            .map(|error| match error.field {
+                Some(field) => format!("{field}: {}", error.message),
+                None => error.message,
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        Error::Api {
+            status,
+            error_code: 0,
+            message,
+            class: None,
+        }
+    }
+
+    /// Builds the [`Error`] for a non-success HTTP response
+    pub fn error_from_response(response: &Response<Bytes>) -> Error {
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Error::rate_limited(response.headers());
+        }
+
+        match serde_json::from_slice::<Self>(response.body()) {
+            Ok(error) if !error.errors.is_empty() => error.into_error(status),
+            _ => {
+                #[cfg(feature = "tracing")]
+                tracing::error!(%status, "unexpected SendGrid error body");
+                Error::unexpected_status(status)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::matchers::{eq, none, pat};
+    use googletest::{expect_that, gtest};
+
+    use super::*;
+
+    fn response(status: StatusCode, body: &'static str) -> Response<Bytes> {
+        Response::builder()
+            .status(status)
+            .header("X-Message-Id", "W8c6fGf1Q3y5lQ0tJ8y5bA")
+            .body(Bytes::from_static(body.as_bytes()))
+            .expect("a valid response")
+    }
+
+    #[gtest]
+    fn converts_message_id_into_delivery() {
+        let response = SendGridEmailResponse::try_from(response(StatusCode::ACCEPTED, ""))
+            .expect("response to parse");
+        let delivery = response.into_delivery(&[
+            "kwame.nkrumah@example.africa".into(),
+            "julius.nyerere@example.africa".into(),
+        ]);
+
+        expect_that!(delivery.message_id, eq("W8c6fGf1Q3y5lQ0tJ8y5bA"));
+        expect_that!(
+            delivery.to,
+            eq("kwame.nkrumah@example.africa, julius.nyerere@example.africa")
+        );
+        expect_that!(delivery.error_code, eq(0));
+    }
+
+    #[gtest]
+    fn missing_message_id_is_still_accepted() {
+        let response = Response::builder()
+            .status(StatusCode::ACCEPTED)
+            .body(Bytes::new())
+            .expect("a valid response");
+        let response = SendGridEmailResponse::try_from(response).expect("response to be accepted");
+
+        expect_that!(response.message_id, none());
+        let delivery = response.into_delivery(&[Mailbox::from("kwame.nkrumah@example.africa")]);
+        expect_that!(delivery.message_id, eq(""));
+    }
+
+    #[gtest]
+    fn joins_the_error_array() {
+        let error = SendGridErrorResponse::error_from_response(&response(
+            StatusCode::BAD_REQUEST,
+            r#"{"errors": [
+                {"message": "The from email does not contain a valid address.", "field": "from.email"},
+                {"message": "Invalid API key", "field": null}
+            ]}"#,
+        ));
+
+        expect_that!(
+            error,
+            pat!(Error::Api {
+                status: eq(&StatusCode::BAD_REQUEST),
+                message: eq(
+                    "from.email: The from email does not contain a valid address.; Invalid API key"
+                ),
+                ..
+            })
+        );
+    }
+
+    #[gtest]
+    fn too_many_requests_is_rate_limited() {
+        let error = SendGridErrorResponse::error_from_response(&response(
+            StatusCode::TOO_MANY_REQUESTS,
+            r#"{"errors": [{"message": "too many requests"}]}"#,
+        ));
+        expect_that!(error, pat!(Error::RateLimitExceeded { .. }));
+    }
+
+    #[gtest]
+    fn unknown_bodies_are_classified_by_status() {
+        let error = SendGridErrorResponse::error_from_response(&response(
+            StatusCode::BAD_GATEWAY,
+            "<html>Bad Gateway</html>",
+        ));
+        expect_that!(
+            error,
+            pat!(Error::Api {
+                status: eq(&StatusCode::BAD_GATEWAY),
+                class: none(),
+                ..
+            })
+        );
+    }
+}
@@ -1,12 +1,21 @@
 //! Email service traits
 //!
 //! Defines the main traits for the services a provider may support
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 
+use crate::email::{EmailDelivery, EmailMessage};
 use crate::error::Error;
 
+/// A boxed email service, for combinators that hold several providers
+pub type BoxEmailService = Box<dyn DynEmailService>;
+
+/// A shared email service, for application state
+pub type ArcEmailService = Arc<dyn DynEmailService>;
+
 /// Trait for sending an email with a provider
 #[async_trait]
 pub trait EmailService<Email, Response>: Send + Sync
@@ -18,18 +27,75 @@ where
     async fn send_email(&self, email: Email) -> Result<Response, Error>;
 }
 
+/// Trait for sending several emails in a single call to a provider
+#[async_trait]
+pub trait BatchEmailService<Email, Response>: Send + Sync
+where
+    Email: Serialize,
+    Response: DeserializeOwned,
+{
+    /// Send a batch of emails
+    ///
+    /// The outer result fails when the whole batch is rejected. Otherwise,
+    /// each inner result reports the outcome of the email at the same position.
+    async fn send_batch(&self, emails: Vec<Email>) -> Result<Vec<Result<Response, Error>>, Error>;
+}
+
+/// Sends the emails one after the other, for providers without a batch endpoint
+///
+/// Each result reports the outcome of the email at the same position.
+pub async fn send_each<S, Email, Response>(
+    service: &S,
+    emails: Vec<Email>,
+) -> Vec<Result<Response, Error>>
+where
+    S: EmailService<Email, Response> + ?Sized,
+    Email: Serialize,
+    Response: DeserializeOwned,
+{
+    let mut results = Vec::with_capacity(emails.len());
+    for email in emails {
+        results.push(service.send_email(email).await);
+    }
+    results
+}
+
+/// Dyn-compatible email service sending an [`EmailMessage`]
+///
+/// Every [`EmailService<EmailMessage, EmailDelivery>`] implements it, so
+/// backends chosen at runtime can be stored as a [`BoxEmailService`] or an
+/// [`ArcEmailService`].
+#[async_trait]
+pub trait DynEmailService: Send + Sync {
+    /// Send an email
+    async fn send(&self, email: EmailMessage) -> Result<EmailDelivery, Error>;
+}
+
+#[async_trait]
+impl<S> DynEmailService for S
+where
+    S: EmailService<EmailMessage, EmailDelivery>,
+{
+    async fn send(&self, email: EmailMessage) -> Result<EmailDelivery, Error> {
+        self.send_email(email).await
+    }
+}
+
 cfg_test_util! {
-    use std::sync::{Arc, Mutex};
+    use std::sync::Mutex;
+    use std::time::SystemTime;
+
+    use crate::datetime::UtcDateTime;
 
     /// A list of sent emails
-    type Outbox<Email> = Arc<Mutex<Vec<Email>>>;
+    type SentEmails<Email> = Arc<Mutex<Vec<Email>>>;
 
     /// Mock sender that records sent emails
     pub struct MockEmailSender<Email> {
         /// The error to return when failure is expected
         pub failure_error: Option<Error>,
         /// Records every email sent so you can assert on them
-        pub outbox: Outbox<Email>,
+        pub outbox: SentEmails<Email>,
     }
 
     #[async_trait]
@@ -49,6 +115,42 @@ cfg_test_util! {
         }
     }
 
+    #[async_trait]
+    impl<Email> BatchEmailService<Email, ()> for MockEmailSender<Email>
+    where
+        Email: Serialize + Send + Sync,
+    {
+        async fn send_batch(&self, emails: Vec<Email>) -> Result<Vec<Result<(), Error>>, Error> {
+            Ok(send_each(self, emails).await)
+        }
+    }
+
+    #[async_trait]
+    impl DynEmailService for MockEmailSender<EmailMessage> {
+        async fn send(&self, email: EmailMessage) -> Result<EmailDelivery, Error> {
+            if let Some(err) = &self.failure_error {
+                return Err(err.clone());
+            }
+            let to = email
+                .to
+                .as_slice()
+                .iter()
+                .map(|mailbox| mailbox.email.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let mut outbox = self.outbox.lock().expect("unpoisoned mutex");
+            outbox.push(email);
+            Ok(EmailDelivery {
+                to,
+                submitted_at: UtcDateTime::from_system_time(SystemTime::now()).to_iso8601(),
+                message_id: format!("mock-{}", outbox.len()),
+                error_code: 0,
+                message: "OK".to_owned(),
+                provider: None,
+            })
+        }
+    }
+
     impl<Email> Default for MockEmailSender<Email> {
         fn default() -> Self {
             Self {
@@ -99,6 +201,7 @@ cfg_test! {
         use googletest::matchers::{eq, err, ok, anything};
 
         use super::*;
+        use crate::fixtures::email_message;
 
         #[tokio::test]
         #[gtest]
@@ -119,5 +222,28 @@ cfg_test! {
             expect_that!(sender.total_emails_sent(), eq(0));
             insta::assert_yaml_snapshot!(sender.sent_emails());
         }
+
+        #[tokio::test]
+        #[gtest]
+        async fn send_batch_reports_each_email() {
+            let sender = MockEmailSender::new();
+            let res = sender.send_batch(vec!["hi", "there"]).await;
+            expect_that!(res.as_ref().map(Vec::len), ok(eq(2)));
+            expect_that!(sender.total_emails_sent(), eq(2));
+        }
+
+        #[tokio::test]
+        #[gtest]
+        async fn mock_sender_is_a_dyn_email_service() {
+            let sender = MockEmailSender::new();
+            let outbox = Arc::clone(&sender.outbox);
+            let service: ArcEmailService = Arc::new(sender);
+
+            let delivery = service.send(email_message()).await.expect("email to be sent");
+
+            expect_that!(delivery.to, eq("kwame.nkrumah@example.africa"));
+            expect_that!(delivery.message_id, eq("mock-1"));
+            expect_that!(outbox.lock().expect("unpoisoned mutex").len(), eq(1));
+        }
     }
 }
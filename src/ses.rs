@@ -0,0 +1,18 @@
+//! Email sending module with Amazon SES
+//!
+//! Talks to the SES v2 API, signing requests with AWS Signature Version 4.
+
+pub mod client;
+pub mod config;
+pub mod request;
+pub mod response;
+pub mod signing;
+
+#[doc(inline)]
+pub use client::SesClient;
+#[doc(inline)]
+pub use config::SesConfig;
+#[doc(inline)]
+pub use request::SesSendEmailRequest;
+#[doc(inline)]
+pub use response::{SesErrorResponse, SesSendEmailResponse};
@@ -0,0 +1,105 @@
+//! The HTTP client that talks to the Amazon SES v2 API
+use std::time::SystemTime;
+
+use bytes::Bytes;
+use http::{Request, Response};
+use secrecy::ExposeSecret;
+
+#[cfg(feature = "reqwest")]
+pub mod reqwest;
+
+use super::config::SesConfig;
+use super::signing::{SigningParams, sign};
+use crate::api::{ApiRequest, encode_body};
+use crate::email::{EmailMessage, MessageDefaults};
+use crate::error::Error;
+use crate::execute::parse_response;
+use crate::ses::SesErrorResponse;
+
+/// Client for interacting with Amazon SES APIs
+#[derive(Debug)]
+pub struct SesClient<C> {
+    /// SES configuration
+    pub config: SesConfig,
+    /// HTTP Client
+    pub client: C,
+    /// Defaults applied to every [`EmailMessage`] before it is sent
+    pub defaults: MessageDefaults,
+}
+
+impl<C> SesClient<C> {
+    /// Service name used in the signature scope
+    const SIGNING_NAME: &str = "ses";
+
+    /// Creates new [`SesClient`] instance
+    pub const fn new(client: C, config: SesConfig) -> Self {
+        Self {
+            client,
+            config,
+            defaults: MessageDefaults::new(),
+        }
+    }
+
+    /// Sets the defaults applied to outgoing messages
+    #[must_use]
+    pub fn with_defaults(mut self, defaults: MessageDefaults) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    /// Applies [`MessageDefaults::prepare`] with [`SesConfig::from_email`]
+    pub fn prepare_email(&self, email: EmailMessage) -> Result<EmailMessage, Error> {
+        self.defaults.prepare(email, &self.config.from_email)
+    }
+
+    /// Creates new signed HTTP request for the SES API
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "SesClient::new_http_request", skip(self, request), err(Debug))
+    )]
+    pub fn new_http_request<R: ApiRequest>(&self, request: &R) -> Result<Request<Bytes>, Error> {
+        let encoded = encode_body(request)?;
+        let uri = format!("{}{}", self.config.base_url(), R::ENDPOINT);
+
+        let mut request = Request::builder()
+            .method(R::METHOD)
+            .uri(uri)
+            .header("content-type", encoded.content_type)
+            .body(encoded.body)
+            .map_err(|err| {
+                #[cfg(feature = "tracing")]
+                tracing::error!(?err);
+                Error::InvalidRequest(format!("failed to build HTTP request: {err}"))
+            })?;
+        self.sign(&mut request, SystemTime::now())?;
+        Ok(request)
+    }
+
+    /// Signs the request with AWS Signature Version 4 as of the given time
+    pub fn sign(&self, request: &mut Request<Bytes>, time: SystemTime) -> Result<(), Error> {
+        let params = SigningParams {
+            access_key_id: &self.config.access_key_id,
+            secret_access_key: self.config.secret_access_key.expose_secret(),
+            session_token: self
+                .config
+                .session_token
+                .as_ref()
+                .map(ExposeSecret::expose_secret),
+            region: &self.config.region,
+            service: Self::SIGNING_NAME,
+            time,
+        };
+        sign(request, &params)
+    }
+
+    /// Parses an SES HTTP response into the expected type
+    ///
+    /// Non-success statuses become errors, with `429 Too Many Requests`
+    /// mapped to [`Error::RateLimitExceeded`].
+    pub fn parse_response<Res>(response: Response<Bytes>) -> Result<Res, Error>
+    where
+        Res: TryFrom<Response<Bytes>, Error = Error>,
+    {
+        parse_response(response, SesErrorResponse::error_from_response)
+    }
+}
@@ -0,0 +1,62 @@
+//! EmailService and Execute trait implementation for [`SesClient<reqwest::Client>`]
+use async_trait::async_trait;
+use bytes::Bytes;
+use http::Request;
+use reqwest::Client;
+
+use super::SesClient;
+use crate::email::{EmailDelivery, EmailMessage};
+use crate::error::Error;
+use crate::execute::{Execute, send_with_reqwest};
+use crate::service::{BatchEmailService, EmailService, send_each};
+use crate::ses::{SesSendEmailRequest, SesSendEmailResponse};
+
+#[async_trait]
+impl EmailService<EmailMessage, EmailDelivery> for SesClient<Client> {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "SesClient::send_email")
+    )]
+    async fn send_email(&self, email: EmailMessage) -> Result<EmailDelivery, Error> {
+        let ses_request: SesSendEmailRequest = self.prepare_email(email)?.into();
+        let request = self.new_http_request(&ses_request)?;
+
+        let response: SesSendEmailResponse = self.execute(request).await.inspect_err(|_err| {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?_err);
+        })?;
+
+        Ok(response.into_delivery(&ses_request.destination.to_addresses))
+    }
+}
+
+#[async_trait]
+impl BatchEmailService<EmailMessage, EmailDelivery> for SesClient<Client> {
+    /// Sends each email with its own request, as SES has no batch endpoint for them
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "SesClient::send_batch", skip(emails), fields(batch_size = emails.len()))
+    )]
+    async fn send_batch(
+        &self,
+        emails: Vec<EmailMessage>,
+    ) -> Result<Vec<Result<EmailDelivery, Error>>, Error> {
+        Ok(send_each(self, emails).await)
+    }
+}
+
+#[async_trait]
+impl Execute for SesClient<reqwest::Client> {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "SesClient::execute", skip(self, request), err(Debug))
+    )]
+    async fn execute<Req, Res>(&self, request: Req) -> Result<Res, Error>
+    where
+        Req: Into<Request<Bytes>> + Send,
+        Res: TryFrom<http::Response<Bytes>, Error = Error>,
+    {
+        let response = send_with_reqwest(&self.client, request.into()).await?;
+        Self::parse_response(response)
+    }
+}
@@ -0,0 +1,117 @@
+//! Configuration for sending through Amazon SES
+use std::env::VarError;
+
+use secrecy::SecretString;
+
+use crate::config::ServiceConfig;
+use crate::error::Error;
+
+/// Amazon SES configuration data
+#[must_use]
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct SesConfig {
+    /// AWS region hosting the SES account, such as `af-south-1`
+    pub region: String,
+    /// Access key ID of the IAM credentials
+    pub access_key_id: String,
+    /// Secret access key of the IAM credentials
+    pub secret_access_key: SecretString,
+    /// Session token for temporary credentials
+    pub session_token: Option<SecretString>,
+    /// API endpoint overriding the regional one
+    pub base_url: Option<String>,
+    /// The verified sender email address
+    ///
+    /// Used for messages that don't set their own sender.
+    pub from_email: String,
+}
+
+impl SesConfig {
+    /// Environment variable for the AWS region
+    pub const AWS_REGION: &str = "AWS_REGION";
+    /// Environment variable for the access key ID
+    pub const AWS_ACCESS_KEY_ID: &str = "AWS_ACCESS_KEY_ID";
+    /// Environment variable for the secret access key
+    pub const AWS_SECRET_ACCESS_KEY: &str = "AWS_SECRET_ACCESS_KEY";
+    /// Environment variable for the session token
+    pub const AWS_SESSION_TOKEN: &str = "AWS_SESSION_TOKEN";
+
+    /// Creates [`SesConfig`] from the standard AWS environment variables
+    ///
+    /// The sender and endpoint override are read from
+    /// [`ServiceConfig::SENDOUT_FROM_EMAIL`] and
+    /// [`ServiceConfig::SENDOUT_BASE_URL`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use sendout::ses::SesConfig;
+    /// let ses_config = SesConfig::from_env()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "SesConfig::from_env", err(Debug))
+    )]
+    pub fn from_env() -> Result<Self, Error> {
+        let required = |name: &str| {
+            std::env::var(name).map_err(|_err| {
+                #[cfg(feature = "tracing")]
+                tracing::error!(%_err);
+                Error::ConfigError(format!("{name} not set"))
+            })
+        };
+        let optional = |name: &str| match std::env::var(name) {
+            Ok(value) => Ok(Some(value)),
+            Err(VarError::NotPresent) => Ok(None),
+            Err(_err) => {
+                #[cfg(feature = "tracing")]
+                tracing::error!(%_err);
+                Err(Error::ConfigError(format!("{name} not set")))
+            }
+        };
+
+        Ok(Self {
+            region: required(Self::AWS_REGION)?,
+            access_key_id: required(Self::AWS_ACCESS_KEY_ID)?,
+            secret_access_key: required(Self::AWS_SECRET_ACCESS_KEY).map(SecretString::from)?,
+            session_token: optional(Self::AWS_SESSION_TOKEN)?.map(SecretString::from),
+            base_url: optional(ServiceConfig::SENDOUT_BASE_URL)?,
+            from_email: required(ServiceConfig::SENDOUT_FROM_EMAIL)?,
+        })
+    }
+
+    /// Returns the API endpoint, `https://email.<region>.amazonaws.com` unless overridden
+    pub fn base_url(&self) -> String {
+        self.base_url
+            .clone()
+            .unwrap_or_else(|| format!("https://email.{}.amazonaws.com", self.region))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::matchers::eq;
+    use googletest::{expect_that, gtest};
+
+    use super::*;
+
+    #[gtest]
+    fn base_url_defaults_to_the_regional_endpoint() {
+        let mut config = SesConfig {
+            region: "af-south-1".into(),
+            access_key_id: "AKIDEXAMPLE".into(),
+            secret_access_key: SecretString::from("secret"),
+            session_token: None,
+            base_url: None,
+            from_email: "sender@example.africa".into(),
+        };
+        expect_that!(
+            config.base_url(),
+            eq("https://email.af-south-1.amazonaws.com")
+        );
+
+        config.base_url = Some("http://localhost:4566".into());
+        expect_that!(config.base_url(), eq("http://localhost:4566"));
+    }
+}
@@ -0,0 +1,370 @@
+//! Amazon SES v2 request types
+#[cfg(feature = "garde")]
+use garde::Validate;
+use http::Method;
+use serde::Serialize;
+
+use crate::api::ApiRequest;
+use crate::email::{Attachment, Body, EmailMessage, Header, Mailbox, Recipients};
+
+/// Name of the message tag holding [`EmailMessage::tag`]
+const TAG_NAME: &str = "tag";
+
+/// SES v2 `SendEmail` request
+///
+/// Built from an [`EmailMessage`] as a simple message, which SES turns into
+/// MIME itself. The message tag and metadata become SES message tags, and
+/// the message stream selects the configuration set.
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[cfg_attr(feature = "garde", derive(Validate))]
+pub struct SesSendEmailRequest {
+    /// The sender mailbox
+    ///
+    /// SES rejects requests without one.
+    #[cfg_attr(feature = "garde", garde(required, dive))]
+    pub from_email_address: Option<Mailbox>,
+    /// Recipients of the message
+    #[cfg_attr(feature = "garde", garde(dive))]
+    pub destination: SesDestination,
+    /// Reply-To mailboxes
+    #[cfg_attr(feature = "garde", garde(dive))]
+    pub reply_to_addresses: Option<Vec<Mailbox>>,
+    /// Content of the message
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub content: SesContent,
+    /// Tags used to categorize the message in event publishing
+    #[cfg_attr(feature = "garde", garde(skip))]
+    pub email_tags: Option<Vec<SesMessageTag>>,
+    /// Configuration set to send through
+    #[cfg_attr(feature = "garde", garde(length(min = 1)))]
+    pub configuration_set_name: Option<String>,
+}
+
+/// Recipients of an SES message
+///
+/// SES accepts up to 50 recipients across all three lists.
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[cfg_attr(feature = "garde", derive(Validate))]
+pub struct SesDestination {
+    /// To mailboxes
+    #[cfg_attr(feature = "garde", garde(length(min = 1, max = 50), dive))]
+    pub to_addresses: Vec<Mailbox>,
+    /// Cc mailboxes
+    #[cfg_attr(feature = "garde", garde(length(max = 50), dive))]
+    pub cc_addresses: Option<Vec<Mailbox>>,
+    /// Bcc mailboxes
+    #[cfg_attr(feature = "garde", garde(length(max = 50), dive))]
+    pub bcc_addresses: Option<Vec<Mailbox>>,
+}
+
+/// Content of an SES message
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SesContent {
+    /// Message that SES formats
+    pub simple: SesSimpleMessage,
+}
+
+/// Message formatted by SES from its parts
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SesSimpleMessage {
+    /// Subject line
+    pub subject: SesText,
+    /// Text and HTML bodies
+    pub body: SesBody,
+    /// Custom headers
+    pub headers: Option<Vec<SesHeader>>,
+    /// File attachments
+    pub attachments: Option<Vec<SesAttachment>>,
+}
+
+/// Text with its charset
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SesText {
+    /// The text itself
+    pub data: String,
+    /// Charset of the text, always `UTF-8`
+    pub charset: String,
+}
+
+/// Bodies of an SES message, at least one of which is set
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SesBody {
+    /// Plain text body
+    pub text: Option<SesText>,
+    /// HTML body
+    pub html: Option<SesText>,
+}
+
+/// SES custom header
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SesHeader {
+    /// Header name
+    pub name: String,
+    /// Header value
+    pub value: String,
+}
+
+/// SES attachment
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SesAttachment {
+    /// Base64-encoded content
+    pub raw_content: String,
+    /// File name
+    pub file_name: String,
+    /// MIME content type
+    pub content_type: Option<String>,
+    /// `ATTACHMENT`, or `INLINE` for attachments with a content ID
+    pub content_disposition: String,
+    /// Content ID referenced from the HTML body
+    pub content_id: Option<String>,
+}
+
+/// SES message tag
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SesMessageTag {
+    /// Tag name
+    pub name: String,
+    /// Tag value
+    pub value: String,
+}
+
+impl ApiRequest for SesSendEmailRequest {
+    const METHOD: Method = Method::POST;
+    const ENDPOINT: &'static str = "/v2/email/outbound-emails";
+}
+
+impl From<EmailMessage> for SesSendEmailRequest {
+    fn from(email: EmailMessage) -> Self {
+        let tags = email
+            .tag
+            .map(|tag| SesMessageTag {
+                name: TAG_NAME.to_owned(),
+                value: tag,
+            })
+            .into_iter()
+            .chain(
+                email
+                    .metadata
+                    .into_iter()
+                    .flatten()
+                    .map(|(name, value)| SesMessageTag { name, value }),
+            )
+            .collect::<Vec<_>>();
+
+        Self {
+            from_email_address: email.from,
+            destination: SesDestination {
+                to_addresses: email.to.into_inner(),
+                cc_addresses: email.cc.map(Recipients::into_inner),
+                bcc_addresses: email.bcc.map(Recipients::into_inner),
+            },
+            reply_to_addresses: email.reply_to.map(Recipients::into_inner),
+            content: SesContent {
+                simple: SesSimpleMessage {
+                    subject: SesText::from(email.subject),
+                    body: email.body.into(),
+                    headers: email
+                        .headers
+                        .map(|headers| headers.into_iter().map(Into::into).collect()),
+                    attachments: email
+                        .attachments
+                        .map(|atts| atts.into_iter().map(Into::into).collect()),
+                },
+            },
+            email_tags: (!tags.is_empty()).then_some(tags),
+            configuration_set_name: email.message_stream,
+        }
+    }
+}
+
+impl From<String> for SesText {
+    fn from(data: String) -> Self {
+        Self {
+            data,
+            charset: "UTF-8".to_owned(),
+        }
+    }
+}
+
+impl From<Body> for SesBody {
+    fn from(body: Body) -> Self {
+        match body {
+            Body::Text(text) => Self {
+                text: Some(text.into()),
+                html: None,
+            },
+            Body::Html(html) => Self {
+                text: None,
+                html: Some(html.into()),
+            },
+            Body::Multipart { text, html } => Self {
+                text: Some(text.into()),
+                html: Some(html.into()),
+            },
+        }
+    }
+}
+
+impl From<Header> for SesHeader {
+    fn from(header: Header) -> Self {
+        Self {
+            name: header.name,
+            value: header.value,
+        }
+    }
+}
+
+impl From<Attachment> for SesAttachment {
+    fn from(attachment: Attachment) -> Self {
+        let content_disposition = if attachment.content_id.is_some() {
+            "INLINE"
+        } else {
+            "ATTACHMENT"
+        };
+        Self {
+            raw_content: attachment.content,
+            file_name: attachment.name,
+            content_type: Some(attachment.content_type).filter(|value| !value.is_empty()),
+            content_disposition: content_disposition.to_owned(),
+            content_id: attachment
+                .content_id
+                .map(|id| id.strip_prefix("cid:").map(str::to_owned).unwrap_or(id)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use googletest::matchers::{eq, none, some};
+    use googletest::{expect_that, gtest};
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    fn email_message() -> EmailMessage {
+        EmailMessage {
+            from: Some("wangari.maathai@example.africa".into()),
+            to: vec!["kwame.nkrumah@example.africa"].into(),
+            subject: "Green Belt Movement Monthly Update".to_owned(),
+            body: Body::Multipart {
+                text: "We planted 10,000 trees.".to_owned(),
+                html: "<p>We planted 10,000 trees.</p>".to_owned(),
+            },
+            cc: None,
+            bcc: Some(vec!["julius.nyerere@example.africa"].into()),
+            tag: Some("monthly".to_owned()),
+            reply_to: None,
+            headers: Some(vec![Header {
+                name: "X-Campaign".to_owned(),
+                value: "green-belt".to_owned(),
+            }]),
+            metadata: Some(HashMap::from([("region".to_owned(), "nyeri".to_owned())])),
+            attachments: None,
+            message_stream: Some("newsletters".to_owned()),
+            idempotency_key: None,
+        }
+    }
+
+    #[gtest]
+    fn api_request_posts_to_outbound_emails() {
+        expect_that!(SesSendEmailRequest::METHOD, eq(Method::POST));
+        expect_that!(
+            SesSendEmailRequest::ENDPOINT,
+            eq("/v2/email/outbound-emails")
+        );
+    }
+
+    #[gtest]
+    fn serializes_simple_message() {
+        let request = SesSendEmailRequest::from(email_message());
+        let json = serde_json::to_value(&request).expect("serialization to succeed");
+
+        expect_that!(
+            json,
+            eq(&json!({
+                "FromEmailAddress": "wangari.maathai@example.africa",
+                "Destination": {
+                    "ToAddresses": ["kwame.nkrumah@example.africa"],
+                    "BccAddresses": ["julius.nyerere@example.africa"],
+                },
+                "Content": {
+                    "Simple": {
+                        "Subject": {
+                            "Data": "Green Belt Movement Monthly Update",
+                            "Charset": "UTF-8",
+                        },
+                        "Body": {
+                            "Text": {"Data": "We planted 10,000 trees.", "Charset": "UTF-8"},
+                            "Html": {"Data": "<p>We planted 10,000 trees.</p>", "Charset": "UTF-8"},
+                        },
+                        "Headers": [{"Name": "X-Campaign", "Value": "green-belt"}],
+                    }
+                },
+                "EmailTags": [
+                    {"Name": "tag", "Value": "monthly"},
+                    {"Name": "region", "Value": "nyeri"},
+                ],
+                "ConfigurationSetName": "newsletters",
+            }))
+        );
+    }
+
+    #[gtest]
+    fn maps_attachments_and_omits_empty_tags() {
+        let mut email = email_message();
+        email.tag = None;
+        email.metadata = None;
+        email.attachments = Some(vec![
+            Attachment {
+                name: "logo.png".to_owned(),
+                content: "iVBORw0KGgo=".to_owned(),
+                content_type: "image/png".to_owned(),
+                content_id: Some("cid:logo".to_owned()),
+            },
+            Attachment {
+                name: "report.pdf".to_owned(),
+                content: "JVBERi0=".to_owned(),
+                content_type: String::new(),
+                content_id: None,
+            },
+        ]);
+        let request = SesSendEmailRequest::from(email);
+        let json: Value = serde_json::to_value(&request).expect("serialization to succeed");
+
+        expect_that!(json.get("EmailTags"), none());
+        let attachments = &json["Content"]["Simple"]["Attachments"];
+        expect_that!(
+            attachments[0],
+            eq(&json!({
+                "RawContent": "iVBORw0KGgo=",
+                "FileName": "logo.png",
+                "ContentType": "image/png",
+                "ContentDisposition": "INLINE",
+                "ContentId": "logo",
+            }))
+        );
+        expect_that!(
+            attachments[1]
+                .get("ContentDisposition")
+                .and_then(Value::as_str),
+            some(eq("ATTACHMENT"))
+        );
+        expect_that!(attachments[1].get("ContentType"), none());
+    }
+}
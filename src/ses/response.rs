@@ -0,0 +1,248 @@
+//! Amazon SES v2 response types
+use std::time::SystemTime;
+
+use bytes::Bytes;
+use http::{Response, StatusCode};
+use serde::Deserialize;
+
+use crate::datetime::UtcDateTime;
+use crate::email::{EmailDelivery, Mailbox};
+use crate::error::Error;
+
+/// Header carrying the error type of failed requests
+const ERROR_TYPE_HEADER: &str = "x-amzn-errortype";
+
+/// SES v2 `SendEmail` response
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SesSendEmailResponse {
+    /// Message ID assigned by SES
+    pub message_id: String,
+}
+
+impl SesSendEmailResponse {
+    /// Converts the response into a delivery receipt for the given recipients
+    ///
+    /// SES only returns the message ID, so the submission time is the time
+    /// of the conversion.
+    pub fn into_delivery(self, to: &[Mailbox]) -> EmailDelivery {
+        EmailDelivery {
+            to: to
+                .iter()
+                .map(|mailbox| mailbox.email.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            submitted_at: UtcDateTime::from_system_time(SystemTime::now()).to_iso8601(),
+            message_id: self.message_id,
+            error_code: 0,
+            message: "OK".to_owned(),
+            provider: None,
+        }
+    }
+}
+
+impl TryFrom<Response<Bytes>> for SesSendEmailResponse {
+    type Error = Error;
+
+    fn try_from(response: Response<Bytes>) -> Result<Self, Self::Error> {
+        serde_json::from_slice(response.body())
+            .map_err(|err| Error::UnexpectedResponse(format!("failed to parse response: {err}")))
+    }
+}
+
+/// SES error response body
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SesErrorResponse {
+    /// Error type, such as `MessageRejected`
+    #[serde(default, rename = "__type", alias = "code", alias = "Code")]
+    pub error_type: String,
+    /// Human readable error message
+    #[serde(default, alias = "Message")]
+    pub message: String,
+}
+
+impl SesErrorResponse {
+    /// Error types returned when sending too fast
+    const THROTTLING: [&str; 4] = [
+        "TooManyRequestsException",
+        "LimitExceededException",
+        "ThrottlingException",
+        "Throttling",
+    ];
+    /// Error types that call for changes to the account or its identities
+    const CONFIGURATION: [&str; 3] = [
+        "AccountSuspendedException",
+        "MailFromDomainNotVerifiedException",
+        "SendingPausedException",
+    ];
+
+    /// Converts the error into an [`Error`]
+    ///
+    /// Throttling becomes [`Error::RateLimitExceeded`], and suspended or
+    /// paused accounts and unverified domains become [`Error::ConfigError`].
+    /// Everything else is an [`Error::Api`] with the given status.
+    pub fn into_error(self, status: StatusCode) -> Error {
+        let error_type = self.error_type.as_str();
+        if status == StatusCode::TOO_MANY_REQUESTS || Self::THROTTLING.contains(&error_type) {
+            return Error::RateLimitExceeded { retry_after: None };
+        }
+
+        let message = if error_type.is_empty() {
+            self.message
+        } else {
+            format!("{error_type}: {}", self.message)
+        };
+        if Self::CONFIGURATION.contains(&error_type) {
+            Error::ConfigError(message)
+        } else {
+            Error::Api {
+                status,
+                error_code: 0,
+                message,
+                class: None,
+            }
+        }
+    }
+
+    /// Builds the [`Error`] for a non-success HTTP response
+    ///
+    /// The error type is read from the `x-amzn-ErrorType` header, or from
+    /// the body when the header is missing.
+    pub fn error_from_response(response: &Response<Bytes>) -> Error {
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Error::rate_limited(response.headers());
+        }
+
+        let mut error = serde_json::from_slice::<Self>(response.body()).unwrap_or_else(|_err| {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?_err, %status);
+            Self::default()
+        });
+        if let Some(error_type) = response
+            .headers()
+            .get(ERROR_TYPE_HEADER)
+            .and_then(|value| value.to_str().ok())
+        {
+            error.error_type = error_type.to_owned();
+        }
+        // Types may look like `MessageRejected:http://...` or `com.amazonaws.ses#MessageRejected`
+        error.error_type = error
+            .error_type
+            .split(':')
+            .next()
+            .and_then(|error_type| error_type.rsplit('#').next())
+            .unwrap_or_default()
+            .to_owned();
+
+        if error.error_type.is_empty() && error.message.is_empty() {
+            return Error::unexpected_status(status);
+        }
+        error.into_error(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::matchers::{anything, eq, none, pat};
+    use googletest::{expect_that, gtest};
+
+    use super::*;
+
+    fn response(
+        status: StatusCode,
+        error_type: Option<&str>,
+        body: &'static str,
+    ) -> Response<Bytes> {
+        let mut builder = Response::builder().status(status);
+        if let Some(error_type) = error_type {
+            builder = builder.header("x-amzn-ErrorType", error_type);
+        }
+        builder
+            .body(Bytes::from_static(body.as_bytes()))
+            .expect("a valid response")
+    }
+
+    #[gtest]
+    fn converts_message_id_into_delivery() {
+        let response = SesSendEmailResponse::try_from(response(
+            StatusCode::OK,
+            None,
+            r#"{"MessageId": "0100018c-ses"}"#,
+        ))
+        .expect("response to parse");
+        let delivery = response.into_delivery(&[
+            "kwame.nkrumah@example.africa".into(),
+            "julius.nyerere@example.africa".into(),
+        ]);
+
+        expect_that!(delivery.message_id, eq("0100018c-ses"));
+        expect_that!(
+            delivery.to,
+            eq("kwame.nkrumah@example.africa, julius.nyerere@example.africa")
+        );
+        expect_that!(delivery.error_code, eq(0));
+    }
+
+    #[gtest]
+    fn rejected_messages_are_api_errors() {
+        let error = SesErrorResponse::error_from_response(&response(
+            StatusCode::BAD_REQUEST,
+            Some("MessageRejected:http://internal.amazon.com/coral/com.amazonaws.ses/"),
+            r#"{"message": "Email address is not verified."}"#,
+        ));
+
+        expect_that!(
+            error,
+            pat!(Error::Api {
+                status: eq(&StatusCode::BAD_REQUEST),
+                message: eq("MessageRejected: Email address is not verified."),
+                ..
+            })
+        );
+    }
+
+    #[gtest]
+    fn throttling_is_rate_limited() {
+        let error = SesErrorResponse::error_from_response(&response(
+            StatusCode::BAD_REQUEST,
+            None,
+            r#"{"__type": "com.amazonaws.ses#LimitExceededException", "message": "slow down"}"#,
+        ));
+        expect_that!(error, pat!(Error::RateLimitExceeded { .. }));
+
+        let error = SesErrorResponse::error_from_response(&response(
+            StatusCode::TOO_MANY_REQUESTS,
+            Some("TooManyRequestsException"),
+            "{}",
+        ));
+        expect_that!(error, pat!(Error::RateLimitExceeded { .. }));
+    }
+
+    #[gtest]
+    fn paused_sending_is_a_configuration_error() {
+        let error = SesErrorResponse::error_from_response(&response(
+            StatusCode::BAD_REQUEST,
+            Some("SendingPausedException"),
+            r#"{"message": "Sending is paused for this account."}"#,
+        ));
+        expect_that!(error, pat!(Error::ConfigError(anything())));
+    }
+
+    #[gtest]
+    fn unknown_bodies_are_classified_by_status() {
+        let error = SesErrorResponse::error_from_response(&response(
+            StatusCode::BAD_GATEWAY,
+            None,
+            "<html>Bad Gateway</html>",
+        ));
+        expect_that!(
+            error,
+            pat!(Error::Api {
+                status: eq(&StatusCode::BAD_GATEWAY),
+                class: none(),
+                ..
+            })
+        );
+    }
+}
@@ -0,0 +1,15 @@
+//! SMTP transport
+//!
+//! Sends messages through an SMTP server or relay instead of an HTTP API.
+//! Messages are rendered with [`MimeRenderer`](crate::mime::MimeRenderer)
+//! and handed over with `MAIL`, `RCPT` and `DATA`, reusing idle connections
+//! across sends.
+pub mod config;
+mod connection;
+mod reply;
+pub mod transport;
+
+#[doc(inline)]
+pub use config::{AuthMechanism, Credentials, Security, SmtpConfig};
+#[doc(inline)]
+pub use transport::SmtpTransport;
@@ -0,0 +1,114 @@
+//! SMTP server settings
+use std::time::Duration;
+
+use secrecy::SecretString;
+
+use crate::email::Mailbox;
+
+/// How the connection to the server is secured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Security {
+    /// Plain TCP, only suitable for a relay on a trusted network
+    None,
+    /// Upgrade a plain connection with `STARTTLS`, usually on port 587
+    ///
+    /// Fails when the server doesn't offer it rather than sending in clear.
+    #[default]
+    StartTls,
+    /// TLS from the start of the connection, usually on port 465
+    Implicit,
+}
+
+/// SASL mechanism used to log in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AuthMechanism {
+    /// `AUTH PLAIN`, sending the credentials in a single step
+    Plain,
+    /// `AUTH LOGIN`, sending the username and password one after the other
+    Login,
+}
+
+impl AuthMechanism {
+    /// Returns the name the server advertises for the mechanism
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Plain => "PLAIN",
+            Self::Login => "LOGIN",
+        }
+    }
+}
+
+/// Username and password used to log in
+#[derive(Debug, Clone)]
+pub struct Credentials {
+    /// Account name
+    pub username: String,
+    /// Account password
+    pub password: SecretString,
+}
+
+impl Credentials {
+    /// Creates credentials from a username and password
+    pub fn new(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            username: username.into(),
+            password: SecretString::from(password.into()),
+        }
+    }
+}
+
+/// SMTP server configuration
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    /// Host name of the server, also used to verify its certificate
+    pub host: String,
+    /// Port of the server
+    pub port: u16,
+    /// How the connection is secured
+    pub security: Security,
+    /// Credentials to log in with, if the server requires it
+    pub credentials: Option<Credentials>,
+    /// Mechanisms to try, in order of preference
+    ///
+    /// The first one the server advertises is used.
+    pub mechanisms: Vec<AuthMechanism>,
+    /// Name this client introduces itself with in `EHLO`
+    pub hello_name: String,
+    /// Sender used for messages that don't set one
+    pub from: Option<Mailbox>,
+    /// Maximum number of idle connections kept for reuse
+    pub max_idle_connections: usize,
+    /// Time limit for connecting and for each reply of the server
+    pub timeout: Duration,
+}
+
+impl SmtpConfig {
+    /// Creates a configuration with default settings for the given server
+    pub fn new(host: impl Into<String>, port: u16, security: Security) -> Self {
+        Self {
+            host: host.into(),
+            port,
+            security,
+            credentials: None,
+            mechanisms: vec![AuthMechanism::Plain, AuthMechanism::Login],
+            hello_name: "localhost".to_owned(),
+            from: None,
+            max_idle_connections: 4,
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Sets the credentials to log in with
+    #[must_use]
+    pub fn with_credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    /// Sets the sender used for messages that don't set one
+    #[must_use]
+    pub fn with_from(mut self, from: impl Into<Mailbox>) -> Self {
+        self.from = Some(from.into());
+        self
+    }
+}
@@ -0,0 +1,476 @@
+//! A single session with an SMTP server
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use base64::prelude::{BASE64_STANDARD, Engine};
+use secrecy::ExposeSecret;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::rustls::pki_types::ServerName;
+
+use super::config::{AuthMechanism, Credentials, Security, SmtpConfig};
+use super::reply::{Reply, parse_line};
+use crate::email::Mailbox;
+use crate::error::Error;
+
+/// Longest reply line accepted from the server, CRLF included
+const MAX_REPLY_LINE: u64 = 4096;
+
+/// Plain or encrypted TCP stream
+enum Stream {
+    /// Unencrypted stream, before `STARTTLS` or without TLS at all
+    Plain(TcpStream),
+    /// TLS stream
+    Tls(Box<TlsStream<TcpStream>>),
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_read(cx, buf),
+            Self::Tls(stream) => Pin::new(stream.as_mut()).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_write(cx, buf),
+            Self::Tls(stream) => Pin::new(stream.as_mut()).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_flush(cx),
+            Self::Tls(stream) => Pin::new(stream.as_mut()).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_shutdown(cx),
+            Self::Tls(stream) => Pin::new(stream.as_mut()).poll_shutdown(cx),
+        }
+    }
+}
+
+/// Service extensions advertised in the `EHLO` reply
+#[derive(Debug, Default)]
+struct Extensions {
+    /// `STARTTLS` is available
+    starttls: bool,
+    /// Commands can be sent in batches, see RFC 2920
+    pipelining: bool,
+    /// Internationalized addresses are accepted, see RFC 6531
+    smtputf8: bool,
+    /// Advertised `AUTH` mechanisms, in upper case
+    auth: Vec<String>,
+}
+
+impl Extensions {
+    /// Reads the extensions from the lines following the greeting
+    fn from_reply(reply: &Reply) -> Self {
+        let mut extensions = Self::default();
+        for line in reply.lines.iter().skip(1) {
+            let mut words = line.split_ascii_whitespace();
+            let Some(keyword) = words.next() else {
+                continue;
+            };
+            match keyword.to_ascii_uppercase().as_str() {
+                "STARTTLS" => extensions.starttls = true,
+                "PIPELINING" => extensions.pipelining = true,
+                "SMTPUTF8" => extensions.smtputf8 = true,
+                "AUTH" => extensions.auth.extend(words.map(str::to_ascii_uppercase)),
+                _ => {}
+            }
+        }
+        extensions
+    }
+}
+
+/// An open, greeted and authenticated connection
+pub struct Connection {
+    /// The buffered stream
+    stream: BufReader<Stream>,
+    /// What the server supports
+    extensions: Extensions,
+    /// Time limit for each reply
+    timeout: Duration,
+    /// Whether the session is in a known state and can send another message
+    reusable: bool,
+}
+
+impl Connection {
+    /// Connects to the server, upgrades to TLS as configured and logs in
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "Connection::open",
+            skip_all,
+            fields(host = %config.host, port = config.port),
+            err(Debug)
+        )
+    )]
+    pub async fn open(config: &SmtpConfig, tls: &TlsConnector) -> Result<Self, Error> {
+        let address = (config.host.as_str(), config.port);
+        let tcp = with_timeout(config.timeout, TcpStream::connect(address)).await?;
+        let stream = match config.security {
+            Security::Implicit => Stream::Tls(Box::new(
+                handshake(tls, &config.host, tcp, config.timeout).await?,
+            )),
+            Security::None | Security::StartTls => Stream::Plain(tcp),
+        };
+
+        let mut connection = Self {
+            stream: BufReader::new(stream),
+            extensions: Extensions::default(),
+            timeout: config.timeout,
+            reusable: true,
+        };
+        connection.read_reply().await?.expect(220)?;
+        connection.hello(&config.hello_name).await?;
+
+        if config.security == Security::StartTls {
+            if !connection.extensions.starttls {
+                return Err(Error::ConfigError(
+                    "SMTP server doesn't offer STARTTLS".into(),
+                ));
+            }
+            connection.command("STARTTLS").await?.expect(220)?;
+            // Anything buffered past the reply was sent in clear and is dropped
+            let stream = match connection.stream.into_inner() {
+                Stream::Plain(tcp) => Stream::Tls(Box::new(
+                    handshake(tls, &config.host, tcp, config.timeout).await?,
+                )),
+                tls_stream @ Stream::Tls(_) => tls_stream,
+            };
+            connection.stream = BufReader::new(stream);
+            connection.hello(&config.hello_name).await?;
+        }
+
+        if let Some(credentials) = &config.credentials {
+            connection
+                .authenticate(credentials, &config.mechanisms)
+                .await?;
+        }
+        Ok(connection)
+    }
+
+    /// Returns `true` if another message can be sent on this connection
+    pub const fn is_reusable(&self) -> bool {
+        self.reusable
+    }
+
+    /// Sends one message to the given recipients
+    ///
+    /// `MAIL`, `RCPT` and `DATA` go out in a single write when the server
+    /// supports pipelining. A failed transaction is reset so the connection
+    /// stays usable. Addresses that could smuggle extra commands are rejected
+    /// before anything is written.
+    pub async fn send(
+        &mut self,
+        from: &str,
+        recipients: &[&str],
+        data: &[u8],
+    ) -> Result<Reply, Error> {
+        for address in std::iter::once(&from).chain(recipients) {
+            check_envelope_address(address)?;
+        }
+        let international = !from.is_ascii() || recipients.iter().any(|rcpt| !rcpt.is_ascii());
+        if international && !self.extensions.smtputf8 {
+            return Err(Error::InvalidRecipient(
+                "SMTP server doesn't accept internationalized addresses".into(),
+            ));
+        }
+
+        let parameters = if international { " SMTPUTF8" } else { "" };
+        let mut commands = vec![format!("MAIL FROM:<{from}>{parameters}")];
+        commands.extend(recipients.iter().map(|rcpt| format!("RCPT TO:<{rcpt}>")));
+        commands.push("DATA".to_owned());
+
+        let mut replies = Vec::with_capacity(commands.len());
+        if self.extensions.pipelining {
+            let batch = commands
+                .iter()
+                .map(|command| format!("{command}\r\n"))
+                .collect::<String>();
+            self.write_all(batch.as_bytes()).await?;
+            for _ in &commands {
+                replies.push(self.read_reply().await?);
+            }
+        } else {
+            for command in &commands {
+                let reply = self.command(command).await?;
+                let accepted = reply.is_positive();
+                replies.push(reply);
+                if !accepted {
+                    break;
+                }
+            }
+        }
+
+        let data_accepted = replies.len() == commands.len()
+            && replies.last().is_some_and(|reply| reply.code == 354);
+        if let Some(idx) = replies.iter().position(|reply| !reply.is_positive()) {
+            if data_accepted {
+                // The server waits for content it must not get, so the
+                // session is abandoned, which discards the transaction
+                self.reusable = false;
+            } else {
+                let _ = self.reset().await;
+            }
+            let reply = replies.swap_remove(idx);
+            return Err(match recipients.get(idx.wrapping_sub(1)) {
+                Some(rcpt) if reply.code >= 500 => {
+                    Error::InvalidRecipient(format!("{rcpt}: {}", reply.message()))
+                }
+                _ => reply.into_error(),
+            });
+        }
+        if !data_accepted {
+            self.reusable = false;
+            return Err(replies.pop().map_or_else(
+                || Error::SendFailed("missing SMTP reply".into()),
+                Reply::into_error,
+            ));
+        }
+
+        self.write_all(&encode_data(data)).await?;
+        let reply = self.read_reply().await?;
+        if reply.code / 100 == 2 {
+            Ok(reply)
+        } else {
+            Err(reply.into_error())
+        }
+    }
+
+    /// Aborts any transaction in progress, checking that the server still answers
+    pub async fn reset(&mut self) -> Result<(), Error> {
+        let result = match self.command("RSET").await {
+            Ok(reply) => reply.expect(250).map(drop),
+            Err(err) => Err(err),
+        };
+        if result.is_err() {
+            self.reusable = false;
+        }
+        result
+    }
+
+    /// Ends the session politely
+    pub async fn quit(mut self) {
+        let _ = self.command("QUIT").await;
+        let _ = self.stream.get_mut().shutdown().await;
+    }
+
+    /// Introduces the client and records the supported extensions
+    async fn hello(&mut self, name: &str) -> Result<(), Error> {
+        let reply = self.command(&format!("EHLO {name}")).await?;
+        if reply.code == 250 {
+            self.extensions = Extensions::from_reply(&reply);
+        } else {
+            // Servers that predate ESMTP only know `HELO`
+            self.command(&format!("HELO {name}")).await?.expect(250)?;
+            self.extensions = Extensions::default();
+        }
+        Ok(())
+    }
+
+    /// Logs in with the first configured mechanism the server offers
+    async fn authenticate(
+        &mut self,
+        credentials: &Credentials,
+        mechanisms: &[AuthMechanism],
+    ) -> Result<(), Error> {
+        let mechanism = mechanisms
+            .iter()
+            .copied()
+            .find(|mechanism| {
+                self.extensions
+                    .auth
+                    .iter()
+                    .any(|offered| offered == mechanism.as_str())
+            })
+            .ok_or_else(|| {
+                Error::ConfigError(
+                    "SMTP server offers none of the configured AUTH mechanisms".into(),
+                )
+            })?;
+
+        let password = credentials.password.expose_secret();
+        match mechanism {
+            AuthMechanism::Plain => {
+                let token =
+                    BASE64_STANDARD.encode(format!("\0{}\0{password}", credentials.username));
+                self.command(&format!("AUTH PLAIN {token}"))
+                    .await?
+                    .expect(235)?;
+            }
+            AuthMechanism::Login => {
+                self.command("AUTH LOGIN").await?.expect(334)?;
+                self.command(&BASE64_STANDARD.encode(&credentials.username))
+                    .await?
+                    .expect(334)?;
+                self.command(&BASE64_STANDARD.encode(password))
+                    .await?
+                    .expect(235)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends a command line and waits for the reply
+    async fn command(&mut self, line: &str) -> Result<Reply, Error> {
+        self.write_all(format!("{line}\r\n").as_bytes()).await?;
+        self.read_reply().await
+    }
+
+    /// Writes and flushes raw bytes
+    async fn write_all(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        let stream = self.stream.get_mut();
+        let write = async {
+            stream.write_all(bytes).await?;
+            stream.flush().await
+        };
+        let result = with_timeout(self.timeout, write).await;
+        if result.is_err() {
+            self.reusable = false;
+        }
+        result
+    }
+
+    /// Reads a complete reply, following continuation lines
+    async fn read_reply(&mut self) -> Result<Reply, Error> {
+        let result = self.read_lines().await;
+        if result.is_err() {
+            self.reusable = false;
+        }
+        result
+    }
+
+    /// Reads reply lines up to the last one
+    async fn read_lines(&mut self) -> Result<Reply, Error> {
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            let mut reader = (&mut self.stream).take(MAX_REPLY_LINE);
+            with_timeout(self.timeout, reader.read_line(&mut line)).await?;
+            if !line.ends_with('\n') {
+                return Err(Error::SendFailed(if line.is_empty() {
+                    "SMTP server closed the connection".into()
+                } else {
+                    "SMTP reply line is too long".into()
+                }));
+            }
+            let (code, last, text) = parse_line(&line)?;
+            lines.push(text.to_owned());
+            if last {
+                return Ok(Reply { code, lines });
+            }
+        }
+    }
+}
+
+/// Runs an I/O future, failing when it takes longer than the timeout
+async fn with_timeout<T>(
+    timeout: Duration,
+    future: impl Future<Output = io::Result<T>>,
+) -> Result<T, Error> {
+    match tokio::time::timeout(timeout, future).await {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(err)) => {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?err);
+            Err(Error::SendFailed(err.to_string()))
+        }
+        Err(_elapsed) => Err(Error::SendFailed("SMTP server timed out".into())),
+    }
+}
+
+/// Performs the TLS handshake, verifying the certificate against the host name
+async fn handshake(
+    tls: &TlsConnector,
+    host: &str,
+    tcp: TcpStream,
+    timeout: Duration,
+) -> Result<TlsStream<TcpStream>, Error> {
+    let server_name = ServerName::try_from(host.to_owned())
+        .map_err(|err| Error::ConfigError(format!("invalid SMTP host name {host}: {err}")))?;
+    with_timeout(timeout, tls.connect(server_name, tcp)).await
+}
+
+/// Checks that an envelope address can't break out of its command
+fn check_envelope_address(address: &str) -> Result<(), Error> {
+    Mailbox::from(address).check_address()
+}
+
+/// Prepares message content for `DATA`
+///
+/// Lines starting with a dot get a second one, and the content ends with
+/// the `.` line marking the end of the message.
+fn encode_data(data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(data.len() + 8);
+    let mut line_start = true;
+    for &byte in data {
+        if line_start && byte == b'.' {
+            output.push(b'.');
+        }
+        output.push(byte);
+        line_start = byte == b'\n';
+    }
+    if !output.is_empty() && !output.ends_with(b"\r\n") {
+        output.extend_from_slice(b"\r\n");
+    }
+    output.extend_from_slice(b".\r\n");
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::matchers::{elements_are, eq};
+    use googletest::{expect_that, gtest};
+
+    use super::*;
+
+    #[gtest]
+    fn stuffs_leading_dots_and_terminates_content() {
+        expect_that!(
+            encode_data(b".hidden\r\nkept.\r\n..two\r\n"),
+            eq(&b"..hidden\r\nkept.\r\n...two\r\n.\r\n".to_vec())
+        );
+        expect_that!(
+            encode_data(b"no newline"),
+            eq(&b"no newline\r\n.\r\n".to_vec())
+        );
+    }
+
+    #[gtest]
+    fn reads_extensions_from_ehlo_reply() {
+        let reply = Reply {
+            code: 250,
+            lines: vec![
+                "smtp.example.africa".to_owned(),
+                "PIPELINING".to_owned(),
+                "auth plain login".to_owned(),
+                "SIZE 10240000".to_owned(),
+            ],
+        };
+        let extensions = Extensions::from_reply(&reply);
+        expect_that!(extensions.pipelining, eq(true));
+        expect_that!(extensions.starttls, eq(false));
+        expect_that!(extensions.auth, elements_are![eq("PLAIN"), eq("LOGIN")]);
+    }
+}
@@ -0,0 +1,99 @@
+//! Replies sent by SMTP servers
+use crate::error::Error;
+
+/// A complete, possibly multiline, reply
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reply {
+    /// Three digit reply code
+    pub code: u16,
+    /// Text of each line, without the code
+    pub lines: Vec<String>,
+}
+
+impl Reply {
+    /// Returns `true` for `2xx` and `3xx` replies
+    pub const fn is_positive(&self) -> bool {
+        self.code >= 200 && self.code < 400
+    }
+
+    /// Returns the text of the reply on a single line
+    pub fn message(&self) -> String {
+        self.lines.join(" ")
+    }
+
+    /// Fails with [`Error::Smtp`] unless the reply has the expected code
+    pub fn expect(self, code: u16) -> Result<Self, Error> {
+        if self.code == code {
+            Ok(self)
+        } else {
+            Err(self.into_error())
+        }
+    }
+
+    /// Converts the reply to an [`Error::Smtp`]
+    pub fn into_error(self) -> Error {
+        Error::Smtp {
+            message: self.message(),
+            code: self.code,
+        }
+    }
+}
+
+/// Splits a reply line into its code, whether it is the last line, and its text
+///
+/// Lines look like `250-PIPELINING`, with `-` marking continuation lines
+/// and a space, or nothing at all, marking the last one.
+pub fn parse_line(line: &str) -> Result<(u16, bool, &str), Error> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    let code = line
+        .get(..3)
+        .filter(|code| code.bytes().all(|byte| byte.is_ascii_digit()))
+        .and_then(|code| code.parse::<u16>().ok())
+        .filter(|code| (200..600).contains(code))
+        .ok_or_else(|| Error::SendFailed(format!("invalid SMTP reply: {line}")))?;
+    match line.as_bytes().get(3) {
+        None => Ok((code, true, "")),
+        Some(b' ') => Ok((code, true, &line[4..])),
+        Some(b'-') => Ok((code, false, &line[4..])),
+        Some(_) => Err(Error::SendFailed(format!("invalid SMTP reply: {line}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::matchers::{anything, eq, err, ok};
+    use googletest::{expect_that, gtest};
+
+    use super::*;
+
+    #[gtest]
+    fn parses_last_and_continuation_lines() {
+        expect_that!(
+            parse_line("250-smtp.example.africa greets you\r\n"),
+            ok(eq(&(250, false, "smtp.example.africa greets you")))
+        );
+        expect_that!(parse_line("250 OK\r\n"), ok(eq(&(250, true, "OK"))));
+        expect_that!(parse_line("354\r\n"), ok(eq(&(354, true, ""))));
+    }
+
+    #[gtest]
+    fn rejects_invalid_lines() {
+        expect_that!(parse_line("hello\r\n"), err(anything()));
+        expect_that!(parse_line("25\r\n"), err(anything()));
+        expect_that!(parse_line("250+OK\r\n"), err(anything()));
+        expect_that!(parse_line("999 OK\r\n"), err(anything()));
+    }
+
+    #[gtest]
+    fn converts_unexpected_replies_to_errors() {
+        let reply = Reply {
+            code: 550,
+            lines: vec!["mailbox".to_owned(), "unavailable".to_owned()],
+        };
+        expect_that!(reply.is_positive(), eq(false));
+        expect_that!(
+            reply.expect(250).map_err(|err| err.to_string()),
+            err(eq("SMTP server replied 550: mailbox unavailable"))
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! [`EmailService`] implementation over SMTP
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use rustls_platform_verifier::BuilderVerifierExt;
+use tokio_rustls::TlsConnector;
+use tokio_rustls::rustls::ClientConfig;
+use tokio_rustls::rustls::crypto::aws_lc_rs;
+
+use super::config::SmtpConfig;
+use super::connection::Connection;
+use crate::datetime::UtcDateTime;
+use crate::email::{EmailDelivery, EmailMessage, MessageDefaults};
+use crate::error::Error;
+use crate::mime::MimeRenderer;
+use crate::mime::render::generate_message_id;
+use crate::service::{BatchEmailService, EmailService, send_each};
+
+/// Sends emails through an SMTP server
+///
+/// Connections are opened on demand and kept for reuse, up to
+/// [`SmtpConfig::max_idle_connections`]. Idle connections are checked with
+/// `RSET` before they send again.
+///
+/// # Examples
+///
+/// ```no_run
+/// use sendout::EmailService;
+/// use sendout::email::{Body, EmailMessage};
+/// use sendout::smtp::{Credentials, Security, SmtpConfig, SmtpTransport};
+///
+/// # async fn run() -> Result<(), sendout::error::Error> {
+/// let config = SmtpConfig::new("smtp.example.africa", 587, Security::StartTls)
+///     .with_credentials(Credentials::new("mailer", "<PASSWORD>"))
+///     .with_from("Thomas Sankara <thomas.sankara@example.africa>".parse::<sendout::email::Mailbox>()?);
+/// let transport = SmtpTransport::new(config)?;
+///
+/// let email = EmailMessage {
+///     from: None,
+///     to: vec!["miriam.makeba@example.africa"].into(),
+///     subject: "Pata Pata".to_owned(),
+///     body: Body::Text("See you at the concert.".to_owned()),
+///     cc: None,
+///     bcc: None,
+///     tag: None,
+///     reply_to: None,
+///     headers: None,
+///     metadata: None,
+///     attachments: None,
+///     message_stream: None,
+///     idempotency_key: None,
+/// };
+/// let delivery = transport.send_email(email).await?;
+/// println!("queued as {}", delivery.message_id);
+/// # Ok(())
+/// # }
+/// ```
+pub struct SmtpTransport {
+    /// Server settings
+    pub config: SmtpConfig,
+    /// Defaults applied to every [`EmailMessage`] before it is sent
+    pub defaults: MessageDefaults,
+    /// Connector used for implicit TLS and `STARTTLS`
+    tls: TlsConnector,
+    /// Connections waiting for the next message
+    idle: Mutex<Vec<Connection>>,
+}
+
+impl SmtpTransport {
+    /// Creates a transport that verifies certificates with the platform's trust store
+    pub fn new(config: SmtpConfig) -> Result<Self, Error> {
+        let tls_config =
+            ClientConfig::builder_with_provider(Arc::new(aws_lc_rs::default_provider()))
+                .with_safe_default_protocol_versions()
+                .and_then(|builder| builder.with_platform_verifier())
+                .map_err(|err| Error::ConfigError(format!("failed to set up TLS: {err}")))?
+                .with_no_client_auth();
+        Ok(Self::with_tls_config(config, Arc::new(tls_config)))
+    }
+
+    /// Creates a transport with a custom TLS configuration
+    ///
+    /// Use it to trust a private certificate authority or present a client
+    /// certificate.
+    pub fn with_tls_config(config: SmtpConfig, tls_config: Arc<ClientConfig>) -> Self {
+        Self {
+            config,
+            defaults: MessageDefaults::new(),
+            tls: TlsConnector::from(tls_config),
+            idle: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Sets the defaults applied to outgoing messages
+    #[must_use]
+    pub fn with_defaults(mut self, defaults: MessageDefaults) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    /// Fills in the sender from [`SmtpConfig::from`] and applies the defaults
+    ///
+    /// Works like [`MessageDefaults::prepare`], except that the sender is
+    /// already a parsed [`Mailbox`] and may be missing, which
+    /// [`SmtpTransport::send_email`] reports.
+    ///
+    /// [`Mailbox`]: crate::email::Mailbox
+    pub fn prepare_email(&self, mut email: EmailMessage) -> EmailMessage {
+        if email.from.is_none() {
+            email.from.clone_from(&self.config.from);
+        }
+        self.defaults.apply(&mut email);
+        email
+    }
+
+    /// Takes a live idle connection, or opens a new one
+    async fn connection(&self) -> Result<Connection, Error> {
+        loop {
+            let idle = self
+                .idle
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .pop();
+            let Some(mut connection) = idle else {
+                break;
+            };
+            if connection.reset().await.is_ok() {
+                return Ok(connection);
+            }
+        }
+        Connection::open(&self.config, &self.tls).await
+    }
+
+    /// Keeps the connection for reuse when there is room, closes it otherwise
+    async fn release(&self, connection: Connection) {
+        if !connection.is_reusable() {
+            return;
+        }
+        let surplus = {
+            let mut idle = self.idle.lock().unwrap_or_else(PoisonError::into_inner);
+            if idle.len() < self.config.max_idle_connections {
+                idle.push(connection);
+                None
+            } else {
+                Some(connection)
+            }
+        };
+        if let Some(connection) = surplus {
+            connection.quit().await;
+        }
+    }
+}
+
+impl fmt::Debug for SmtpTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let idle = self
+            .idle
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len();
+        f.debug_struct("SmtpTransport")
+            .field("config", &self.config)
+            .field("idle_connections", &idle)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl EmailService<EmailMessage, EmailDelivery> for SmtpTransport {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "SmtpTransport::send_email", skip_all, err(Debug))
+    )]
+    async fn send_email(&self, email: EmailMessage) -> Result<EmailDelivery, Error> {
+        let email = self.prepare_email(email);
+        let from = email
+            .from
+            .clone()
+            .ok_or_else(|| Error::ConfigError("no sender set for the message".into()))?;
+        let recipients = [Some(&email.to), email.cc.as_ref(), email.bcc.as_ref()]
+            .into_iter()
+            .flatten()
+            .flat_map(|recipients| recipients.as_slice())
+            .map(|mailbox| mailbox.email.as_str())
+            .collect::<Vec<_>>();
+
+        let date = SystemTime::now();
+        let message_id = generate_message_id(&from);
+        let renderer = MimeRenderer {
+            date: Some(date),
+            message_id: Some(message_id.clone()),
+            include_bcc: false,
+        };
+        let data = renderer.render_to_vec(&email)?;
+
+        let mut connection = self.connection().await?;
+        let result = connection.send(&from.email, &recipients, &data).await;
+        self.release(connection).await;
+        let reply = result?;
+
+        Ok(EmailDelivery {
+            to: email
+                .to
+                .as_slice()
+                .iter()
+                .map(|mailbox| mailbox.email.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            submitted_at: UtcDateTime::from_system_time(date).to_iso8601(),
+            message_id,
+            error_code: 0,
+            message: reply.message(),
+            provider: None,
+        })
+    }
+}
+
+#[async_trait]
+impl BatchEmailService<EmailMessage, EmailDelivery> for SmtpTransport {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "SmtpTransport::send_batch", skip(emails), fields(batch_size = emails.len()))
+    )]
+    async fn send_batch(
+        &self,
+        emails: Vec<EmailMessage>,
+    ) -> Result<Vec<Result<EmailDelivery, Error>>, Error> {
+        Ok(send_each(self, emails).await)
+    }
+}
@@ -0,0 +1,123 @@
+//! Integration with `tower` services
+//!
+//! [`TowerClient`] sends Postmark requests through any HTTP service, so
+//! layers such as timeouts and concurrency limits wrap the API calls.
+//! [`EmailServiceAdapter`] goes the other way and turns an [`EmailService`]
+//! into a service that layers can wrap as a whole.
+use std::fmt;
+use std::future::{Future, poll_fn};
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use http::{Request, Response};
+use serde::Serialize;
+use tower_service::Service;
+
+use crate::email::EmailDelivery;
+use crate::error::Error;
+use crate::service::EmailService;
+
+/// Error type accepted from wrapped services
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// HTTP client backed by a `tower` service
+///
+/// The service is cloned for every request, as usual for `tower` clients,
+/// and its errors become [`Error::SendFailed`].
+#[derive(Debug, Clone)]
+pub struct TowerClient<S> {
+    /// The wrapped HTTP service
+    pub service: S,
+}
+
+impl<S> TowerClient<S> {
+    /// Wraps the HTTP service
+    pub const fn new(service: S) -> Self {
+        Self { service }
+    }
+}
+
+impl<S> TowerClient<S>
+where
+    S: Service<Request<Bytes>, Response = Response<Bytes>> + Clone,
+    S::Error: Into<BoxError>,
+{
+    /// Waits for the service to be ready and sends the request
+    pub async fn call(&self, request: Request<Bytes>) -> Result<Response<Bytes>, Error> {
+        let mut service = self.service.clone();
+        poll_fn(|cx| service.poll_ready(cx))
+            .await
+            .map_err(Self::send_failed)?;
+        service.call(request).await.map_err(Self::send_failed)
+    }
+
+    /// Converts an error of the service
+    fn send_failed(err: S::Error) -> Error {
+        let err = err.into();
+        #[cfg(feature = "tracing")]
+        tracing::error!(?err);
+        Error::SendFailed(err.to_string())
+    }
+}
+
+/// `tower` service sending emails through an [`EmailService`]
+pub struct EmailServiceAdapter<S, Email> {
+    /// The wrapped email service
+    inner: Arc<S>,
+    /// Type of the emails accepted
+    email: PhantomData<fn(Email)>,
+}
+
+impl<S, Email> EmailServiceAdapter<S, Email> {
+    /// Wraps the email service
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            email: PhantomData,
+        }
+    }
+
+    /// Returns a reference to the wrapped service
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+impl<S, Email> Clone for EmailServiceAdapter<S, Email> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            email: PhantomData,
+        }
+    }
+}
+
+impl<S: fmt::Debug, Email> fmt::Debug for EmailServiceAdapter<S, Email> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EmailServiceAdapter")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<S, Email> Service<Email> for EmailServiceAdapter<S, Email>
+where
+    S: EmailService<Email, EmailDelivery> + 'static,
+    Email: Serialize + Send + 'static,
+{
+    type Response = EmailDelivery;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<EmailDelivery, Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, email: Email) -> Self::Future {
+        let inner = Arc::clone(&self.inner);
+        Box::pin(async move { inner.send_email(email).await })
+    }
+}
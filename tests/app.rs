@@ -3,7 +3,6 @@
 use reqwest::Client;
 use reqwest::redirect::Policy;
 use sendout::ServiceConfig;
-use sendout::email::{Body, EmailMessage};
 use uuid::Uuid;
 use wiremock::MockServer;
 
@@ -23,7 +22,7 @@ impl TestApp {
             base_url: email_server.uri(),
             server_token: String::from(Uuid::new_v4()).into(),
             account_token: Some(String::from(Uuid::new_v4()).into()),
-            from_email: "test-user".into(),
+            from_email: "sender@example.africa".into(),
         };
 
         Self {
@@ -37,22 +36,4 @@ impl TestApp {
         let client = Client::builder().redirect(Policy::none()).build()?;
         Ok(client)
     }
-
-    /// Create email message
-    pub fn email_message() -> EmailMessage {
-        EmailMessage {
-            from: "wangari.maathai@example.africa".to_owned(),
-            to: vec!["kwame.nkrumah@example.africa"].into(),
-            subject: "Green Belt Movement Monthly Update".to_owned(),
-            body: Body::Text("We planted 10,000 trees across Kenya this month.".to_owned()),
-            cc: None,
-            bcc: None,
-            tag: None,
-            reply_to: None,
-            headers: None,
-            metadata: None,
-            attachments: None,
-            message_stream: None,
-        }
-    }
 }
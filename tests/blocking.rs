@@ -0,0 +1,101 @@
+//! Integration tests with the blocking Postmark client
+
+#![cfg(all(feature = "postmark", feature = "blocking"))]
+
+mod fixtures;
+
+use std::time::Duration;
+
+use googletest::matchers::{eq, pat, some};
+use googletest::{expect_that, gtest};
+use http::StatusCode;
+use sendout::blocking::EmailService;
+use sendout::config::ServiceConfig;
+use sendout::email::EmailDelivery;
+use sendout::error::Error;
+use sendout::postmark::PostmarkClient;
+use serde_json::json;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Sends the email from a blocking thread, away from the test runtime
+async fn send_email(server: &MockServer) -> Result<EmailDelivery, Error> {
+    let config = ServiceConfig {
+        base_url: server.uri(),
+        server_token: "server-token".to_owned().into(),
+        account_token: None,
+        from_email: "sender@example.africa".into(),
+    };
+    tokio::task::spawn_blocking(move || {
+        let client = PostmarkClient::new(reqwest::blocking::Client::new(), config);
+        client.send_email(fixtures::email_message())
+    })
+    .await
+    .expect("blocking task to complete")
+}
+
+#[tokio::test]
+#[gtest]
+async fn sends_email_without_a_runtime() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/email"))
+        .and(header("X-Postmark-Server-Token", "server-token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "To": "kwame.nkrumah@example.africa",
+            "SubmittedAt": "2026-10-16T08:30:00Z",
+            "MessageID": "msg-abc-123",
+            "ErrorCode": 0,
+            "Message": "OK"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let delivery = send_email(&server).await.expect("email to be sent");
+
+    expect_that!(delivery.message_id, eq("msg-abc-123"));
+}
+
+#[tokio::test]
+#[gtest]
+async fn rate_limits_keep_the_retry_after_header() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "7"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let result = send_email(&server).await;
+
+    expect_that!(
+        result.as_ref().err().and_then(Error::retry_after),
+        some(eq(Duration::from_secs(7)))
+    );
+}
+
+#[tokio::test]
+#[gtest]
+async fn postmark_errors_are_parsed() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+            "ErrorCode": 10,
+            "Message": "No Account or Server API tokens were supplied in the HTTP headers."
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let result = send_email(&server).await;
+
+    expect_that!(
+        result,
+        pat!(Err(pat!(Error::Api {
+            status: eq(&StatusCode::UNAUTHORIZED),
+            error_code: eq(&10),
+            ..
+        })))
+    );
+}
@@ -0,0 +1,22 @@
+//! Messages shared by the integration tests
+
+use sendout::email::{Body, EmailMessage};
+
+/// Creates a plain text email with a sender and a single recipient
+pub fn email_message() -> EmailMessage {
+    EmailMessage {
+        from: Some("wangari.maathai@example.africa".into()),
+        to: vec!["kwame.nkrumah@example.africa"].into(),
+        subject: "Green Belt Movement Monthly Update".to_owned(),
+        body: Body::Text("We planted 10,000 trees across Kenya this month.".to_owned()),
+        cc: None,
+        bcc: None,
+        tag: None,
+        reply_to: None,
+        headers: None,
+        metadata: None,
+        attachments: None,
+        message_stream: None,
+        idempotency_key: None,
+    }
+}
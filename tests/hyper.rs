@@ -0,0 +1,130 @@
+//! Integration tests with the `hyper` backend
+
+#![cfg(all(feature = "postmark", feature = "hyper"))]
+
+mod fixtures;
+
+use std::time::Duration;
+
+use bytes::Bytes;
+use googletest::matchers::{eq, pat, some};
+use googletest::{expect_that, gtest};
+use http::StatusCode;
+use http_body_util::Full;
+use hyper_util::client::legacy::Client;
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::rt::TokioExecutor;
+use sendout::EmailService;
+use sendout::config::ServiceConfig;
+use sendout::error::Error;
+use sendout::postmark::PostmarkClient;
+use serde_json::json;
+use wiremock::matchers::{body_partial_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn postmark_client(base_url: String) -> PostmarkClient<Client<HttpConnector, Full<Bytes>>> {
+    let config = ServiceConfig {
+        base_url,
+        server_token: "server-token".to_owned().into(),
+        account_token: None,
+        from_email: "sender@example.africa".into(),
+    };
+    PostmarkClient::new(Client::builder(TokioExecutor::new()).build_http(), config)
+}
+
+#[tokio::test]
+#[gtest]
+async fn sends_email_through_hyper() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/email"))
+        .and(header("X-Postmark-Server-Token", "server-token"))
+        .and(header("content-type", "application/json"))
+        .and(body_partial_json(json!({
+            "To": "kwame.nkrumah@example.africa",
+            "Subject": "Green Belt Movement Monthly Update",
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "To": "kwame.nkrumah@example.africa",
+            "SubmittedAt": "2026-10-16T08:30:00Z",
+            "MessageID": "msg-abc-123",
+            "ErrorCode": 0,
+            "Message": "OK"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let delivery = postmark_client(server.uri())
+        .send_email(fixtures::email_message())
+        .await
+        .expect("email to be sent");
+
+    expect_that!(delivery.message_id, eq("msg-abc-123"));
+    expect_that!(delivery.error_code, eq(0));
+}
+
+#[tokio::test]
+#[gtest]
+async fn rate_limits_keep_the_retry_after_header() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "7"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let result = postmark_client(server.uri())
+        .send_email(fixtures::email_message())
+        .await;
+
+    expect_that!(
+        result.as_ref().err().and_then(Error::retry_after),
+        some(eq(Duration::from_secs(7)))
+    );
+}
+
+#[tokio::test]
+#[gtest]
+async fn postmark_errors_are_parsed() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/email"))
+        .respond_with(ResponseTemplate::new(422).set_body_json(json!({
+            "ErrorCode": 406,
+            "Message": "You tried to send to a recipient that has been marked as inactive."
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let result = postmark_client(server.uri())
+        .send_email(fixtures::email_message())
+        .await;
+
+    expect_that!(
+        result,
+        pat!(Err(pat!(Error::Api {
+            status: eq(&StatusCode::UNPROCESSABLE_ENTITY),
+            error_code: eq(&406),
+            ..
+        })))
+    );
+}
+
+#[tokio::test]
+#[gtest]
+async fn connection_failures_are_send_failures() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("a free port");
+    let address = listener.local_addr().expect("a local address");
+    drop(listener);
+
+    let result = postmark_client(format!("http://{address}"))
+        .send_email(fixtures::email_message())
+        .await;
+
+    expect_that!(
+        result,
+        pat!(Err(pat!(Error::SendFailed(eq("connection failed")))))
+    );
+}
@@ -0,0 +1,149 @@
+//! Integration tests with the Mailgun client
+
+#![cfg(all(feature = "mailgun", feature = "reqwest"))]
+
+mod app;
+mod fixtures;
+
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use googletest::matchers::{contains_substring, eq, pat, starts_with};
+use googletest::{expect_that, gtest};
+use http::StatusCode;
+use reqwest::Client;
+use secrecy::SecretString;
+use sendout::EmailService;
+use sendout::email::Attachment;
+use sendout::error::Error;
+use sendout::mailgun::{MailgunClient, MailgunConfig, MailgunRegion};
+use serde_json::json;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use app::TestApp;
+
+impl TestApp {
+    /// Creates a Mailgun client talking to the mock server
+    fn mailgun_client(&self) -> MailgunClient<Client> {
+        let reqwest_client = Self::reqwest_client().expect("to create reqwest client");
+        let config = MailgunConfig {
+            api_key: SecretString::from("key-example"),
+            domain: "mg.example.africa".into(),
+            region: MailgunRegion::Eu,
+            base_url: Some(self.email_server.uri()),
+            from_email: self.config.from_email.clone(),
+        };
+        MailgunClient::new(reqwest_client, config)
+    }
+}
+
+#[tokio::test]
+#[gtest]
+async fn send_mail_succeeds() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/v3/mg.example.africa/messages"))
+        .and(header(
+            "authorization",
+            format!("Basic {}", BASE64_STANDARD.encode("api:key-example")).as_str(),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "<20261016.1.ABC@mg.example.africa>",
+            "message": "Queued. Thank you.",
+        })))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let mut message = fixtures::email_message();
+    message.attachments = Some(vec![Attachment {
+        name: "trees.csv".to_owned(),
+        content: BASE64_STANDARD.encode("kenya,10000"),
+        content_type: "text/csv".to_owned(),
+        content_id: None,
+    }]);
+    let delivery = app
+        .mailgun_client()
+        .send_email(message)
+        .await
+        .expect("email to be sent");
+
+    expect_that!(delivery.message_id, eq("20261016.1.ABC@mg.example.africa"));
+    expect_that!(delivery.to, eq("kwame.nkrumah@example.africa"));
+
+    let requests = app
+        .email_server
+        .received_requests()
+        .await
+        .expect("requests to be recorded");
+    let content_type = requests[0]
+        .headers
+        .get("content-type")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    expect_that!(content_type, starts_with("multipart/form-data; boundary="));
+    let body = String::from_utf8_lossy(&requests[0].body).into_owned();
+    expect_that!(
+        body,
+        contains_substring("name=\"to\"\r\n\r\nkwame.nkrumah@example.africa\r\n")
+    );
+    expect_that!(
+        body,
+        contains_substring(
+            "name=\"attachment\"; filename=\"trees.csv\"\r\ncontent-type: text/csv\r\n\r\nkenya,10000\r\n"
+        )
+    );
+}
+
+#[tokio::test]
+#[gtest]
+async fn invalid_request_returns_api_error() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/v3/mg.example.africa/messages"))
+        .respond_with(
+            ResponseTemplate::new(400)
+                .set_body_json(json!({"message": "'to' parameter is not a valid address"})),
+        )
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let result = app
+        .mailgun_client()
+        .send_email(fixtures::email_message())
+        .await;
+
+    expect_that!(
+        result,
+        pat!(Err(pat!(Error::Api {
+            status: eq(&StatusCode::BAD_REQUEST),
+            message: eq("'to' parameter is not a valid address"),
+            ..
+        })))
+    );
+}
+
+#[tokio::test]
+#[gtest]
+async fn too_many_requests_returns_rate_limit_error() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/v3/mg.example.africa/messages"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "2"))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let result = app
+        .mailgun_client()
+        .send_email(fixtures::email_message())
+        .await;
+
+    expect_that!(
+        result,
+        pat!(Err(pat!(Error::RateLimitExceeded {
+            retry_after: eq(&Some(std::time::Duration::from_secs(2)))
+        })))
+    );
+}
@@ -3,6 +3,7 @@
 #![cfg(all(feature = "postmark", feature = "reqwest"))]
 
 mod app;
+mod fixtures;
 #[path = "postmark/mod.rs"]
 mod postmark_tests;
 
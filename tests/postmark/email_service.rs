@@ -1,13 +1,22 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
 use googletest::matchers::eq;
 use googletest::{expect_that, gtest};
+use http::StatusCode;
 use secrecy::ExposeSecret;
-use sendout::EmailService;
+use sendout::email::MessageDefaults;
 use sendout::error::Error;
+use sendout::postmark::{PostmarkTemplate, PostmarkTemplateEmailRequest};
+use sendout::service::ArcEmailService;
+use sendout::{BatchEmailService, EmailService};
 use serde_json::{Value, json};
-use wiremock::matchers::{header, method, path};
+use wiremock::matchers::{body_partial_json, header, method, path};
 use wiremock::{Mock, ResponseTemplate};
 
 use crate::app::TestApp;
+use crate::fixtures;
 
 #[tokio::test]
 #[gtest]
@@ -26,7 +35,7 @@ async fn send_mail_succeeds() {
         .mount(&app.email_server)
         .await;
 
-    let message = TestApp::email_message();
+    let message = fixtures::email_message();
     let email_client = app.postmark_client();
     let delivery = email_client
         .send_email(message)
@@ -36,6 +45,25 @@ async fn send_mail_succeeds() {
     expect_that!(delivery.error_code, eq(0));
 }
 
+#[tokio::test]
+#[gtest]
+async fn send_mail_through_a_dyn_service() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/email"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(email_delivery_receipt()))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let service: ArcEmailService = Arc::new(app.postmark_client());
+    let delivery = service
+        .send(fixtures::email_message())
+        .await
+        .expect("email to be sent");
+    expect_that!(delivery.message_id, eq("msg-abc-123"));
+}
+
 #[tokio::test]
 #[gtest]
 async fn send_email_hit_rate_limit() {
@@ -45,15 +73,254 @@ async fn send_email_hit_rate_limit() {
             "X-Postmark-Server-Token",
             app.config.server_token.expose_secret(),
         ))
-        .respond_with(ResponseTemplate::new(429))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "7"))
         .expect(1)
         .mount(&app.email_server)
         .await;
 
-    let message = TestApp::email_message();
+    let message = fixtures::email_message();
     let email_client = app.postmark_client();
     let result = email_client.send_email(message).await;
-    assert!(matches!(result, Err(Error::RateLimitExceeded)));
+    assert!(matches!(result, Err(Error::RateLimitExceeded { .. })));
+    expect_that!(
+        result.err().and_then(|err| err.retry_after()),
+        eq(Some(Duration::from_secs(7)))
+    );
+}
+
+#[tokio::test]
+#[gtest]
+async fn send_email_rejected_with_postmark_error() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/email"))
+        .respond_with(ResponseTemplate::new(422).set_body_json(json!({
+            "ErrorCode": 406,
+            "Message": "You tried to send to a recipient that has been marked as inactive."
+        })))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let message = fixtures::email_message();
+    let email_client = app.postmark_client();
+    let result = email_client.send_email(message).await;
+    assert!(matches!(
+        result,
+        Err(Error::Api {
+            status: StatusCode::UNPROCESSABLE_ENTITY,
+            error_code: 406,
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+#[gtest]
+async fn send_email_with_invalid_token() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/email"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+            "ErrorCode": 10,
+            "Message": "No Account or Server API tokens were supplied in the HTTP headers."
+        })))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let message = fixtures::email_message();
+    let email_client = app.postmark_client();
+    let result = email_client.send_email(message).await;
+    assert!(matches!(
+        result,
+        Err(Error::Api {
+            status: StatusCode::UNAUTHORIZED,
+            error_code: 10,
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+#[gtest]
+async fn send_email_server_error_without_body() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/email"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let message = fixtures::email_message();
+    let email_client = app.postmark_client();
+    let result = email_client.send_email(message).await;
+    assert!(matches!(
+        result,
+        Err(Error::Api {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+#[gtest]
+async fn send_email_falls_back_to_configured_sender_and_defaults() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/email"))
+        .and(body_partial_json(json!({
+            "From": app.config.from_email,
+            "MessageStream": "outbound",
+            "Tag": "green-belt",
+            "Metadata": { "campaign": "monthly-update" }
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(email_delivery_receipt()))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let mut message = fixtures::email_message();
+    message.from = None;
+    let email_client = app.postmark_client().with_defaults(MessageDefaults {
+        message_stream: Some("outbound".to_owned()),
+        tag: Some("green-belt".to_owned()),
+        headers: Vec::new(),
+        metadata: BTreeMap::from([("campaign".to_owned(), "monthly-update".to_owned())]),
+    });
+    email_client
+        .send_email(message)
+        .await
+        .expect("email to be sent");
+}
+
+#[tokio::test]
+#[gtest]
+async fn send_email_with_invalid_configured_sender() {
+    let mut app = TestApp::spawn().await;
+    app.config.from_email = "not an address".to_owned();
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.email_server)
+        .await;
+
+    let mut message = fixtures::email_message();
+    message.from = None;
+    let email_client = app.postmark_client();
+    let result = email_client.send_email(message).await;
+    assert!(matches!(result, Err(Error::ConfigError(_))));
+}
+
+#[tokio::test]
+#[gtest]
+async fn send_batch_reports_per_message_results() {
+    let app = TestApp::spawn().await;
+    let response_body = json!([
+        email_delivery_receipt(),
+        {
+            "ErrorCode": 406,
+            "Message": "You tried to send to a recipient that has been marked as inactive."
+        }
+    ]);
+
+    Mock::given(method("POST"))
+        .and(path("/email/batch"))
+        .and(header(
+            "X-Postmark-Server-Token",
+            app.config.server_token.expose_secret(),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response_body))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let messages = vec![fixtures::email_message(), fixtures::email_message()];
+    let email_client = app.postmark_client();
+    let results = email_client
+        .send_batch(messages)
+        .await
+        .expect("batch to be accepted");
+
+    expect_that!(results.len(), eq(2));
+    let delivery = results[0].as_ref().expect("first email to be sent");
+    expect_that!(delivery.message_id, eq("msg-abc-123"));
+    assert!(results[1].is_err());
+}
+
+#[tokio::test]
+#[gtest]
+async fn send_batch_over_limit_fails_without_request() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.email_server)
+        .await;
+
+    let messages = vec![fixtures::email_message(); 501];
+    let email_client = app.postmark_client();
+    let result = email_client.send_batch(messages).await;
+    assert!(matches!(result, Err(Error::InvalidRequest(_))));
+}
+
+#[tokio::test]
+#[gtest]
+async fn send_template_email_succeeds() {
+    let app = TestApp::spawn().await;
+
+    Mock::given(method("POST"))
+        .and(path("/email/withTemplate"))
+        .and(body_partial_json(json!({
+            "TemplateAlias": "password-reset",
+            "TemplateModel": { "name": "Kwame" }
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(email_delivery_receipt()))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let request = PostmarkTemplateEmailRequest::new(
+        "wangari.maathai@example.africa",
+        vec!["kwame.nkrumah@example.africa".into()],
+        PostmarkTemplate::TemplateAlias("password-reset".to_owned()),
+        json!({ "name": "Kwame" }),
+    );
+    let email_client = app.postmark_client();
+    let delivery = email_client
+        .send_email(request)
+        .await
+        .expect("email to be sent");
+    expect_that!(delivery.message_id, eq("msg-abc-123"));
+}
+
+#[tokio::test]
+#[gtest]
+async fn send_template_batch_succeeds() {
+    let app = TestApp::spawn().await;
+
+    Mock::given(method("POST"))
+        .and(path("/email/batchWithTemplates"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([email_delivery_receipt()])))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let request = PostmarkTemplateEmailRequest::new(
+        "wangari.maathai@example.africa",
+        vec!["kwame.nkrumah@example.africa".into()],
+        PostmarkTemplate::TemplateId(4_242),
+        json!({ "name": "Kwame" }),
+    );
+    let email_client = app.postmark_client();
+    let results = email_client
+        .send_batch(vec![request])
+        .await
+        .expect("batch to be accepted");
+    expect_that!(results.len(), eq(1));
+    assert!(results[0].is_ok());
 }
 
 fn email_delivery_receipt() -> Value {
@@ -1 +1,3 @@
 mod email_service;
+#[cfg(feature = "retry")]
+mod retry;
@@ -0,0 +1,98 @@
+use googletest::matchers::eq;
+use googletest::{expect_that, gtest};
+use http::StatusCode;
+use sendout::EmailService;
+use sendout::error::Error;
+use sendout::retry::{Retry, RetryPolicy};
+use serde_json::json;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use crate::app::TestApp;
+use crate::fixtures;
+
+fn retry_policy() -> RetryPolicy {
+    RetryPolicy {
+        initial_backoff: std::time::Duration::from_millis(1),
+        ..RetryPolicy::default()
+    }
+}
+
+#[tokio::test]
+#[gtest]
+async fn retries_after_rate_limit() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/email"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/email"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "To": "kwame.nkrumah@example.africa",
+            "SubmittedAt": "2026-02-15T10:00:00Z",
+            "MessageID": "msg-retried",
+            "ErrorCode": 0,
+            "Message": "Ok"
+        })))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let email_client = Retry::new(app.postmark_client(), retry_policy());
+    let delivery = email_client
+        .send_email(fixtures::email_message())
+        .await
+        .expect("email to be sent after retrying");
+    expect_that!(delivery.message_id, eq("msg-retried"));
+}
+
+#[tokio::test]
+#[gtest]
+async fn gives_up_after_max_attempts() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/email"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(3)
+        .mount(&app.email_server)
+        .await;
+
+    let email_client = Retry::new(app.postmark_client(), retry_policy());
+    let result = email_client.send_email(fixtures::email_message()).await;
+    assert!(matches!(
+        result,
+        Err(Error::Api {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+#[gtest]
+async fn does_not_retry_permanent_errors() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/email"))
+        .respond_with(ResponseTemplate::new(422).set_body_json(json!({
+            "ErrorCode": 300,
+            "Message": "Invalid email request"
+        })))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let email_client = Retry::new(app.postmark_client(), retry_policy());
+    let result = email_client.send_email(fixtures::email_message()).await;
+    assert!(matches!(
+        result,
+        Err(Error::Api {
+            error_code: 300,
+            ..
+        })
+    ));
+}
@@ -0,0 +1,194 @@
+//! Integration tests with the Resend client
+
+#![cfg(all(feature = "resend", feature = "reqwest"))]
+
+mod app;
+mod fixtures;
+
+use googletest::matchers::{anything, elements_are, eq, pat};
+use googletest::{expect_that, gtest};
+use http::StatusCode;
+use reqwest::Client;
+use secrecy::ExposeSecret;
+use sendout::email::{Attachment, EmailDelivery, EmailMessage};
+use sendout::error::Error;
+use sendout::resend::{ResendClient, ResendEmailRequest};
+use sendout::{BatchEmailService, EmailService};
+use serde_json::json;
+use wiremock::matchers::{body_partial_json, header, method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use app::TestApp;
+
+impl TestApp {
+    /// Creates a Resend client talking to the mock server
+    fn resend_client(&self) -> ResendClient<Client> {
+        let reqwest_client = Self::reqwest_client().expect("to create reqwest client");
+        ResendClient::new(reqwest_client, self.config.clone())
+    }
+}
+
+#[tokio::test]
+#[gtest]
+async fn send_mail_succeeds() {
+    let app = TestApp::spawn().await;
+    let bearer = format!("Bearer {}", app.config.server_token.expose_secret());
+    Mock::given(method("POST"))
+        .and(path("/emails"))
+        .and(header("authorization", bearer.as_str()))
+        .and(body_partial_json(json!({
+            "from": "wangari.maathai@example.africa",
+            "to": ["kwame.nkrumah@example.africa"],
+            "tags": [{"name": "tag", "value": "monthly"}],
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "49a3999c"})))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let mut message = fixtures::email_message();
+    message.tag = Some("monthly".to_owned());
+    let delivery = app
+        .resend_client()
+        .send_email(message)
+        .await
+        .expect("email to be sent");
+
+    expect_that!(delivery.message_id, eq("49a3999c"));
+    expect_that!(delivery.to, eq("kwame.nkrumah@example.africa"));
+}
+
+#[tokio::test]
+#[gtest]
+async fn sends_the_idempotency_key() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/emails"))
+        .and(header("idempotency-key", "welcome/42"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "49a3999c"})))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let mut request = ResendEmailRequest::from(fixtures::email_message());
+    request.idempotency_key = Some("welcome/42".to_owned());
+    app.resend_client()
+        .send_email(request)
+        .await
+        .expect("email to be sent");
+}
+
+#[tokio::test]
+#[gtest]
+async fn forwards_the_idempotency_key_of_the_message() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/emails"))
+        .and(header("idempotency-key", "receipt/42"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "49a3999c"})))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let mut email = fixtures::email_message();
+    email.idempotency_key = Some("receipt/42".to_owned());
+    EmailService::<EmailMessage, EmailDelivery>::send_email(&app.resend_client(), email)
+        .await
+        .expect("email to be sent");
+}
+
+#[tokio::test]
+#[gtest]
+async fn send_batch_returns_one_delivery_per_email() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/emails/batch"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "data": [{"id": "first"}, {"id": "second"}],
+        })))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let mut second = fixtures::email_message();
+    second.to = vec!["julius.nyerere@example.africa"].into();
+    let results = BatchEmailService::<EmailMessage, EmailDelivery>::send_batch(
+        &app.resend_client(),
+        vec![fixtures::email_message(), second],
+    )
+    .await
+    .expect("batch to be sent");
+
+    expect_that!(
+        results,
+        elements_are![
+            pat!(Ok(pat!(EmailDelivery {
+                message_id: eq("first"),
+                to: eq("kwame.nkrumah@example.africa"),
+                ..
+            }))),
+            pat!(Ok(pat!(EmailDelivery {
+                message_id: eq("second"),
+                to: eq("julius.nyerere@example.africa"),
+                ..
+            }))),
+        ]
+    );
+}
+
+#[tokio::test]
+#[gtest]
+async fn send_batch_rejects_attachments() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/emails/batch"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.email_server)
+        .await;
+
+    let mut second = fixtures::email_message();
+    second.attachments = Some(vec![Attachment {
+        name: "trees.csv".to_owned(),
+        content: "a2VueWEsMTAwMDA=".to_owned(),
+        content_type: "text/csv".to_owned(),
+        content_id: None,
+    }]);
+    let result = BatchEmailService::<EmailMessage, EmailDelivery>::send_batch(
+        &app.resend_client(),
+        vec![fixtures::email_message(), second],
+    )
+    .await;
+
+    expect_that!(result, pat!(Err(pat!(Error::InvalidRequest(anything())))));
+}
+
+#[tokio::test]
+#[gtest]
+async fn validation_error_returns_api_error() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/emails"))
+        .respond_with(ResponseTemplate::new(422).set_body_json(json!({
+            "statusCode": 422,
+            "name": "validation_error",
+            "message": "Invalid `to` field.",
+        })))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let result = app
+        .resend_client()
+        .send_email(fixtures::email_message())
+        .await;
+
+    expect_that!(
+        result,
+        pat!(Err(pat!(Error::Api {
+            status: eq(&StatusCode::UNPROCESSABLE_ENTITY),
+            message: eq("validation_error: Invalid `to` field."),
+            ..
+        })))
+    );
+}
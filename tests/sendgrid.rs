@@ -0,0 +1,137 @@
+//! Integration tests with the SendGrid client
+
+#![cfg(all(feature = "sendgrid", feature = "reqwest"))]
+
+mod app;
+mod fixtures;
+
+use googletest::matchers::{eq, pat};
+use googletest::{expect_that, gtest};
+use http::StatusCode;
+use reqwest::Client;
+use secrecy::ExposeSecret;
+use sendout::EmailService;
+use sendout::error::Error;
+use sendout::sendgrid::SendGridClient;
+use serde_json::json;
+use wiremock::matchers::{body_partial_json, header, method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use app::TestApp;
+
+impl TestApp {
+    /// Creates a SendGrid client talking to the mock server
+    fn sendgrid_client(&self) -> SendGridClient<Client> {
+        let reqwest_client = Self::reqwest_client().expect("to create reqwest client");
+        SendGridClient::new(reqwest_client, self.config.clone())
+    }
+}
+
+#[tokio::test]
+#[gtest]
+async fn send_mail_succeeds() {
+    let app = TestApp::spawn().await;
+    let bearer = format!("Bearer {}", app.config.server_token.expose_secret());
+    Mock::given(method("POST"))
+        .and(path("/v3/mail/send"))
+        .and(header("authorization", bearer.as_str()))
+        .and(body_partial_json(json!({
+            "personalizations": [{"to": [{"email": "kwame.nkrumah@example.africa"}]}],
+            "from": {"email": "wangari.maathai@example.africa"},
+            "content": [{
+                "type": "text/plain",
+                "value": "We planted 10,000 trees across Kenya this month.",
+            }],
+        })))
+        .respond_with(ResponseTemplate::new(202).insert_header("X-Message-Id", "W8c6fGf1Q3y5"))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let delivery = app
+        .sendgrid_client()
+        .send_email(fixtures::email_message())
+        .await
+        .expect("email to be sent");
+
+    expect_that!(delivery.message_id, eq("W8c6fGf1Q3y5"));
+    expect_that!(delivery.to, eq("kwame.nkrumah@example.africa"));
+}
+
+#[tokio::test]
+#[gtest]
+async fn falls_back_to_configured_sender() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/v3/mail/send"))
+        .and(body_partial_json(json!({
+            "from": {"email": app.config.from_email},
+        })))
+        .respond_with(ResponseTemplate::new(202).insert_header("X-Message-Id", "W8c6fGf1Q3y5"))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let mut message = fixtures::email_message();
+    message.from = None;
+    app.sendgrid_client()
+        .send_email(message)
+        .await
+        .expect("email to be sent");
+}
+
+#[tokio::test]
+#[gtest]
+async fn invalid_request_returns_api_error() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/v3/mail/send"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "errors": [{
+                "message": "The subject is required.",
+                "field": "subject",
+                "help": "http://sendgrid.com/docs/API_Reference/Web_API_v3/Mail/errors.html",
+            }],
+        })))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let result = app
+        .sendgrid_client()
+        .send_email(fixtures::email_message())
+        .await;
+
+    expect_that!(
+        result,
+        pat!(Err(pat!(Error::Api {
+            status: eq(&StatusCode::BAD_REQUEST),
+            message: eq("subject: The subject is required."),
+            ..
+        })))
+    );
+}
+
+#[tokio::test]
+#[gtest]
+async fn too_many_requests_returns_rate_limit_error() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/v3/mail/send"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "2"))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let result = app
+        .sendgrid_client()
+        .send_email(fixtures::email_message())
+        .await;
+
+    expect_that!(
+        result,
+        pat!(Err(pat!(Error::RateLimitExceeded {
+            retry_after: eq(&Some(std::time::Duration::from_secs(2)))
+        })))
+    );
+}
@@ -0,0 +1,160 @@
+//! Integration tests with the SES client
+
+#![cfg(all(feature = "ses", feature = "reqwest"))]
+
+mod app;
+mod fixtures;
+
+use googletest::matchers::{eq, pat, starts_with};
+use googletest::{expect_that, gtest};
+use http::StatusCode;
+use reqwest::Client;
+use secrecy::SecretString;
+use sendout::EmailService;
+use sendout::error::Error;
+use sendout::ses::{SesClient, SesConfig};
+use serde_json::json;
+use wiremock::matchers::{body_partial_json, header, header_exists, method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use app::TestApp;
+
+impl TestApp {
+    /// Creates an SES client talking to the mock server
+    fn ses_client(&self) -> SesClient<Client> {
+        let reqwest_client = Self::reqwest_client().expect("to create reqwest client");
+        let config = SesConfig {
+            region: "af-south-1".into(),
+            access_key_id: "AKIDEXAMPLE".into(),
+            secret_access_key: SecretString::from("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY"),
+            session_token: Some(SecretString::from("session-token")),
+            base_url: Some(self.email_server.uri()),
+            from_email: self.config.from_email.clone(),
+        };
+        SesClient::new(reqwest_client, config)
+    }
+}
+
+#[tokio::test]
+#[gtest]
+async fn send_mail_succeeds() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/email/outbound-emails"))
+        .and(header("x-amz-security-token", "session-token"))
+        .and(header_exists("x-amz-date"))
+        .and(body_partial_json(json!({
+            "FromEmailAddress": "wangari.maathai@example.africa",
+            "Destination": {"ToAddresses": ["kwame.nkrumah@example.africa"]},
+        })))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({"MessageId": "0100018c-example"})),
+        )
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let delivery = app
+        .ses_client()
+        .send_email(fixtures::email_message())
+        .await
+        .expect("email to be sent");
+
+    expect_that!(delivery.message_id, eq("0100018c-example"));
+    expect_that!(delivery.to, eq("kwame.nkrumah@example.africa"));
+
+    let requests = app
+        .email_server
+        .received_requests()
+        .await
+        .expect("requests to be recorded");
+    let authorization = requests[0]
+        .headers
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    expect_that!(
+        authorization,
+        starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/")
+    );
+    expect_that!(
+        authorization.contains("/af-south-1/ses/aws4_request"),
+        eq(true)
+    );
+}
+
+#[tokio::test]
+#[gtest]
+async fn falls_back_to_configured_sender() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/email/outbound-emails"))
+        .and(body_partial_json(json!({
+            "FromEmailAddress": app.config.from_email,
+        })))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({"MessageId": "0100018c-example"})),
+        )
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let mut message = fixtures::email_message();
+    message.from = None;
+    app.ses_client()
+        .send_email(message)
+        .await
+        .expect("email to be sent");
+}
+
+#[tokio::test]
+#[gtest]
+async fn rejected_message_returns_api_error() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/email/outbound-emails"))
+        .respond_with(
+            ResponseTemplate::new(400)
+                .insert_header("x-amzn-ErrorType", "MessageRejected")
+                .set_body_json(json!({"message": "Email address is not verified."})),
+        )
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let result = app.ses_client().send_email(fixtures::email_message()).await;
+
+    expect_that!(
+        result,
+        pat!(Err(pat!(Error::Api {
+            status: eq(&StatusCode::BAD_REQUEST),
+            message: eq("MessageRejected: Email address is not verified."),
+            ..
+        })))
+    );
+}
+
+#[tokio::test]
+#[gtest]
+async fn throttled_request_returns_rate_limit_error() {
+    let app = TestApp::spawn().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/email/outbound-emails"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("x-amzn-ErrorType", "TooManyRequestsException")
+                .insert_header("Retry-After", "2"),
+        )
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let result = app.ses_client().send_email(fixtures::email_message()).await;
+
+    expect_that!(
+        result,
+        pat!(Err(pat!(Error::RateLimitExceeded {
+            retry_after: eq(&Some(std::time::Duration::from_secs(2)))
+        })))
+    );
+}
@@ -0,0 +1,281 @@
+//! Integration tests with the SMTP transport
+
+#![cfg(feature = "smtp")]
+
+#[path = "smtp/server.rs"]
+mod server;
+
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use googletest::matchers::{anything, contains, contains_substring, ends_with, eq, err, not, pat};
+use googletest::{expect_that, gtest};
+use sendout::email::{Body, EmailMessage, Header, Mailbox, MessageDefaults};
+use sendout::error::{Error, ErrorClass};
+use sendout::smtp::{AuthMechanism, Credentials, Security, SmtpConfig, SmtpTransport};
+use sendout::{BatchEmailService, EmailService};
+
+use server::{Behavior, FakeSmtpServer};
+
+/// Creates a transport talking to the fake server
+fn transport(server: &FakeSmtpServer, security: Security) -> SmtpTransport {
+    let mut config = SmtpConfig::new("127.0.0.1", server.port, security).with_from(
+        "Wangari Maathai <wangari.maathai@example.africa>"
+            .parse::<Mailbox>()
+            .expect("a valid mailbox"),
+    );
+    config.timeout = Duration::from_secs(5);
+    SmtpTransport::new(config).expect("to create the transport")
+}
+
+/// Creates an email message without a sender
+fn email_message() -> EmailMessage {
+    EmailMessage {
+        from: None,
+        to: vec!["kwame.nkrumah@example.africa"].into(),
+        subject: "Green Belt Movement Monthly Update".to_owned(),
+        body: Body::Text("We planted 10,000 trees.\n.and kept counting".to_owned()),
+        cc: None,
+        bcc: Some(vec!["julius.nyerere@example.africa"].into()),
+        tag: None,
+        reply_to: None,
+        headers: None,
+        metadata: None,
+        attachments: None,
+        message_stream: None,
+        idempotency_key: None,
+    }
+}
+
+#[tokio::test]
+#[gtest]
+async fn sends_message_with_pipelining() {
+    let server = FakeSmtpServer::start(Behavior {
+        pipelining: true,
+        ..Behavior::default()
+    })
+    .await;
+    let transport = transport(&server, Security::None);
+
+    let delivery = transport
+        .send_email(email_message())
+        .await
+        .expect("email to be sent");
+
+    expect_that!(delivery.to, eq("kwame.nkrumah@example.africa"));
+    expect_that!(delivery.message_id, ends_with("@example.africa"));
+    expect_that!(delivery.submitted_at, ends_with("Z"));
+    expect_that!(delivery.message, contains_substring("queued as 8A3F"));
+    expect_that!(
+        server.commands(),
+        contains(eq("MAIL FROM:<wangari.maathai@example.africa>"))
+    );
+    expect_that!(
+        server.commands(),
+        contains(eq("RCPT TO:<julius.nyerere@example.africa>"))
+    );
+
+    let messages = server.messages();
+    expect_that!(messages.len(), eq(1));
+    expect_that!(
+        messages[0],
+        contains_substring(format!("Message-ID: <{}>", delivery.message_id))
+    );
+    expect_that!(messages[0], contains_substring("\r\n.and kept counting"));
+    expect_that!(messages[0], not(contains_substring("julius.nyerere")));
+}
+
+#[tokio::test]
+#[gtest]
+async fn sends_message_without_pipelining() {
+    let server = FakeSmtpServer::start(Behavior::default()).await;
+    let transport = transport(&server, Security::None);
+
+    transport
+        .send_email(email_message())
+        .await
+        .expect("email to be sent");
+
+    expect_that!(server.messages().len(), eq(1));
+}
+
+#[tokio::test]
+#[gtest]
+async fn applies_message_defaults() {
+    let server = FakeSmtpServer::start(Behavior::default()).await;
+    let transport = transport(&server, Security::None).with_defaults(MessageDefaults {
+        headers: vec![Header {
+            name: "X-Campaign".to_owned(),
+            value: "green-belt".to_owned(),
+        }],
+        ..MessageDefaults::default()
+    });
+
+    transport
+        .send_email(email_message())
+        .await
+        .expect("email to be sent");
+
+    let messages = server.messages();
+    expect_that!(messages.len(), eq(1));
+    expect_that!(
+        messages[0],
+        contains_substring("X-Campaign: green-belt\r\n")
+    );
+}
+
+#[tokio::test]
+#[gtest]
+async fn reuses_idle_connections() {
+    let server = FakeSmtpServer::start(Behavior {
+        pipelining: true,
+        ..Behavior::default()
+    })
+    .await;
+    let transport = transport(&server, Security::None);
+
+    let results = transport
+        .send_batch(vec![email_message(), email_message()])
+        .await
+        .expect("batch to be sent");
+
+    expect_that!(results.len(), eq(2));
+    expect_that!(results.iter().all(Result::is_ok), eq(true));
+    expect_that!(server.connections.load(Ordering::SeqCst), eq(1));
+    expect_that!(server.commands(), contains(eq("RSET")));
+}
+
+#[tokio::test]
+#[gtest]
+async fn authenticates_with_the_offered_mechanism() {
+    let server = FakeSmtpServer::start(Behavior {
+        auth: vec!["LOGIN"],
+        credentials: Some(("mailer", "harambee")),
+        ..Behavior::default()
+    })
+    .await;
+    let mut transport = transport(&server, Security::None);
+    transport.config.credentials = Some(Credentials::new("mailer", "harambee"));
+
+    transport
+        .send_email(email_message())
+        .await
+        .expect("email to be sent");
+
+    expect_that!(server.commands(), contains(eq("AUTH LOGIN")));
+}
+
+#[tokio::test]
+#[gtest]
+async fn rejected_credentials_are_a_configuration_error() {
+    let server = FakeSmtpServer::start(Behavior {
+        auth: vec!["PLAIN", "LOGIN"],
+        credentials: Some(("mailer", "harambee")),
+        ..Behavior::default()
+    })
+    .await;
+    let mut transport = transport(&server, Security::None);
+    transport.config.credentials = Some(Credentials::new("mailer", "wrong"));
+    transport.config.mechanisms = vec![AuthMechanism::Plain];
+
+    let error = transport
+        .send_email(email_message())
+        .await
+        .expect_err("login to fail");
+
+    expect_that!(error, pat!(Error::Smtp { code: eq(&535), .. }));
+    expect_that!(error.class(), eq(ErrorClass::Configuration));
+}
+
+#[tokio::test]
+#[gtest]
+async fn rejected_recipient_keeps_the_connection_usable() {
+    let server = FakeSmtpServer::start(Behavior {
+        pipelining: true,
+        rejected_recipient: Some("kwame.nkrumah@example.africa"),
+        ..Behavior::default()
+    })
+    .await;
+    let transport = transport(&server, Security::None);
+
+    let mut email = email_message();
+    email.bcc = None;
+    let result = transport.send_email(email).await;
+    expect_that!(
+        result,
+        err(pat!(Error::InvalidRecipient(contains_substring(
+            "kwame.nkrumah@example.africa"
+        ))))
+    );
+
+    let mut email = email_message();
+    email.to = vec!["julius.nyerere@example.africa"].into();
+    email.bcc = None;
+    transport.send_email(email).await.expect("email to be sent");
+    expect_that!(server.connections.load(Ordering::SeqCst), eq(1));
+}
+
+#[tokio::test]
+#[gtest]
+async fn rejects_recipients_smuggling_commands() {
+    let server = FakeSmtpServer::start(Behavior {
+        pipelining: true,
+        ..Behavior::default()
+    })
+    .await;
+    let transport = transport(&server, Security::None);
+
+    let mut email = email_message();
+    email.bcc = Some(vec!["julius.nyerere@example.africa>\r\nRCPT TO:<evil@attacker.com"].into());
+    let result = transport.send_email(email).await;
+
+    expect_that!(result, err(pat!(Error::InvalidRecipient(anything()))));
+    expect_that!(
+        server.commands(),
+        not(contains(contains_substring("evil@attacker.com")))
+    );
+    expect_that!(server.messages().len(), eq(0));
+}
+
+#[tokio::test]
+#[gtest]
+async fn refuses_to_send_in_clear_without_starttls() {
+    let server = FakeSmtpServer::start(Behavior::default()).await;
+    let transport = transport(&server, Security::StartTls);
+
+    let result = transport.send_email(email_message()).await;
+
+    expect_that!(result, err(pat!(Error::ConfigError(anything()))));
+    expect_that!(server.commands(), contains(eq("EHLO localhost")));
+    expect_that!(server.messages().len(), eq(0));
+}
+
+#[tokio::test]
+#[gtest]
+async fn failed_starttls_never_falls_back_to_clear_text() {
+    let server = FakeSmtpServer::start(Behavior {
+        starttls: true,
+        ..Behavior::default()
+    })
+    .await;
+    let transport = transport(&server, Security::StartTls);
+
+    let result = transport.send_email(email_message()).await;
+
+    expect_that!(result, err(pat!(Error::Smtp { code: eq(&454), .. })));
+    expect_that!(server.commands(), contains(eq("STARTTLS")));
+    expect_that!(server.commands().len(), eq(2));
+}
+
+#[tokio::test]
+#[gtest]
+async fn message_without_sender_fails() {
+    let server = FakeSmtpServer::start(Behavior::default()).await;
+    let mut transport = transport(&server, Security::None);
+    transport.config.from = None;
+
+    let result = transport.send_email(email_message()).await;
+
+    expect_that!(result, err(pat!(Error::ConfigError(anything()))));
+    expect_that!(server.connections.load(Ordering::SeqCst), eq(0));
+}
@@ -0,0 +1,246 @@
+//! In-process SMTP server standing in for a relay
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use base64::prelude::{BASE64_STANDARD, Engine};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+/// How the server behaves
+#[derive(Debug, Clone, Default)]
+pub struct Behavior {
+    /// Advertise `PIPELINING`
+    pub pipelining: bool,
+    /// Advertise `STARTTLS`
+    pub starttls: bool,
+    /// Advertised `AUTH` mechanisms
+    pub auth: Vec<&'static str>,
+    /// Expected `(username, password)`
+    pub credentials: Option<(&'static str, &'static str)>,
+    /// Recipient rejected with a `550` reply
+    pub rejected_recipient: Option<&'static str>,
+}
+
+/// A running fake server
+pub struct FakeSmtpServer {
+    /// Port the server listens on
+    pub port: u16,
+    /// Number of accepted connections
+    pub connections: Arc<AtomicUsize>,
+    /// Every command received, in order
+    pub commands: Arc<Mutex<Vec<String>>>,
+    /// Content of the messages received, unstuffed
+    pub messages: Arc<Mutex<Vec<String>>>,
+}
+
+impl FakeSmtpServer {
+    /// Starts a server on a random local port
+    pub async fn start(behavior: Behavior) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("to bind a local port");
+        let port = listener.local_addr().expect("a local address").port();
+        let server = Self {
+            port,
+            connections: Arc::default(),
+            commands: Arc::default(),
+            messages: Arc::default(),
+        };
+
+        let session = Session {
+            behavior,
+            commands: Arc::clone(&server.commands),
+            messages: Arc::clone(&server.messages),
+        };
+        let connections = Arc::clone(&server.connections);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                connections.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(session.clone().run(stream));
+            }
+        });
+        server
+    }
+
+    /// Returns the commands received so far
+    pub fn commands(&self) -> Vec<String> {
+        self.commands
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Returns the messages received so far
+    pub fn messages(&self) -> Vec<String> {
+        self.messages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+/// State shared by the handlers of every connection
+#[derive(Clone)]
+struct Session {
+    /// How the server behaves
+    behavior: Behavior,
+    /// Every command received
+    commands: Arc<Mutex<Vec<String>>>,
+    /// Messages received
+    messages: Arc<Mutex<Vec<String>>>,
+}
+
+impl Session {
+    /// Talks SMTP on one connection until the client quits
+    async fn run(self, stream: TcpStream) {
+        let mut stream = BufReader::new(stream);
+        let mut accepted_recipients = 0;
+        stream
+            .get_mut()
+            .write_all(b"220 relay.example.africa ESMTP\r\n")
+            .await
+            .expect("to greet");
+
+        while let Some(line) = read_line(&mut stream).await {
+            self.commands
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(line.clone());
+            let verb = line
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_ascii_uppercase();
+
+            let reply = match verb.as_str() {
+                "EHLO" => self.ehlo_reply(),
+                "MAIL" => {
+                    accepted_recipients = 0;
+                    "250 2.1.0 OK\r\n".to_owned()
+                }
+                "RCPT" => {
+                    let rejected = self
+                        .behavior
+                        .rejected_recipient
+                        .is_some_and(|rcpt| line.contains(&format!("<{rcpt}>")));
+                    if rejected {
+                        "550 5.1.1 mailbox unavailable\r\n".to_owned()
+                    } else {
+                        accepted_recipients += 1;
+                        "250 2.1.5 OK\r\n".to_owned()
+                    }
+                }
+                "DATA" if accepted_recipients == 0 => {
+                    "554 5.5.1 no valid recipients\r\n".to_owned()
+                }
+                "DATA" => {
+                    write(&mut stream, "354 end data with <CR><LF>.<CR><LF>\r\n").await;
+                    let message = read_data(&mut stream).await;
+                    self.messages
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .push(message);
+                    "250 2.0.0 OK queued as 8A3F\r\n".to_owned()
+                }
+                "AUTH" => self.authenticate(&line, &mut stream).await,
+                "STARTTLS" => "454 4.7.0 TLS not available\r\n".to_owned(),
+                "RSET" => "250 2.0.0 OK\r\n".to_owned(),
+                "QUIT" => {
+                    write(&mut stream, "221 2.0.0 bye\r\n").await;
+                    return;
+                }
+                _ => "502 5.5.2 command not recognized\r\n".to_owned(),
+            };
+            write(&mut stream, &reply).await;
+        }
+    }
+
+    /// Builds the multiline `EHLO` reply
+    fn ehlo_reply(&self) -> String {
+        let mut lines = vec!["relay.example.africa".to_owned()];
+        if self.behavior.pipelining {
+            lines.push("PIPELINING".to_owned());
+        }
+        if self.behavior.starttls {
+            lines.push("STARTTLS".to_owned());
+        }
+        if !self.behavior.auth.is_empty() {
+            lines.push(format!("AUTH {}", self.behavior.auth.join(" ")));
+        }
+        lines.push("8BITMIME".to_owned());
+
+        let last = lines.len() - 1;
+        lines
+            .iter()
+            .enumerate()
+            .map(|(idx, line)| {
+                let separator = if idx == last { ' ' } else { '-' };
+                format!("250{separator}{line}\r\n")
+            })
+            .collect()
+    }
+
+    /// Runs an `AUTH PLAIN` or `AUTH LOGIN` exchange
+    async fn authenticate(&self, line: &str, stream: &mut BufReader<TcpStream>) -> String {
+        let mut words = line.split_whitespace().skip(1);
+        let mechanism = words.next().unwrap_or_default().to_ascii_uppercase();
+        let given = match mechanism.as_str() {
+            "PLAIN" => {
+                let decoded = decode(words.next().unwrap_or_default());
+                let mut fields = decoded.split('\0').skip(1);
+                let username = fields.next().unwrap_or_default().to_owned();
+                let password = fields.next().unwrap_or_default().to_owned();
+                (username, password)
+            }
+            "LOGIN" => {
+                write(stream, "334 VXNlcm5hbWU6\r\n").await;
+                let username = decode(&read_line(stream).await.unwrap_or_default());
+                write(stream, "334 UGFzc3dvcmQ6\r\n").await;
+                let password = decode(&read_line(stream).await.unwrap_or_default());
+                (username, password)
+            }
+            _ => return "504 5.5.4 unrecognized authentication type\r\n".to_owned(),
+        };
+
+        let expected = self.behavior.credentials.unwrap_or_default();
+        if given.0 == expected.0 && given.1 == expected.1 {
+            "235 2.7.0 authentication successful\r\n".to_owned()
+        } else {
+            "535 5.7.8 authentication credentials invalid\r\n".to_owned()
+        }
+    }
+}
+
+/// Reads one line without its line ending, `None` once the client hangs up
+async fn read_line(stream: &mut BufReader<TcpStream>) -> Option<String> {
+    let mut line = String::new();
+    match stream.read_line(&mut line).await {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line.trim_end_matches(['\r', '\n']).to_owned()),
+    }
+}
+
+/// Reads message content up to the lone `.` line, removing dot-stuffing
+async fn read_data(stream: &mut BufReader<TcpStream>) -> String {
+    let mut message = String::new();
+    while let Some(line) = read_line(stream).await {
+        if line == "." {
+            break;
+        }
+        message.push_str(line.strip_prefix('.').unwrap_or(&line));
+        message.push_str("\r\n");
+    }
+    message
+}
+
+/// Writes a reply
+async fn write(stream: &mut BufReader<TcpStream>, reply: &str) {
+    let _ = stream.get_mut().write_all(reply.as_bytes()).await;
+}
+
+/// Decodes a base64 line sent during authentication
+fn decode(encoded: &str) -> String {
+    let bytes = BASE64_STANDARD.decode(encoded).unwrap_or_default();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
@@ -0,0 +1,135 @@
+//! Integration tests with `tower` services
+
+#![cfg(all(feature = "postmark", feature = "tower"))]
+
+mod fixtures;
+
+use std::convert::Infallible;
+use std::time::Duration;
+
+use bytes::Bytes;
+use googletest::matchers::{anything, eq, pat, some};
+use googletest::{expect_that, gtest};
+use http::{Request, Response, StatusCode};
+use sendout::EmailService;
+use sendout::config::ServiceConfig;
+use sendout::email::EmailDelivery;
+use sendout::error::Error;
+use sendout::postmark::PostmarkClient;
+use sendout::tower::{EmailServiceAdapter, TowerClient};
+use serde_json::json;
+use tower::{ServiceBuilder, ServiceExt, service_fn};
+
+fn config() -> ServiceConfig {
+    ServiceConfig {
+        base_url: "https://api.postmarkapp.com".into(),
+        server_token: "server-token".to_owned().into(),
+        account_token: None,
+        from_email: "sender@example.africa".into(),
+    }
+}
+
+/// Answers like Postmark, after checking the server token
+async fn postmark(request: Request<Bytes>) -> Result<Response<Bytes>, Infallible> {
+    let authorized = request
+        .headers()
+        .get("X-Postmark-Server-Token")
+        .is_some_and(|token| token == "server-token");
+    let (status, body) = if authorized && request.uri().path() == "/email" {
+        (
+            StatusCode::OK,
+            json!({
+                "To": "kwame.nkrumah@example.africa",
+                "SubmittedAt": "2026-10-16T08:30:00Z",
+                "MessageID": "b7bc2f4a-e38e-4336-af7d-e6c392c2f817",
+                "ErrorCode": 0,
+                "Message": "OK",
+            }),
+        )
+    } else {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            json!({"ErrorCode": 10, "Message": "Bad or missing API token"}),
+        )
+    };
+    Ok(Response::builder()
+        .status(status)
+        .body(Bytes::from(body.to_string()))
+        .expect("a valid response"))
+}
+
+#[tokio::test]
+#[gtest]
+async fn sends_through_layered_http_service() {
+    let service = ServiceBuilder::new()
+        .timeout(Duration::from_secs(5))
+        .concurrency_limit(4)
+        .service(service_fn(postmark));
+    let client = PostmarkClient::new(TowerClient::new(service), config());
+
+    let delivery = client
+        .send_email(fixtures::email_message())
+        .await
+        .expect("email to be sent");
+
+    expect_that!(
+        delivery.message_id,
+        eq("b7bc2f4a-e38e-4336-af7d-e6c392c2f817")
+    );
+}
+
+#[tokio::test]
+#[gtest]
+async fn maps_error_responses() {
+    let service = service_fn(|_request: Request<Bytes>| async {
+        Ok::<_, Infallible>(
+            Response::builder()
+                .status(StatusCode::TOO_MANY_REQUESTS)
+                .header("Retry-After", "3")
+                .body(Bytes::new())
+                .expect("a valid response"),
+        )
+    });
+    let client = PostmarkClient::new(TowerClient::new(service), config());
+
+    let result = client.send_email(fixtures::email_message()).await;
+
+    expect_that!(
+        result,
+        pat!(Err(pat!(Error::RateLimitExceeded {
+            retry_after: some(eq(&Duration::from_secs(3)))
+        })))
+    );
+}
+
+#[tokio::test]
+#[gtest]
+async fn timeouts_become_send_failures() {
+    let service = ServiceBuilder::new()
+        .timeout(Duration::from_millis(10))
+        .service(service_fn(|request: Request<Bytes>| async move {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            postmark(request).await
+        }));
+    let client = PostmarkClient::new(TowerClient::new(service), config());
+
+    let result = client.send_email(fixtures::email_message()).await;
+
+    expect_that!(result, pat!(Err(pat!(Error::SendFailed(anything())))));
+}
+
+#[tokio::test]
+#[gtest]
+async fn email_service_composes_with_layers() {
+    let client = PostmarkClient::new(TowerClient::new(service_fn(postmark)), config());
+    let service = ServiceBuilder::new()
+        .timeout(Duration::from_secs(5))
+        .service(EmailServiceAdapter::new(client));
+
+    let delivery: EmailDelivery = service
+        .oneshot(fixtures::email_message())
+        .await
+        .expect("email to be sent");
+
+    expect_that!(delivery.to, eq("kwame.nkrumah@example.africa"));
+}